@@ -1,5 +1,22 @@
 use std::time::Instant;
 
+/// Steps of the top/htop signal menu popup.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SignalStage {
+    Menu,    // choose a signal from the list
+    Custom,  // type an arbitrary signal number
+    Confirm, // confirm sending the chosen signal
+    Result,  // show the outcome (with optional sudo retry)
+}
+
+/// Which scheduling setting the top/htop tuning popup edits.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TuneMode {
+    Nice,     // renice
+    Affinity, // per-core CPU checklist
+    IoPrio,   // I/O scheduling class and level
+}
+
 /// Global application state shared between the draw loop and input handler.
 ///
 /// Fields capture the current UI selection and popup states, as well as cached
@@ -10,21 +27,60 @@ pub struct App {
     pub selected_proc_tab: usize, // reserved (no Process tab)
     // Help popup state
     pub help_popup: bool,
-    // Network rate tracking (iface -> (rx_bytes, tx_bytes) and computed rates in bytes/sec)
-    pub net_prev: std::collections::HashMap<String, (u64, u64)>,
-    pub net_rates: std::collections::HashMap<String, (f64, f64)>,
-    pub net_last: Instant,
+    // Snapshots from the background collectors (see Collectors in main.rs)
+    pub net_rates: std::collections::HashMap<String, (f64, f64)>, // iface -> (rx, tx) bytes/sec
+    pub cpu_temp_c: Option<f32>,
+    pub disks: Vec<super::DiskInfo>,
+    pub apps_status: Vec<(String, String, String)>, // (application, active, installed)
+    pub log_files: Vec<super::LogEntry>,     // Logs tab: files under /var/log
+    pub journal_files: Vec<super::LogEntry>, // Journal tab: files under /var/log/journal
     // Cached GPU detection (best-effort; computed once on startup)
     pub gpus: Vec<super::GpuInfo>,
+    // Cached DMI manufacturer and model for the header (read once on startup)
+    pub hw_model: (Option<String>, Option<String>),
+    pub uid_names: std::collections::HashMap<u32, String>, // /etc/passwd, read at startup
     // Embedded shell session (PTY) for the Shell tab
     pub shell: Option<super::shell::ShellSession>,
     // Services tab state
     pub services_scroll: usize, // top visible row index
     pub services_selected: usize, // absolute selected row index
+    // Cached service list (from the ServiceCollector thread) and a request to reload it after an action
+    pub services: Vec<super::services::UnitInfo>,
+    pub services_dirty: bool,
+    pub service_details_wanted: Option<String>, // Enter on a unit: details to request from the services collector
+    pub services_source: &'static str, // where the unit list came from: D-Bus, systemctl or unsupported
+    pub services_usage: std::collections::HashMap<String, super::services::UnitUsage>, // per-unit cgroup usage
+    pub services_sort_key: super::services::ServiceColumn,
+    pub services_sort_desc: bool,
+    pub services_filter: super::services::ServiceFilter,
+    pub services_type: usize, // index into services::UNIT_TYPES
+    pub services_user: bool,  // list the current user's manager (systemctl --user)
+    pub services_search_prompt: bool, // editing the name search
+    pub services_search: String,      // case-insensitive unit name substring
+    // Failed system units for the Dashboard alert panel (collected while the Dashboard is shown)
+    pub failed_units: Vec<super::services::FailedUnit>,
     // Service details popup state
     pub service_popup: bool,
     pub service_detail_title: String,
-    pub service_detail_text: String,
+    pub service_detail_text: String, // Status tab (details or unit action output)
+    pub service_detail_user: bool,     // unit belongs to the user manager
+    pub service_detail_tab: usize,     // index into services::SERVICE_DETAIL_TABS
+    pub service_detail_scroll: usize,
+    pub service_tab_text: String,      // Unit file / Properties tab text
+    pub service_deps: Vec<super::services::DepRow>,
+    pub service_deps_error: String,
+    pub service_deps_root: String,     // unit whose dependency tree is shown
+    pub service_deps_history: Vec<String>, // previous roots (Backspace goes back)
+    pub service_deps_selected: usize,
+    pub service_deps_reverse: bool,    // show what pulls the unit in
+    // Unit action menu state (Services tab): pick an action, confirm, then the
+    // systemctl output is shown in the service popup
+    pub service_action_popup: bool,
+    pub service_action_confirm: bool,
+    pub service_action_selected: usize, // index into SERVICE_ACTIONS
+    pub service_action_unit: String,
+    pub service_action_sudo_pending: bool, // sudo password prompt was opened for a unit action
+    pub service_action_job: Option<std::sync::mpsc::Receiver<super::ServiceActionDone>>, // action running on a worker
     // Processes (top/htop) tab state
     pub procs_scroll: usize, // top visible row index
     pub procs_selected: usize, // absolute selected row index among sorted list
@@ -32,8 +88,80 @@ pub struct App {
     pub process_popup: bool,
     pub process_detail_title: String,
     pub process_detail_text: String,
-    // Cached list of process PIDs sorted by CPU (rebuilt each tick)
+    pub process_detail_pid: i32,
+    pub process_detail_tab: usize, // index into PROCESS_DETAIL_TABS
+    pub process_detail_scroll: usize,
+    // Live samples while the details popup is open (one per tick), and whether the PID is gone
+    pub process_detail_cpu_hist: Vec<f32>,
+    pub process_detail_rss_hist: Vec<u64>,
+    pub process_detail_exited: bool,
+    // Thread view popup state (top/htop): per-thread rows refreshed each tick, with
+    // CPU% computed from the utime+stime delta since the previous sample
+    pub threads_popup: bool,
+    pub threads_pid: i32,
+    pub threads_title: String,
+    pub threads_rows: Vec<super::processes::ThreadInfo>,
+    pub threads_cpu: std::collections::HashMap<i32, f64>,
+    pub threads_prev: std::collections::HashMap<i32, u64>,
+    pub threads_last: Instant,
+    pub threads_exited: bool,
+    pub threads_scroll: usize,
+    // Cached list of process PIDs in display order (rebuilt each tick)
     pub procs_pids_sorted: Vec<i32>,
+    // Process table sort column and direction
+    pub procs_sort_key: super::processes::ProcColumn,
+    pub procs_sort_desc: bool,
+    // Process table columns in display order (persisted in the config file)
+    pub procs_columns: Vec<super::processes::ProcColumn>,
+    // READ/s and WRITE/s columns: rates in bytes/sec from the system collector
+    // (PIDs whose io file is unreadable are absent)
+    pub procs_io_rates: std::collections::HashMap<i32, (f64, f64)>,
+    // Process filter ("/" prompt): raw input, parsed filter, parse error, and which
+    // rows of procs_pids_sorted match (tree mode also keeps non-matching ancestors)
+    pub procs_filter_prompt: bool,
+    pub procs_filter_input: String,
+    pub procs_filter: Option<super::processes::ProcFilter>,
+    pub procs_filter_error: String,
+    pub procs_match_flags: Vec<bool>,
+    // Process tree mode: rows aligned with procs_pids_sorted, PIDs whose subtree is collapsed,
+    // and each PID's parent as read by the system collector
+    pub procs_tree_mode: bool,
+    pub procs_tree_rows: Vec<super::processes::ProcTreeRow>,
+    pub procs_collapsed: std::collections::HashSet<i32>,
+    pub procs_ppids: std::collections::HashMap<i32, Option<i32>>,
+    pub procs_sort_values: Option<(super::processes::ProcColumn, std::collections::HashMap<i32, super::ProcSortVal>)>, // sort column read from /proc
+    // Column chooser popup state (top/htop): every column with its visibility, in display order
+    pub columns_popup: bool,
+    pub columns_edit: Vec<(super::processes::ProcColumn, bool)>,
+    pub columns_cursor: usize,
+    pub columns_error: String,
+    // Signal menu popup state (top/htop)
+    pub signal_popup: bool,
+    pub signal_stage: SignalStage,
+    pub signal_selected: usize, // index into SIGNALS; SIGNALS.len() is the custom entry
+    pub signal_custom_input: String,
+    pub signal_pid: i32,
+    pub signal_proc_name: String,
+    pub signal_number: i32, // signal awaiting confirmation or last sent
+    pub signal_result: String,
+    pub signal_denied: bool, // last attempt failed with EPERM (offer sudo)
+    pub signal_sudo_pending: bool, // sudo password prompt was opened for a signal
+    // Scheduling popup state (top/htop: renice, CPU affinity, I/O priority)
+    pub tune_popup: bool,
+    pub tune_mode: TuneMode,
+    pub tune_pid: i32,
+    pub tune_proc_name: String,
+    pub tune_all_threads: bool,
+    pub tune_nice: i32,
+    pub tune_cpus: Vec<bool>,
+    pub tune_cpu_cursor: usize,
+    pub tune_io_class: u8, // index into IO_CLASSES
+    pub tune_io_level: u8,
+    pub tune_result: String, // empty while editing; outcome after applying
+    pub tune_denied: bool, // last attempt failed with EPERM (offer sudo)
+    pub tune_sudo_pending: bool, // sudo password prompt was opened for a tuning change
+    // Number of CPUs reported by sysinfo (refreshed each tick)
+    pub cpu_count: usize,
     // Logs tab state
     pub logs_scroll: usize,
     pub logs_selected: usize,
@@ -43,14 +171,43 @@ pub struct App {
     // Log content popup state (reused for Journal)
     pub log_popup: bool,
     pub log_detail_title: String,
-    pub log_detail_text: String,
-    pub log_popup_scroll: usize,
-    // Sudo password prompt state for Logs/Journal
+    pub log_doc: super::logview::LogDoc, // paged from disk
+    pub log_top: u64,                     // offset of the first line shown
+    pub log_severity: Option<super::logview::Severity>, // hide less severe lines (files; unit journals filter in journalctl)
+    // Follow mode (new lines appended every second), for unit journals and /var/log files
+    pub log_follow: bool,
+    pub log_follow_last: Instant, // last follow-up read
+    pub log_file_path: Option<String>, // /var/log file shown in the popup, None for journals
+    pub log_stitched: bool,            // the file is shown with all its rotations
+    pub log_tail: Option<super::logview::LogTail>,
+    pub log_load: Option<super::logview::LogLoad>, // decompression (or sudo read) still filling log_doc
+    pub log_load_to_end: bool,                     // keep showing the end while it loads (R in the popup)
+    // Log popup search (/ regex, n/N)
+    pub log_search_prompt: bool,  // editing the pattern
+    pub log_search_input: String,
+    pub log_search_case: bool,    // case-sensitive (default: insensitive)
+    pub log_search: Option<super::logview::LogSearch>,
+    pub log_search_error: String,
+    pub log_search_current: Option<super::logview::LogMatch>,
+    pub log_search_pending: Option<bool>, // n/N still scanning for its match (Some(forward))
+    // Unit journal shown in the log popup (journalctl -u), None for log/journal files
+    pub log_journal_unit: Option<String>,
+    // Journal file shown in the log popup (journalctl --file); shares the priority filter
+    pub log_journal_file: Option<String>,
+    pub log_journal_user: bool,
+    pub log_journal_priority: usize, // index into services::JOURNAL_PRIORITIES
+    pub log_journal_boot: Option<i32>, // -b offset; None = all boots
+    pub log_journal_cursor: Option<String>,
+    pub log_journal_limited: bool, // journalctl hid entries we may not read
+    pub log_journal_sudo: bool,    // read through sudo
+    pub log_journal_sudo_pending: bool, // sudo password prompt was opened for the journal
+    // Sudo password prompt state (Logs/Journal, and process signals)
     pub logs_password_prompt: bool,
     pub logs_password_input: String,
     pub logs_password_error: String,
     pub logs_sudo_password: Option<String>,
     pub logs_pending_path: String, // path awaiting sudo read (log or journal)
+    pub logs_pending_stitched: bool, // ... to be shown with its rotations
 }
 
 /// Construct the initial application state.
@@ -60,35 +217,151 @@ impl Default for App {
             selected_top_tab: 0,
             selected_proc_tab: 0,
             help_popup: false,
-            net_prev: std::collections::HashMap::new(),
             net_rates: std::collections::HashMap::new(),
-            net_last: Instant::now(),
+            cpu_temp_c: None,
+            disks: Vec::new(),
+            apps_status: Vec::new(),
+            log_files: Vec::new(),
+            journal_files: Vec::new(),
             gpus: Vec::new(),
+            hw_model: (None, None),
+            uid_names: std::collections::HashMap::new(),
             shell: None,
             services_scroll: 0,
             services_selected: 0,
+            services: Vec::new(),
+            services_dirty: false,
+            service_details_wanted: None,
+            services_source: "systemctl",
+            services_usage: std::collections::HashMap::new(),
+            services_sort_key: super::services::ServiceColumn::Unit,
+            services_sort_desc: false,
+            services_filter: super::services::ServiceFilter::All,
+            services_type: 0,
+            services_user: false,
+            services_search_prompt: false,
+            services_search: String::new(),
+            failed_units: Vec::new(),
             service_popup: false,
             service_detail_title: String::new(),
             service_detail_text: String::new(),
+            service_detail_user: false,
+            service_detail_tab: 0,
+            service_detail_scroll: 0,
+            service_tab_text: String::new(),
+            service_deps: Vec::new(),
+            service_deps_error: String::new(),
+            service_deps_root: String::new(),
+            service_deps_history: Vec::new(),
+            service_deps_selected: 0,
+            service_deps_reverse: false,
+            service_action_popup: false,
+            service_action_confirm: false,
+            service_action_selected: 0,
+            service_action_unit: String::new(),
+            service_action_sudo_pending: false,
+            service_action_job: None,
             procs_scroll: 0,
             procs_selected: 0,
             process_popup: false,
             process_detail_title: String::new(),
             process_detail_text: String::new(),
+            process_detail_pid: 0,
+            process_detail_tab: 0,
+            process_detail_scroll: 0,
+            process_detail_cpu_hist: Vec::new(),
+            process_detail_rss_hist: Vec::new(),
+            process_detail_exited: false,
+            threads_popup: false,
+            threads_pid: 0,
+            threads_title: String::new(),
+            threads_rows: Vec::new(),
+            threads_cpu: std::collections::HashMap::new(),
+            threads_prev: std::collections::HashMap::new(),
+            threads_last: Instant::now(),
+            threads_exited: false,
+            threads_scroll: 0,
             procs_pids_sorted: Vec::new(),
+            procs_sort_key: super::processes::ProcColumn::Cpu,
+            procs_sort_desc: true,
+            procs_columns: super::processes::ProcColumn::DEFAULT.to_vec(),
+            procs_io_rates: std::collections::HashMap::new(),
+            procs_filter_prompt: false,
+            procs_filter_input: String::new(),
+            procs_filter: None,
+            procs_filter_error: String::new(),
+            procs_match_flags: Vec::new(),
+            procs_tree_mode: false,
+            procs_tree_rows: Vec::new(),
+            procs_collapsed: std::collections::HashSet::new(),
+            procs_ppids: std::collections::HashMap::new(),
+            procs_sort_values: None,
+            columns_popup: false,
+            columns_edit: Vec::new(),
+            columns_cursor: 0,
+            columns_error: String::new(),
+            signal_popup: false,
+            signal_stage: SignalStage::Menu,
+            signal_selected: 0,
+            signal_custom_input: String::new(),
+            signal_pid: 0,
+            signal_proc_name: String::new(),
+            signal_number: 0,
+            signal_result: String::new(),
+            signal_denied: false,
+            signal_sudo_pending: false,
+            tune_popup: false,
+            tune_mode: TuneMode::Nice,
+            tune_pid: 0,
+            tune_proc_name: String::new(),
+            tune_all_threads: true,
+            tune_nice: 0,
+            tune_cpus: Vec::new(),
+            tune_cpu_cursor: 0,
+            tune_io_class: 0,
+            tune_io_level: 4,
+            tune_result: String::new(),
+            tune_denied: false,
+            tune_sudo_pending: false,
+            cpu_count: 0,
             logs_scroll: 0,
             logs_selected: 0,
             journal_scroll: 0,
             journal_selected: 0,
             log_popup: false,
             log_detail_title: String::new(),
-            log_detail_text: String::new(),
-            log_popup_scroll: 0,
+            log_doc: super::logview::LogDoc::default(),
+            log_top: 0,
+            log_severity: None,
+            log_follow: false,
+            log_follow_last: Instant::now(),
+            log_file_path: None,
+            log_stitched: false,
+            log_tail: None,
+            log_load: None,
+            log_load_to_end: false,
+            log_search_prompt: false,
+            log_search_input: String::new(),
+            log_search_case: false,
+            log_search: None,
+            log_search_error: String::new(),
+            log_search_current: None,
+            log_search_pending: None,
+            log_journal_unit: None,
+            log_journal_file: None,
+            log_journal_user: false,
+            log_journal_priority: 0,
+            log_journal_boot: Some(0),
+            log_journal_cursor: None,
+            log_journal_limited: false,
+            log_journal_sudo: false,
+            log_journal_sudo_pending: false,
             logs_password_prompt: false,
             logs_password_input: String::new(),
             logs_password_error: String::new(),
             logs_sudo_password: None,
             logs_pending_path: String::new(),
+            logs_pending_stitched: false,
         }
     }
 }
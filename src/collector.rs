@@ -0,0 +1,72 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+// -------- Background data collection --------
+// One worker thread per data source, refreshing on its own schedule and handing the newest
+// snapshot to the UI loop, which only ever takes what is ready and never waits on a worker.
+
+struct Shared<T> {
+    latest: Mutex<Option<T>>, // newest snapshot not yet taken by the UI
+    wake: Mutex<bool>,        // refresh requested (forced run)
+    wake_cv: Condvar,
+    enabled: AtomicBool,      // cleared while nobody looks at this data
+    stop: AtomicBool,
+}
+
+/// Handle to a worker thread that runs `work` every `interval` (or sooner when woken).
+/// Dropping the handle stops the worker after its current run.
+pub struct Collector<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T: Send + 'static> Collector<T> {
+    /// Start a worker named `rtop-<name>`. `work` receives `true` on the first run and
+    /// after `wake()`, and returns a snapshot to publish (or `None` if nothing changed).
+    pub fn spawn<F>(name: &str, interval: Duration, mut work: F) -> Self
+    where
+        F: FnMut(bool) -> Option<T> + Send + 'static,
+    {
+        let shared = Arc::new(Shared { latest: Mutex::new(None), wake: Mutex::new(true), wake_cv: Condvar::new(), enabled: AtomicBool::new(true), stop: AtomicBool::new(false) });
+        let worker = shared.clone();
+        let spawned = std::thread::Builder::new().name(format!("rtop-{}", name)).spawn(move || {
+            loop {
+                let forced = {
+                    let Ok(guard) = worker.wake.lock() else { return; };
+                    let Ok((mut woken, _)) = worker.wake_cv.wait_timeout_while(guard, interval, |w| !*w && !worker.stop.load(Ordering::Relaxed)) else { return; };
+                    std::mem::take(&mut *woken)
+                };
+                if worker.stop.load(Ordering::Relaxed) { return; }
+                if !worker.enabled.load(Ordering::Relaxed) { continue; }
+                if let Some(snapshot) = work(forced) && let Ok(mut latest) = worker.latest.lock() {
+                    *latest = Some(snapshot);
+                }
+            }
+        });
+        if spawned.is_err() { shared.stop.store(true, Ordering::Relaxed); }
+        Collector { shared }
+    }
+
+    /// Newest snapshot published since the last call, if any.
+    pub fn take(&self) -> Option<T> {
+        self.shared.latest.lock().ok()?.take()
+    }
+
+    /// Pause or resume the worker; resuming refreshes at once so the data is not stale.
+    pub fn set_enabled(&self, enabled: bool) {
+        if !self.shared.enabled.swap(enabled, Ordering::Relaxed) && enabled { self.wake(); }
+    }
+
+    /// Ask the worker to refresh now instead of waiting for its interval.
+    pub fn wake(&self) {
+        if let Ok(mut w) = self.shared.wake.lock() { *w = true; }
+        self.shared.wake_cv.notify_one();
+    }
+}
+
+impl<T> Drop for Collector<T> {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::Relaxed);
+        self.shared.wake_cv.notify_one();
+    }
+}
@@ -0,0 +1,56 @@
+use crate::processes::ProcColumn;
+
+// -------- Persistent settings (~/.config/rtop/config) --------
+// A small "key = value" file; unknown keys are ignored so older versions can read newer files.
+
+/// User settings persisted between runs.
+pub struct Config {
+    pub process_columns: Vec<ProcColumn>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self { process_columns: ProcColumn::DEFAULT.to_vec() }
+    }
+}
+
+/// Location of the config file: $XDG_CONFIG_HOME/rtop/config, else ~/.config/rtop/config.
+pub fn config_path() -> Option<std::path::PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()) {
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => std::path::PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("rtop").join("config"))
+}
+
+impl Config {
+    /// Load the config file, falling back to defaults for anything missing or invalid.
+    pub fn load() -> Self {
+        let mut cfg = Config::default();
+        let Some(text) = config_path().and_then(|p| std::fs::read_to_string(p).ok()) else { return cfg; };
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') { continue; }
+            let Some((key, value)) = line.split_once('=') else { continue; };
+            if key.trim() == "process_columns" {
+                let mut cols: Vec<ProcColumn> = Vec::new();
+                for c in value.split(',').filter_map(ProcColumn::from_config_name) {
+                    if !cols.contains(&c) { cols.push(c); }
+                }
+                if !cols.is_empty() { cfg.process_columns = cols; }
+            }
+        }
+        cfg
+    }
+
+    /// Write the config file, creating its directory if needed.
+    pub fn save(&self) -> Result<(), String> {
+        let path = config_path().ok_or_else(|| String::from("cannot locate config directory (HOME not set)"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        }
+        let cols: Vec<&str> = self.process_columns.iter().map(|c| c.config_name()).collect();
+        let text = format!("# rtop configuration\nprocess_columns = {}\n", cols.join(","));
+        std::fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
@@ -0,0 +1,587 @@
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::time::{Duration, Instant};
+
+use crate::processes::current_uid;
+
+// -------- Minimal D-Bus client (blocking, Unix socket, EXTERNAL auth; Linux only) --------
+// Just enough of the wire protocol for systemd: method calls with string arguments,
+// generic decoding of replies and signals, and match rules.
+
+/// Default system bus socket when DBUS_SYSTEM_BUS_ADDRESS is not set.
+const SYSTEM_BUS_DEFAULT: &str = "unix:path=/var/run/dbus/system_bus_socket";
+
+/// How long a method call waits for its reply.
+const CALL_TIMEOUT: Duration = Duration::from_secs(5);
+
+const MSG_METHOD_CALL: u8 = 1;
+const MSG_METHOD_RETURN: u8 = 2;
+const MSG_ERROR: u8 = 3;
+pub const MSG_SIGNAL: u8 = 4;
+
+/// A decoded D-Bus value. Strings, object paths and signatures all decode to Str.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Byte(u8),
+    Bool(bool),
+    I16(i16),
+    U16(u16),
+    I32(i32),
+    U32(u32),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Str(String),
+    Fd(u32),
+    Array(Vec<Value>),
+    Struct(Vec<Value>),
+    DictEntry(Box<Value>, Box<Value>),
+    Variant(Box<Value>),
+}
+
+impl Value {
+    /// The string inside a Str (looking through variants).
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            Value::Variant(v) => v.as_str(),
+            _ => None,
+        }
+    }
+
+    /// Any unsigned/non-negative integer as u64 (looking through variants).
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Byte(v) => Some(*v as u64),
+            Value::U16(v) => Some(*v as u64),
+            Value::U32(v) => Some(*v as u64),
+            Value::U64(v) => Some(*v),
+            Value::I16(v) if *v >= 0 => Some(*v as u64),
+            Value::I32(v) if *v >= 0 => Some(*v as u64),
+            Value::I64(v) if *v >= 0 => Some(*v as u64),
+            Value::Variant(v) => v.as_u64(),
+            _ => None,
+        }
+    }
+
+    /// Elements of an array or fields of a struct (looking through variants).
+    pub fn items(&self) -> &[Value] {
+        match self {
+            Value::Array(v) | Value::Struct(v) => v,
+            Value::Variant(v) => v.items(),
+            _ => &[],
+        }
+    }
+
+    /// Human-readable rendering for property listings.
+    pub fn display(&self) -> String {
+        match self {
+            Value::Byte(v) => v.to_string(),
+            Value::Bool(v) => if *v { String::from("yes") } else { String::from("no") },
+            Value::I16(v) => v.to_string(),
+            Value::U16(v) => v.to_string(),
+            Value::I32(v) => v.to_string(),
+            Value::U32(v) => v.to_string(),
+            Value::I64(v) => v.to_string(),
+            Value::U64(v) => v.to_string(),
+            Value::F64(v) => v.to_string(),
+            Value::Str(s) => s.clone(),
+            Value::Fd(v) => format!("fd {}", v),
+            Value::Array(v) => v.iter().map(|x| x.display()).collect::<Vec<_>>().join(" "),
+            Value::Struct(v) => format!("({})", v.iter().map(|x| x.display()).collect::<Vec<_>>().join(", ")),
+            Value::DictEntry(k, v) => format!("{}={}", k.display(), v.display()),
+            Value::Variant(v) => v.display(),
+        }
+    }
+}
+
+/// A received message: header fields of interest plus the decoded body.
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub msg_type: u8,
+    pub reply_serial: Option<u32>,
+    pub path: Option<String>,
+    pub interface: Option<String>,
+    pub member: Option<String>,
+    pub error_name: Option<String>,
+    pub body: Vec<Value>,
+}
+
+// -------- Wire format: decoding --------
+/// Split the first complete type off a signature ("a{sv}u" -> ("a{sv}", "u")).
+fn next_type(sig: &str) -> Option<(&str, &str)> {
+    let b = sig.as_bytes();
+    let first = *b.first()?;
+    let end = match first {
+        b'a' => 1 + next_type(&sig[1..])?.0.len(),
+        b'(' | b'{' => {
+            let close = if first == b'(' { b')' } else { b'}' };
+            let mut depth = 0usize;
+            let mut i = 0usize;
+            loop {
+                let c = *b.get(i)?;
+                if c == first { depth += 1; } else if c == close { depth -= 1; if depth == 0 { break i + 1; } }
+                i += 1;
+            }
+        }
+        _ => 1,
+    };
+    Some((&sig[..end], &sig[end..]))
+}
+
+fn alignment(t: u8) -> usize {
+    match t {
+        b'y' | b'g' | b'v' => 1,
+        b'n' | b'q' => 2,
+        b'x' | b't' | b'd' | b'(' | b'{' => 8,
+        _ => 4,
+    }
+}
+
+/// Containers and variants nested deeper than this are rejected (the spec allows 64).
+const MAX_DEPTH: usize = 64;
+
+fn bad(msg: &str) -> std::io::Error { std::io::Error::new(std::io::ErrorKind::InvalidData, msg.to_string()) }
+
+/// Cursor over a message buffer; positions are relative to the start of the message,
+/// so alignment padding works as on the wire.
+struct Reader<'a> { buf: &'a [u8], pos: usize, depth: usize }
+
+impl<'a> Reader<'a> {
+    fn align(&mut self, n: usize) { self.pos = self.pos.div_ceil(n) * n; }
+
+    fn take(&mut self, n: usize) -> std::io::Result<&'a [u8]> {
+        let s = self.buf.get(self.pos..self.pos + n).ok_or_else(|| bad("truncated D-Bus message"))?;
+        self.pos += n;
+        Ok(s)
+    }
+
+    fn u32(&mut self) -> std::io::Result<u32> {
+        self.align(4);
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap_or([0; 4])))
+    }
+
+    fn u64(&mut self) -> std::io::Result<u64> {
+        self.align(8);
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap_or([0; 8])))
+    }
+
+    fn string(&mut self, len: usize) -> std::io::Result<String> {
+        let s = String::from_utf8_lossy(self.take(len)?).into_owned();
+        self.take(1)?; // trailing NUL
+        Ok(s)
+    }
+
+    /// Decode one value of the single complete type `t`. Signatures come from the peer
+    /// (variants, the body signature), so anything but exactly one complete type is an error.
+    fn value(&mut self, t: &str) -> std::io::Result<Value> {
+        if !matches!(next_type(t), Some((_, ""))) { return Err(bad("bad signature")); }
+        if self.depth >= MAX_DEPTH { return Err(bad("D-Bus value nested too deeply")); }
+        self.depth += 1;
+        let v = self.value_of(t);
+        self.depth -= 1;
+        v
+    }
+
+    fn value_of(&mut self, t: &str) -> std::io::Result<Value> {
+        let code = t.as_bytes()[0];
+        Ok(match code {
+            b'y' => Value::Byte(self.take(1)?[0]),
+            b'b' => Value::Bool(self.u32()? != 0),
+            b'n' | b'q' => {
+                self.align(2);
+                let raw = u16::from_le_bytes(self.take(2)?.try_into().unwrap_or([0; 2]));
+                if code == b'n' { Value::I16(raw as i16) } else { Value::U16(raw) }
+            }
+            b'i' => Value::I32(self.u32()? as i32),
+            b'u' => Value::U32(self.u32()?),
+            b'h' => Value::Fd(self.u32()?),
+            b'x' => Value::I64(self.u64()? as i64),
+            b't' => Value::U64(self.u64()?),
+            b'd' => Value::F64(f64::from_bits(self.u64()?)),
+            b's' | b'o' => { let n = self.u32()? as usize; Value::Str(self.string(n)?) }
+            b'g' => { let n = self.take(1)?[0] as usize; Value::Str(self.string(n)?) }
+            b'v' => {
+                let n = self.take(1)?[0] as usize;
+                let sig = self.string(n)?;
+                Value::Variant(Box::new(self.value(&sig)?))
+            }
+            b'a' => {
+                let elem = &t[1..];
+                let len = self.u32()? as usize;
+                self.align(alignment(elem.as_bytes()[0]));
+                let end = self.pos.checked_add(len).filter(|&e| e <= self.buf.len()).ok_or_else(|| bad("truncated D-Bus message"))?;
+                let mut items = Vec::new();
+                while self.pos < end { items.push(self.value(elem)?); }
+                Value::Array(items)
+            }
+            b'(' => {
+                self.align(8);
+                let mut rest = &t[1..t.len() - 1];
+                if rest.is_empty() { return Err(bad("bad signature")); }
+                let mut fields = Vec::new();
+                while !rest.is_empty() {
+                    let (ft, r) = next_type(rest).ok_or_else(|| bad("bad signature"))?;
+                    fields.push(self.value(ft)?);
+                    rest = r;
+                }
+                Value::Struct(fields)
+            }
+            b'{' => {
+                self.align(8);
+                let inner = &t[1..t.len() - 1];
+                let (kt, vt) = next_type(inner).ok_or_else(|| bad("bad signature"))?;
+                let k = self.value(kt)?;
+                let v = self.value(vt)?;
+                Value::DictEntry(Box::new(k), Box::new(v))
+            }
+            _ => return Err(bad("unsupported D-Bus type")),
+        })
+    }
+
+    /// Decode a whole signature (several complete types).
+    fn values(&mut self, mut sig: &str) -> std::io::Result<Vec<Value>> {
+        let mut out = Vec::new();
+        while !sig.is_empty() {
+            let (t, rest) = next_type(sig).ok_or_else(|| bad("bad signature"))?;
+            out.push(self.value(t)?);
+            sig = rest;
+        }
+        Ok(out)
+    }
+}
+
+// -------- Wire format: encoding --------
+struct Writer { buf: Vec<u8> }
+
+impl Writer {
+    fn align(&mut self, n: usize) { while !self.buf.len().is_multiple_of(n) { self.buf.push(0); } }
+    fn u32(&mut self, v: u32) { self.align(4); self.buf.extend_from_slice(&v.to_le_bytes()); }
+    fn string(&mut self, s: &str) { self.u32(s.len() as u32); self.buf.extend_from_slice(s.as_bytes()); self.buf.push(0); }
+    fn signature(&mut self, s: &str) { self.buf.push(s.len() as u8); self.buf.extend_from_slice(s.as_bytes()); self.buf.push(0); }
+
+    /// One header field: (code, variant of type `sig` holding `s`).
+    fn field(&mut self, code: u8, sig: &str, s: &str) {
+        self.align(8);
+        self.buf.push(code);
+        self.signature(sig);
+        if sig == "g" { self.signature(s); } else { self.string(s); }
+    }
+}
+
+/// Encode a message whose body is a sequence of strings.
+fn encode_message(msg_type: u8, serial: u32, dest: Option<&str>, path: &str, iface: Option<&str>, member: &str, args: &[&str]) -> Vec<u8> {
+    let mut body = Writer { buf: Vec::new() };
+    for a in args { body.string(a); }
+    let mut w = Writer { buf: vec![b'l', msg_type, 0, 1] };
+    w.u32(body.buf.len() as u32);
+    w.u32(serial);
+    // Header fields array: length placeholder, then the fields (8-aligned structs)
+    w.u32(0);
+    let start = w.buf.len().div_ceil(8) * 8;
+    w.field(1, "o", path);
+    if let Some(i) = iface { w.field(2, "s", i); }
+    w.field(3, "s", member);
+    if let Some(d) = dest { w.field(6, "s", d); }
+    if !args.is_empty() { w.field(8, "g", &"s".repeat(args.len())); }
+    let len = (w.buf.len() - start) as u32;
+    w.buf[12..16].copy_from_slice(&len.to_le_bytes());
+    w.align(8);
+    w.buf.extend_from_slice(&body.buf);
+    w.buf
+}
+
+/// Parse a complete message (fixed header, header fields, body).
+fn decode_message(buf: &[u8]) -> std::io::Result<Message> {
+    if buf.first() != Some(&b'l') { return Err(bad("big-endian D-Bus messages are not supported")); }
+    let mut r = Reader { buf, pos: 12, depth: 0 };
+    let fields = r.value("a(yv)")?;
+    let mut msg = Message { msg_type: buf.get(1).copied().unwrap_or(0), reply_serial: None, path: None, interface: None, member: None, error_name: None, body: Vec::new() };
+    let mut signature = String::new();
+    for f in fields.items() {
+        let (Some(code), Some(val)) = (f.items().first().and_then(|c| c.as_u64()), f.items().get(1)) else { continue; };
+        match code {
+            1 => msg.path = val.as_str().map(String::from),
+            2 => msg.interface = val.as_str().map(String::from),
+            3 => msg.member = val.as_str().map(String::from),
+            4 => msg.error_name = val.as_str().map(String::from),
+            5 => msg.reply_serial = val.as_u64().map(|v| v as u32),
+            8 => signature = val.as_str().unwrap_or("").to_string(),
+            _ => {}
+        }
+    }
+    r.align(8);
+    msg.body = r.values(&signature)?;
+    Ok(msg)
+}
+
+// -------- Connection --------
+/// A blocking connection to a message bus.
+pub struct Connection {
+    stream: UnixStream,
+    serial: u32,
+    pending: VecDeque<Message>, // signals that arrived while waiting for a reply
+}
+
+impl Connection {
+    /// Connect to the system bus (DBUS_SYSTEM_BUS_ADDRESS, e.g. a private test bus, or the default socket).
+    pub fn system() -> std::io::Result<Self> {
+        let addr = std::env::var("DBUS_SYSTEM_BUS_ADDRESS").unwrap_or_else(|_| SYSTEM_BUS_DEFAULT.to_string());
+        Self::open(&addr)
+    }
+
+    /// Connect to the session bus (DBUS_SESSION_BUS_ADDRESS, else $XDG_RUNTIME_DIR/bus or /run/user/<uid>/bus).
+    pub fn session() -> std::io::Result<Self> {
+        if let Ok(addr) = std::env::var("DBUS_SESSION_BUS_ADDRESS") { return Self::open(&addr); }
+        let dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| format!("/run/user/{}", current_uid()));
+        Self::open(&format!("unix:path={}/bus", dir))
+    }
+
+    /// Connect to a bus address ("unix:path=..." or "unix:abstract=..."; the first usable entry wins).
+    pub fn open(address: &str) -> std::io::Result<Self> {
+        let mut last_err = bad("no usable unix: bus address");
+        for entry in address.split(';') {
+            let Some(params) = entry.strip_prefix("unix:") else { continue; };
+            let kv: Vec<(&str, &str)> = params.split(',').filter_map(|p| p.split_once('=')).collect();
+            let get = |k: &str| kv.iter().find(|(key, _)| *key == k).map(|(_, v)| *v);
+            let res = if let Some(path) = get("path") {
+                UnixStream::connect(path)
+            } else if let Some(name) = get("abstract") {
+                connect_abstract(name)
+            } else {
+                continue;
+            };
+            match res.and_then(Self::handshake) {
+                Ok(c) => return Ok(c),
+                Err(e) => last_err = e,
+            }
+        }
+        Err(last_err)
+    }
+
+    /// SASL EXTERNAL authentication followed by the mandatory Hello call.
+    fn handshake(mut stream: UnixStream) -> std::io::Result<Self> {
+        stream.set_read_timeout(Some(CALL_TIMEOUT))?;
+        let uid = current_uid().to_string();
+        let hex: String = uid.bytes().map(|b| format!("{:02x}", b)).collect();
+        stream.write_all(format!("\0AUTH EXTERNAL {}\r\n", hex).as_bytes())?;
+        let mut line = Vec::new();
+        let mut byte = [0u8; 1];
+        while !line.ends_with(b"\r\n") {
+            if stream.read(&mut byte)? == 0 { return Err(bad("bus closed during authentication")); }
+            line.push(byte[0]);
+            if line.len() > 512 { return Err(bad("bad authentication reply")); }
+        }
+        if !line.starts_with(b"OK ") { return Err(std::io::Error::new(std::io::ErrorKind::PermissionDenied, "D-Bus authentication rejected")); }
+        stream.write_all(b"BEGIN\r\n")?;
+        let mut conn = Connection { stream, serial: 0, pending: VecDeque::new() };
+        conn.call("org.freedesktop.DBus", "/org/freedesktop/DBus", "org.freedesktop.DBus", "Hello", &[])?;
+        Ok(conn)
+    }
+
+    /// Call a method with string arguments and wait for its reply body.
+    /// D-Bus error replies become io::Error with the error name and message.
+    pub fn call(&mut self, dest: &str, path: &str, iface: &str, member: &str, args: &[&str]) -> std::io::Result<Vec<Value>> {
+        self.serial += 1;
+        let serial = self.serial;
+        self.stream.write_all(&encode_message(MSG_METHOD_CALL, serial, Some(dest), path, Some(iface), member, args))?;
+        self.stream.set_read_timeout(Some(CALL_TIMEOUT))?;
+        loop {
+            let msg = self.read_message()?;
+            if msg.reply_serial == Some(serial) && msg.msg_type == MSG_METHOD_RETURN { return Ok(msg.body); }
+            if msg.reply_serial == Some(serial) && msg.msg_type == MSG_ERROR {
+                let text = msg.body.first().and_then(|v| v.as_str()).unwrap_or("").to_string();
+                return Err(std::io::Error::other(format!("{}: {}", msg.error_name.unwrap_or_default(), text)));
+            }
+            if msg.msg_type == MSG_SIGNAL { self.pending.push_back(msg); }
+        }
+    }
+
+    /// Ask the bus to route signals matching `rule` to this connection.
+    pub fn add_match(&mut self, rule: &str) -> std::io::Result<()> {
+        self.call("org.freedesktop.DBus", "/org/freedesktop/DBus", "org.freedesktop.DBus", "AddMatch", &[rule]).map(|_| ())
+    }
+
+    /// Wait up to `timeout` for the next signal (replies to earlier calls are skipped);
+    /// `Ok(None)` if none arrived in time.
+    pub fn next_signal(&mut self, timeout: Duration) -> std::io::Result<Option<Message>> {
+        if let Some(m) = self.pending.pop_front() { return Ok(Some(m)); }
+        let deadline = Instant::now() + timeout;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() { return Ok(None); }
+            // Only the first byte waits on `timeout`; once a message starts, read all of it
+            let mut fixed = [0u8; 16];
+            self.stream.set_read_timeout(Some(left))?;
+            match self.stream.read(&mut fixed[..1]) {
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(_) => {}
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => return Ok(None),
+                Err(e) => return Err(e),
+            }
+            self.stream.set_read_timeout(Some(CALL_TIMEOUT))?;
+            self.stream.read_exact(&mut fixed[1..])?;
+            let msg = self.finish_message(fixed)?;
+            if msg.msg_type == MSG_SIGNAL { return Ok(Some(msg)); }
+        }
+    }
+
+    fn read_message(&mut self) -> std::io::Result<Message> {
+        let mut fixed = [0u8; 16];
+        self.stream.read_exact(&mut fixed)?;
+        self.finish_message(fixed)
+    }
+
+    /// Read the rest of a message whose fixed 16-byte header is already in hand.
+    fn finish_message(&mut self, fixed: [u8; 16]) -> std::io::Result<Message> {
+        let body_len = u32::from_le_bytes(fixed[4..8].try_into().unwrap_or([0; 4])) as usize;
+        let fields_len = u32::from_le_bytes(fixed[12..16].try_into().unwrap_or([0; 4])) as usize;
+        let header_len = (16 + fields_len).div_ceil(8) * 8;
+        let total = header_len + body_len;
+        if total > 128 * 1024 * 1024 { return Err(bad("D-Bus message too large")); }
+        let mut buf = vec![0u8; total];
+        buf[..16].copy_from_slice(&fixed);
+        self.stream.read_exact(&mut buf[16..])?;
+        decode_message(&buf)
+    }
+}
+
+fn connect_abstract(name: &str) -> std::io::Result<UnixStream> {
+    use std::os::linux::net::SocketAddrExt;
+    let addr = std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())?;
+    UnixStream::connect_addr(&addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(buf: &[u8], sig: &str) -> std::io::Result<Vec<Value>> { Reader { buf, pos: 0, depth: 0 }.values(sig) }
+
+    #[test]
+    fn message_round_trip() {
+        let buf = encode_message(MSG_METHOD_CALL, 7, Some("org.freedesktop.systemd1"), "/org/x", Some("org.x.Iface"), "Get", &["a", "bc"]);
+        let fields_len = u32::from_le_bytes(buf[12..16].try_into().unwrap()) as usize;
+        assert_eq!(buf.len() - (16 + fields_len).div_ceil(8) * 8, 4 + 2 + 2 + 4 + 3, "body follows the 8-aligned header; the second string is 4-aligned");
+        let msg = decode_message(&buf).unwrap();
+        assert_eq!(msg.msg_type, MSG_METHOD_CALL);
+        assert_eq!(msg.path.as_deref(), Some("/org/x"));
+        assert_eq!(msg.interface.as_deref(), Some("org.x.Iface"));
+        assert_eq!(msg.member.as_deref(), Some("Get"));
+        let body: Vec<&str> = msg.body.iter().filter_map(|v| v.as_str()).collect();
+        assert_eq!(body, ["a", "bc"]);
+
+        let msg = decode_message(&encode_message(MSG_SIGNAL, 1, None, "/", None, "Ping", &[])).unwrap();
+        assert_eq!(msg.member.as_deref(), Some("Ping"));
+        assert!(msg.interface.is_none() && msg.body.is_empty());
+    }
+
+    #[test]
+    fn next_signal_times_out_between_messages() {
+        let (stream, mut bus) = UnixStream::pair().unwrap();
+        let mut conn = Connection { stream, serial: 0, pending: VecDeque::new() };
+        assert!(conn.next_signal(Duration::from_millis(20)).unwrap().is_none());
+        bus.write_all(&encode_message(MSG_METHOD_RETURN, 1, None, "/", None, "Done", &[])).unwrap();
+        bus.write_all(&encode_message(MSG_SIGNAL, 2, None, "/", None, "Changed", &[])).unwrap();
+        let msg = conn.next_signal(Duration::from_millis(200)).unwrap().expect("signal after the skipped reply");
+        assert_eq!(msg.member.as_deref(), Some("Changed"));
+        assert!(conn.next_signal(Duration::from_millis(20)).unwrap().is_none());
+        drop(bus);
+        assert!(conn.next_signal(Duration::from_millis(20)).is_err(), "a closed bus is an error, not a timeout");
+    }
+
+    #[test]
+    fn aligned_containers() {
+        // y, then a{sv} (dict entries 8-aligned), then a(yt) (struct array, 8-aligned t)
+        let mut w = Writer { buf: Vec::new() };
+        w.buf.push(9);
+        w.u32(0);
+        let len_at = w.buf.len() - 4;
+        w.align(8);
+        let start = w.buf.len();
+        for (k, v) in [("A", 1u32), ("Bee", 2)] {
+            w.align(8);
+            w.string(k);
+            w.signature("u");
+            w.u32(v);
+        }
+        let n = (w.buf.len() - start) as u32;
+        w.buf[len_at..len_at + 4].copy_from_slice(&n.to_le_bytes());
+        w.u32(0);
+        let len_at = w.buf.len() - 4;
+        w.align(8);
+        let start = w.buf.len();
+        for (y, t) in [(1u8, 10u64), (2, u64::MAX)] {
+            w.align(8);
+            w.buf.push(y);
+            w.align(8);
+            w.buf.extend_from_slice(&t.to_le_bytes());
+        }
+        let n = (w.buf.len() - start) as u32;
+        w.buf[len_at..len_at + 4].copy_from_slice(&n.to_le_bytes());
+
+        let vals = read(&w.buf, "ya{sv}a(yt)").unwrap();
+        assert_eq!(vals.len(), 3);
+        assert_eq!(vals[0].as_u64(), Some(9));
+        let dict: Vec<String> = vals[1].items().iter().map(|e| e.display()).collect();
+        assert_eq!(dict, ["A=1", "Bee=2"]);
+        let structs: Vec<String> = vals[2].items().iter().map(|e| e.display()).collect();
+        assert_eq!(structs, ["(1, 10)", format!("(2, {})", u64::MAX).as_str()]);
+    }
+
+    #[test]
+    fn next_type_splits_complete_types() {
+        assert_eq!(next_type("a{sv}u"), Some(("a{sv}", "u")));
+        assert_eq!(next_type("a(sa{s(ii)})s"), Some(("a(sa{s(ii)})", "s")));
+        assert_eq!(next_type("aas"), Some(("aas", "")));
+        assert_eq!(next_type("((i)(s))x"), Some(("((i)(s))", "x")));
+        assert_eq!(next_type(""), None);
+        assert_eq!(next_type("a"), None);
+        assert_eq!(next_type("("), None);
+        assert_eq!(next_type("{sv"), None);
+        assert_eq!(next_type("a(ii"), None);
+    }
+
+    #[test]
+    fn malformed_signatures_are_errors() {
+        let zeros = [0u8; 64];
+        for sig in ["a", "(", "{", "()", "{}", "{s}", "{svu}", "(i", "a(", "z"] {
+            assert!(read(&zeros, sig).is_err(), "signature {:?}", sig);
+        }
+        // The same signatures arriving inside a variant
+        for sig in ["a", "(", "{", "()", "ii"] {
+            let mut buf = vec![sig.len() as u8];
+            buf.extend_from_slice(sig.as_bytes());
+            buf.push(0);
+            buf.resize(64, 0);
+            assert!(read(&buf, "v").is_err(), "variant signature {:?}", sig);
+        }
+        // Variants nested past the depth limit
+        let buf: Vec<u8> = std::iter::repeat_n([1, b'v', 0], 200).flatten().collect();
+        assert!(read(&buf, "v").is_err());
+    }
+
+    #[test]
+    fn truncated_bodies_are_errors() {
+        // Array length pointing past the end of the buffer
+        let mut buf = 1000u32.to_le_bytes().to_vec();
+        buf.extend_from_slice(&[1, 0, 0, 0]);
+        assert!(read(&buf, "au").is_err());
+        assert!(read(&u32::MAX.to_le_bytes(), "ay").is_err());
+        // String length past the end, missing NUL
+        let mut buf = 10u32.to_le_bytes().to_vec();
+        buf.extend_from_slice(b"abc");
+        assert!(read(&buf, "s").is_err());
+        assert!(read(&[2, 0, 0, 0, b'h', b'i'], "s").is_err());
+        assert!(read(&[], "t").is_err());
+
+        // Every prefix of a real message fails cleanly
+        let full = encode_message(MSG_METHOD_CALL, 3, Some("org.freedesktop.systemd1"), "/a/b", Some("org.x"), "M", &["arg"]);
+        for n in 0..full.len() {
+            assert!(decode_message(&full[..n]).is_err(), "prefix of {} bytes", n);
+        }
+        assert!(decode_message(b"B\x01\0\x01").is_err());
+    }
+}
@@ -121,29 +121,9 @@ pub fn read_log_file_best_effort(path: &str, sudo_pass: Option<&str>) -> Result<
             // If permission denied and sudo password provided, try sudo -S cat
             if e.kind() == std::io::ErrorKind::PermissionDenied {
                 if let Some(pw) = sudo_pass {
-                    let mut child = match Command::new("sudo")
-                        .arg("-S")
-                        .arg("--")
-                        .arg("cat")
-                        .arg(path)
-                        .stdin(Stdio::piped())
-                        .stdout(Stdio::piped())
-                        .stderr(Stdio::piped())
-                        .spawn() { Ok(c) => c, Err(spawn_err) => return Err(format!("Failed to spawn sudo: {}", spawn_err)) };
-                    if let Some(mut stdin) = child.stdin.take() {
-                        use std::io::Write;
-                        let _ = stdin.write_all(pw.as_bytes());
-                        let _ = stdin.write_all(b"\n");
-                    }
-                    let out = child.wait_with_output().map_err(|e| format!("sudo error: {}", e))?;
-                    if out.status.success() {
-                        let mut text = String::from_utf8_lossy(&out.stdout).into_owned();
-                        cap_log_text(&mut text);
-                        return Ok(text);
-                    } else {
-                        let err = String::from_utf8_lossy(&out.stderr).into_owned();
-                        return Err(if err.trim().is_empty() { String::from("sudo failed") } else { err });
-                    }
+                    let mut text = crate::sudo::run_with_sudo(&["cat", path], pw)?;
+                    cap_log_text(&mut text);
+                    return Ok(text);
                 }
             }
             // Other errors or no password
@@ -179,31 +159,9 @@ pub fn read_journal_file_best_effort(path: &str, sudo_pass: Option<&str>) -> Res
 
     if need_sudo {
         if let Some(pw) = sudo_pass {
-            let mut child = match Command::new("sudo")
-                .arg("-S")
-                .arg("--")
-                .arg("journalctl")
-                .arg("--file").arg(path)
-                .arg("-n").arg("5000")
-                .arg("-o").arg("short-iso")
-                .stdin(Stdio::piped())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn() { Ok(c) => c, Err(spawn_err) => return Err(format!("Failed to spawn sudo: {}", spawn_err)) };
-            if let Some(mut stdin) = child.stdin.take() {
-                use std::io::Write;
-                let _ = stdin.write_all(pw.as_bytes());
-                let _ = stdin.write_all(b"\n");
-            }
-            let out = child.wait_with_output().map_err(|e| format!("sudo error: {}", e))?;
-            if out.status.success() {
-                let mut text = String::from_utf8_lossy(&out.stdout).into_owned();
-                cap_log_text(&mut text);
-                return Ok(text);
-            } else {
-                let err = String::from_utf8_lossy(&out.stderr).into_owned();
-                return Err(if err.trim().is_empty() { String::from("sudo journalctl failed") } else { err });
-            }
+            let mut text = crate::sudo::run_with_sudo(&["journalctl", "--file", path, "-n", "5000", "-o", "short-iso"], pw)?;
+            cap_log_text(&mut text);
+            return Ok(text);
         }
     }
     Err(String::from("Unable to read journal (no sudo password provided)"))
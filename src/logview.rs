@@ -0,0 +1,1434 @@
+use std::cell::{Cell, RefCell};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use regex::{Regex, RegexBuilder};
+
+// -------- Log viewer: paged documents --------
+
+/// Bytes read at a time when scanning a document.
+const READ_CHUNK: usize = 64 * 1024;
+/// Lines between two entries of the line index.
+const INDEX_STRIDE: u64 = 1024;
+/// Bytes of one line kept for display and search; the rest of a longer line is skipped.
+const MAX_LINE_BYTES: usize = 16 * 1024;
+/// Bytes scanned at most for the next line passing the severity filter.
+const FILTER_BUDGET: u64 = 32 << 20;
+
+/// Backing store of one document segment.
+enum Store {
+    File(File),
+    /// Fallback when no spool file can be created (or written to)
+    Mem(Vec<u8>),
+}
+
+impl Store {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> std::io::Result<usize> {
+        match self {
+            Store::File(f) => read_file_at(f, buf, offset),
+            Store::Mem(v) => {
+                let from = (offset as usize).min(v.len());
+                let n = buf.len().min(v.len() - from);
+                buf[..n].copy_from_slice(&v[from..from + n]);
+                Ok(n)
+            }
+        }
+    }
+}
+
+struct Segment {
+    store: Store,
+    start: u64,  // offset of the segment in the document
+    len: u64,
+    spool: bool, // ours to append to
+}
+
+/// Text shown in the log popup, read on demand by byte offset so that files of any size can
+/// be paged through with bounded memory. A document is a sequence of file ranges: plain log
+/// files are read in place, while decompressed, sudo-read and generated text (journals, lines
+/// appended in follow mode) goes to unlinked spool files in the temp directory. Lines start at
+/// offset 0 and after every '\n'; line numbers come from an index built a slice at a time.
+/// Lines below the severity filter are skipped when paging.
+pub struct LogDoc {
+    segments: Vec<Segment>,
+    len: u64,
+    checkpoints: Vec<u64>, // start of every INDEX_STRIDE-th line
+    newlines: u64,         // '\n' bytes before `indexed`
+    indexed: u64,          // how far the line index reaches
+    min_severity: Option<Severity>,
+    // Drawn every frame: remember the last answers (keyed by document length)
+    last_page: Cell<Option<(u64, usize, u64)>>, // (len, rows, top)
+    window: RefCell<Option<LogWindow>>,
+}
+
+/// Lines last returned by `LogDoc::lines_from`: (document length, offset, count, lines).
+type LogWindow = (u64, u64, usize, Vec<(u64, String)>);
+
+impl Default for LogDoc {
+    fn default() -> Self {
+        LogDoc { segments: Vec::new(), len: 0, checkpoints: vec![0], newlines: 0, indexed: 0, min_severity: None, last_page: Cell::new(None), window: RefCell::new(None) }
+    }
+}
+
+impl LogDoc {
+    /// Document holding `text` (journal output, error messages).
+    pub fn from_text(text: &str) -> LogDoc {
+        let mut doc = LogDoc::default();
+        doc.append(text);
+        doc
+    }
+
+    /// Add the first `len` bytes of `store` as new lines at the end.
+    fn push_store(&mut self, store: Store, len: u64) {
+        self.end_line();
+        self.segments.push(Segment { store, start: self.len, len, spool: false });
+        self.len += len;
+    }
+
+    /// Extend the last segment, a spool file a command is still writing, to the complete lines
+    /// among its first `written` bytes (to all of them once `done`).
+    fn grow_last(&mut self, written: u64, done: bool) {
+        let Some(seg) = self.segments.last_mut() else { return; };
+        let len = if done { written } else { last_line_end(&seg.store, written) };
+        if len <= seg.len { return; }
+        self.len += len - seg.len;
+        seg.len = len;
+    }
+
+    /// Add `text` as new lines at the end.
+    pub fn append(&mut self, text: &str) {
+        if text.is_empty() { return; }
+        self.end_line();
+        self.spool_write(text.as_bytes());
+        if !text.ends_with('\n') { self.spool_write(b"\n"); }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Terminate an unfinished last line so that whatever is added next starts a new one.
+    fn end_line(&mut self) {
+        let mut last = [0u8];
+        if self.len > 0 && self.read_at(self.len - 1, &mut last) == 1 && last[0] != b'\n' { self.spool_write(b"\n"); }
+    }
+
+    fn spool_write(&mut self, bytes: &[u8]) {
+        if !self.segments.last().is_some_and(|s| s.spool) {
+            let store = spool_file().map(Store::File).unwrap_or(Store::Mem(Vec::new()));
+            self.segments.push(Segment { store, start: self.len, len: 0, spool: true });
+        }
+        let Some(seg) = self.segments.last_mut() else { return; };
+        let written = match &mut seg.store {
+            Store::File(f) => write_file_at(f, bytes, seg.len).is_ok(),
+            Store::Mem(v) => { v.extend_from_slice(bytes); true }
+        };
+        if written {
+            seg.len += bytes.len() as u64;
+        } else {
+            // Temp directory full: keep going in memory
+            self.segments.push(Segment { store: Store::Mem(bytes.to_vec()), start: self.len, len: bytes.len() as u64, spool: true });
+        }
+        self.len += bytes.len() as u64;
+    }
+
+    /// Read the bytes at `offset` into `buf`, returning how many there were.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> usize {
+        let mut n = 0;
+        while n < buf.len() {
+            let pos = offset + n as u64;
+            let i = self.segments.partition_point(|s| s.start + s.len <= pos);
+            let Some(seg) = self.segments.get(i) else { break; };
+            let want = (buf.len() - n).min((seg.start + seg.len - pos) as usize);
+            match seg.store.read_at(&mut buf[n..n + want], pos - seg.start) {
+                Ok(0) | Err(_) => break, // file shrank under us
+                Ok(k) => n += k,
+            }
+        }
+        n
+    }
+
+    /// Call `f(offset, line)` for each line from `offset` (a line start) on, until `f` returns
+    /// false or about `budget` bytes were read. Returns the offset of the first line not visited.
+    fn for_lines(&self, offset: u64, budget: u64, mut f: impl FnMut(u64, &[u8]) -> bool) -> u64 {
+        let mut buf = vec![0u8; READ_CHUNK];
+        let (mut pos, mut start, mut line) = (offset, offset, Vec::new());
+        let keep = |line: &mut Vec<u8>, bytes: &[u8]| {
+            let room = MAX_LINE_BYTES.saturating_sub(line.len());
+            line.extend_from_slice(&bytes[..bytes.len().min(room)]);
+        };
+        while pos < self.len {
+            let n = self.read_at(pos, &mut buf);
+            if n == 0 { return self.len; }
+            let mut i = 0;
+            while let Some(k) = buf[i..n].iter().position(|b| *b == b'\n') {
+                keep(&mut line, &buf[i..i + k]);
+                i += k + 1;
+                let next = pos + i as u64;
+                if !f(start, &line) || next - offset >= budget { return next; }
+                line.clear();
+                start = next;
+            }
+            keep(&mut line, &buf[i..n]);
+            pos += n as u64;
+        }
+        // Last line without a trailing '\n'
+        if start < self.len { f(start, &line); }
+        self.len
+    }
+
+    /// Hide lines below `level` when paging (`None` shows all of them).
+    pub fn set_min_severity(&mut self, level: Option<Severity>) {
+        self.min_severity = level;
+        self.last_page.set(None);
+        self.window.take();
+    }
+
+    /// Whether a line passes the severity filter; lines of unknown severity count as info.
+    fn shows(&self, line: &[u8]) -> bool {
+        self.min_severity.is_none_or(|min| line_severity(&String::from_utf8_lossy(line)).unwrap_or(Severity::Info) >= min)
+    }
+
+    /// Up to `count` shown lines from `offset` (a line start): each line's offset and text.
+    pub fn lines_from(&self, offset: u64, count: usize) -> Vec<(u64, String)> {
+        if let Some((len, at, n, lines)) = &*self.window.borrow() && (*len, *at, *n) == (self.len, offset, count) {
+            return lines.clone();
+        }
+        let mut out = Vec::new();
+        if count > 0 {
+            self.for_lines(offset, FILTER_BUDGET, |at, line| {
+                if self.shows(line) { out.push((at, String::from_utf8_lossy(line).into_owned())); }
+                out.len() < count
+            });
+        }
+        *self.window.borrow_mut() = Some((self.len, offset, count, out.clone()));
+        out
+    }
+
+    /// Start of the line holding the byte at `pos`.
+    fn line_start(&self, pos: u64) -> u64 {
+        let mut buf = [0u8; 8192];
+        let mut end = pos;
+        while end > 0 {
+            let from = end.saturating_sub(buf.len() as u64);
+            let n = self.read_at(from, &mut buf[..(end - from) as usize]);
+            if let Some(k) = buf[..n].iter().rposition(|b| *b == b'\n') { return from + k as u64 + 1; }
+            end = from;
+        }
+        0
+    }
+
+    /// Start of the first shown line at or after `offset`, or else the last shown line before it.
+    pub fn shown_from(&self, offset: u64) -> u64 {
+        let mut found = None;
+        let stop = self.for_lines(offset, FILTER_BUDGET, |at, line| {
+            if self.shows(line) { found = Some(at); }
+            found.is_none()
+        });
+        found.or((stop < self.len).then_some(stop)).unwrap_or_else(|| self.step(offset, -1))
+    }
+
+    /// Offset of the shown line `n` lines below (above, for negative `n`) the one at `offset`,
+    /// stopping at the first/last line. Scanning stops after `FILTER_BUDGET` bytes; the line
+    /// reached then is returned even if it is hidden, so paging through long stretches of
+    /// filtered-out lines goes on a slice per key press instead of freezing.
+    pub fn step(&self, offset: u64, n: i64) -> u64 {
+        if n == 0 { return offset; }
+        let mut left = n.unsigned_abs();
+        let mut found = offset;
+        if n > 0 {
+            let stop = self.for_lines(offset, FILTER_BUDGET, |at, line| {
+                if at != offset && self.shows(line) { found = at; left -= 1; }
+                left > 0
+            });
+            return if left > 0 && stop < self.len { stop } else { found };
+        }
+        // Upwards: read back a block at a time
+        let mut end = offset;
+        while end > 0 && left > 0 {
+            if offset - end >= FILTER_BUDGET { return end; }
+            let from = self.line_start(end.saturating_sub(READ_CHUNK as u64));
+            let mut shown = Vec::new();
+            self.for_lines(from, u64::MAX, |at, line| {
+                if at >= end { return false; }
+                if self.shows(line) { shown.push(at); }
+                true
+            });
+            for at in shown.into_iter().rev().take(left as usize) {
+                found = at;
+                left -= 1;
+            }
+            end = from;
+        }
+        found
+    }
+
+    /// Start of the last shown line (0 when there is none).
+    pub fn last_line(&self) -> u64 {
+        match self.step(self.len, -1) {
+            last if last == self.len => 0,
+            last => last,
+        }
+    }
+
+    /// First line shown when the last line is at the bottom of a view `rows` lines high.
+    pub fn last_page_top(&self, rows: usize) -> u64 {
+        if let Some((len, r, top)) = self.last_page.get() && (len, r) == (self.len, rows) { return top; }
+        let top = self.step(self.last_line(), 1 - rows as i64);
+        self.last_page.set(Some((self.len, rows, top)));
+        top
+    }
+
+    /// Extend the line index by about `budget` bytes; true once it covers the whole document.
+    pub fn index_step(&mut self, budget: u64) -> bool {
+        let mut buf = vec![0u8; READ_CHUNK];
+        let stop = self.len.min(self.indexed.saturating_add(budget));
+        while self.indexed < stop {
+            let want = ((stop - self.indexed) as usize).min(READ_CHUNK);
+            let n = self.read_at(self.indexed, &mut buf[..want]);
+            if n == 0 { self.indexed = self.len; break; }
+            for (i, _) in buf[..n].iter().enumerate().filter(|(_, b)| **b == b'\n') {
+                self.newlines += 1;
+                if self.newlines.is_multiple_of(INDEX_STRIDE) { self.checkpoints.push(self.indexed + i as u64 + 1); }
+            }
+            self.indexed += n as u64;
+        }
+        self.indexed >= self.len
+    }
+
+    /// 0-based number of the line at `offset`, once the index reaches that far.
+    pub fn line_number(&self, offset: u64) -> Option<u64> {
+        if offset > self.indexed { return None; }
+        let k = self.checkpoints.partition_point(|c| *c <= offset).saturating_sub(1);
+        let mut line = k as u64 * INDEX_STRIDE;
+        let mut pos = self.checkpoints.get(k).copied().unwrap_or(0);
+        let mut buf = vec![0u8; READ_CHUNK];
+        while pos < offset {
+            let n = self.read_at(pos, &mut buf[..((offset - pos) as usize).min(READ_CHUNK)]);
+            if n == 0 { break; }
+            line += buf[..n].iter().filter(|b| **b == b'\n').count() as u64;
+            pos += n as u64;
+        }
+        Some(line)
+    }
+
+    /// Number of lines, once the index covers the whole document.
+    pub fn total_lines(&self) -> Option<u64> {
+        if self.indexed < self.len { return None; }
+        let mut last = [0u8];
+        let unterminated = self.len > 0 && self.read_at(self.len - 1, &mut last) == 1 && last[0] != b'\n';
+        Some(self.newlines + u64::from(unterminated))
+    }
+
+    /// Share of the document (0-100) before `offset`.
+    pub fn percent(&self, offset: u64) -> u64 {
+        offset.saturating_mul(100).checked_div(self.len).unwrap_or(100)
+    }
+}
+
+/// Read at `offset` without using the file position (Unix), so handles can be shared.
+#[cfg(unix)]
+fn read_file_at(file: &File, buf: &mut [u8], offset: u64) -> std::io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+
+/// Without positioned reads the shared position is moved; readers that rely on it seek first.
+#[cfg(not(unix))]
+fn read_file_at(mut file: &File, buf: &mut [u8], offset: u64) -> std::io::Result<usize> {
+    file.seek(SeekFrom::Start(offset))?;
+    file.read(buf)
+}
+
+#[cfg(unix)]
+fn write_file_at(file: &File, bytes: &[u8], offset: u64) -> std::io::Result<()> {
+    std::os::unix::fs::FileExt::write_all_at(file, bytes, offset)
+}
+
+#[cfg(not(unix))]
+fn write_file_at(mut file: &File, bytes: &[u8], offset: u64) -> std::io::Result<()> {
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(bytes)
+}
+
+/// (device, inode) of a file, to notice when a followed path is replaced.
+#[cfg(unix)]
+fn file_id(md: &std::fs::Metadata) -> (u64, u64) {
+    use std::os::unix::fs::MetadataExt;
+    (md.dev(), md.ino())
+}
+
+/// No inodes: the creation time stands in (replacement goes unnoticed where it is unknown).
+#[cfg(not(unix))]
+fn file_id(md: &std::fs::Metadata) -> (u64, u64) {
+    let created = md.created().ok().and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok());
+    created.map_or((0, 0), |d| (d.as_secs(), d.subsec_nanos() as u64))
+}
+
+static SPOOL_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+/// A new temporary file, readable by us only and unlinked at once: it goes away with the
+/// last handle, even if rtop is killed.
+fn spool_file() -> std::io::Result<File> {
+    let path = std::env::temp_dir().join(format!("rtop-{}-{}.log", std::process::id(), SPOOL_SEQ.fetch_add(1, Ordering::Relaxed)));
+    let mut options = OpenOptions::new();
+    options.read(true).write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let file = options.open(&path)?;
+    let _ = std::fs::remove_file(&path);
+    Ok(file)
+}
+
+/// Run `cmd` with its output going straight to a spool file (with `password`, `cmd` is a sudo
+/// command reading it from stdin), returning the output and its length. The output is kept in
+/// memory only when no temporary file can be created.
+fn spool_command(cmd: Command, password: Option<&str>) -> Result<(Store, u64), String> {
+    let spool = spool_file().ok();
+    let stdout = match &spool {
+        Some(f) => Stdio::from(f.try_clone().map_err(|e| e.to_string())?),
+        None => Stdio::piped(),
+    };
+    let program = cmd.get_program().to_string_lossy().into_owned();
+    let child = spawn_command(cmd, password, stdout)?;
+    let res = child.wait_with_output().map_err(|e| format!("{} error: {}", program, e))?;
+    if !res.status.success() {
+        let err = String::from_utf8_lossy(&res.stderr).trim().to_string();
+        return Err(if err.is_empty() { format!("{} failed", program) } else { err });
+    }
+    match spool {
+        Some(f) => {
+            let len = f.metadata().map_err(|e| e.to_string())?.len();
+            Ok((Store::File(f), len))
+        }
+        None => {
+            let len = res.stdout.len() as u64;
+            Ok((Store::Mem(res.stdout), len))
+        }
+    }
+}
+
+/// Start `cmd` with its output going to `stdout` and its errors to a pipe; `password` is
+/// written to its stdin (for `sudo -S`).
+fn spawn_command(mut cmd: Command, password: Option<&str>, stdout: Stdio) -> Result<Child, String> {
+    let program = cmd.get_program().to_string_lossy().into_owned();
+    let mut child = cmd
+        .stdin(if password.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(stdout)
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if let Some(pw) = password && let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(pw.as_bytes());
+        let _ = stdin.write_all(b"\n");
+    }
+    Ok(child)
+}
+
+/// End of the last complete line among the first `len` bytes of `store` (0 if none).
+fn last_line_end(store: &Store, len: u64) -> u64 {
+    let mut buf = [0u8; 8192];
+    let mut end = len;
+    while end > 0 {
+        let from = end.saturating_sub(buf.len() as u64);
+        let Ok(n) = store.read_at(&mut buf[..(end - from) as usize], from) else { return 0; };
+        if let Some(k) = buf[..n].iter().rposition(|b| *b == b'\n') { return from + k as u64 + 1; }
+        end = from;
+    }
+    0
+}
+
+// -------- Log viewer: follow mode (tail -F) --------
+
+/// Follows a log file like `tail -F`: appended lines are picked up on every poll, and
+/// logrotate is survived both when the file is truncated in place (copytruncate) and when it
+/// is renamed and a new file created at the same path.
+pub enum LogTail {
+    /// Readable file: appended bytes are read from our own handle.
+    File {
+        path: String,
+        file: File,
+        id: (u64, u64),   // (dev, inode) of the open file
+        offset: u64,      // bytes consumed so far
+        partial: Vec<u8>, // trailing bytes of a line still being written
+    },
+    /// Output of a following command (`sudo tail -F` for files only root may read,
+    /// `journalctl -f` for unit journals), streamed through a reader thread.
+    Pipe { child: Child, lines: Receiver<String>, name: &'static str },
+}
+
+impl LogTail {
+    /// Start following `path` after its last complete line; the complete lines it already
+    /// holds are added to `doc`.
+    pub fn open(path: &str, doc: &mut LogDoc) -> std::io::Result<LogTail> {
+        let file = File::open(path)?;
+        let md = file.metadata()?;
+        // The clone shares the file position with the document's handle; poll seeks before reading
+        let tail_file = file.try_clone()?;
+        let store = Store::File(file);
+        let offset = last_line_end(&store, md.len());
+        doc.push_store(store, offset);
+        Ok(LogTail::File { path: path.to_string(), file: tail_file, id: file_id(&md), offset, partial: Vec::new() })
+    }
+
+    /// Read `path` through sudo into a spool file, adding its complete lines to `doc`, and
+    /// follow it from there with `sudo tail -F`.
+    pub fn open_sudo(path: &str, password: &str, doc: &mut LogDoc) -> Result<LogTail, String> {
+        let mut cat = Command::new("sudo");
+        cat.args(["-S", "-p", "", "--", "cat", "--", path]);
+        let (store, len) = spool_command(cat, Some(password))?;
+        let offset = last_line_end(&store, len);
+        doc.push_store(store, offset);
+        let mut tail = Command::new("sudo");
+        tail.args(["-S", "-p", "", "--", "tail", "-c", &format!("+{}", offset + 1), "-F", "--", path]);
+        LogTail::spawn(tail, Some(password), "sudo tail")
+    }
+
+    /// Follow the lines a long-running command prints; `password` is written to its stdin
+    /// (for `sudo -S`). `name` describes the command when it fails.
+    pub fn spawn(mut cmd: Command, password: Option<&str>, name: &'static str) -> Result<LogTail, String> {
+        let mut child = cmd
+            .stdin(if password.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run {}: {}", name, e))?;
+        if let (Some(pw), Some(mut stdin)) = (password, child.stdin.take()) {
+            let _ = stdin.write_all(pw.as_bytes());
+            let _ = stdin.write_all(b"\n");
+        }
+        let stdout = child.stdout.take().ok_or_else(|| format!("{}: no output pipe", name))?;
+        let (tx, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).split(b'\n') {
+                let Ok(line) = line else { break; };
+                if tx.send(String::from_utf8_lossy(&line).into_owned()).is_err() { break; }
+            }
+        });
+        Ok(LogTail::Pipe { child, lines, name })
+    }
+
+    /// Lines appended since the last poll, joined with '\n' ("" if none).
+    pub fn poll(&mut self) -> Result<String, String> {
+        match self {
+            LogTail::Pipe { child, lines, name } => {
+                let mut out: Vec<String> = Vec::new();
+                loop {
+                    match lines.try_recv() {
+                        Ok(line) => out.push(line),
+                        Err(TryRecvError::Empty) => break,
+                        Err(TryRecvError::Disconnected) => {
+                            if !out.is_empty() { break; }
+                            // The command (or sudo) exited: report why
+                            let _ = child.wait();
+                            let mut err = String::new();
+                            if let Some(mut e) = child.stderr.take() { let _ = e.read_to_string(&mut err); }
+                            return Err(if err.trim().is_empty() { format!("{} exited", name) } else { err.trim().to_string() });
+                        }
+                    }
+                }
+                Ok(out.join("\n"))
+            }
+            LogTail::File { path, file, id, offset, partial } => {
+                let mut bytes = Vec::new();
+                // Truncated in place (copytruncate): start over from the top; the partial line
+                // was truncated with the rest
+                if file.metadata().map(|m| m.len() < *offset).unwrap_or(false) {
+                    *offset = 0;
+                    partial.clear();
+                }
+                file.seek(SeekFrom::Start(*offset)).map_err(|e| e.to_string())?;
+                file.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+                // Renamed away and recreated: after draining the old file, switch to the new one.
+                // While the path is missing (between rename and create) keep the old handle.
+                if let Ok(md) = std::fs::metadata(&*path) && file_id(&md) != *id && let Ok(mut new) = File::open(&*path) {
+                    // The old file's unterminated last line ends there, not glued to the new file's first
+                    if bytes.last().map_or(!partial.is_empty(), |b| *b != b'\n') { bytes.push(b'\n'); }
+                    let start = bytes.len();
+                    new.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+                    *file = new;
+                    *id = file_id(&md);
+                    *offset = (bytes.len() - start) as u64;
+                } else {
+                    *offset += bytes.len() as u64;
+                }
+                Ok(self.take_lines(bytes))
+            }
+        }
+    }
+
+    /// Complete lines from `bytes` (prefixed by any partial line kept from before); a trailing
+    /// partial line is kept for the next read.
+    fn take_lines(&mut self, bytes: Vec<u8>) -> String {
+        let LogTail::File { partial, .. } = self else { return String::new(); };
+        partial.extend_from_slice(&bytes);
+        let Some(last_nl) = partial.iter().rposition(|b| *b == b'\n') else { return String::new(); };
+        let rest = partial.split_off(last_nl + 1);
+        let text = String::from_utf8_lossy(&partial[..last_nl]).into_owned();
+        *partial = rest;
+        text
+    }
+}
+
+impl Drop for LogTail {
+    fn drop(&mut self) {
+        // SIGTERM rather than SIGKILL so sudo passes it on to the command
+        if let LogTail::Pipe { child, .. } = self && matches!(child.try_wait(), Ok(None)) {
+            let _ = crate::processes::send_signal(child.id() as i32, 15);
+            let _ = child.wait();
+        }
+    }
+}
+
+// -------- Log viewer: search --------
+
+/// One search hit: offset of the line in the document and byte range within that line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogMatch {
+    pub line: u64,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Matches remembered for navigation; further ones are only counted.
+const MAX_MATCHES: usize = 100_000;
+
+/// Outcome of `LogSearch::jump`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Jump {
+    Found(LogMatch),
+    /// No match in that direction, even after wrapping around
+    NoMatch,
+    /// Not reached within the budget: call again to scan further
+    Pending,
+}
+
+/// Compile a log search pattern (a regex), case-insensitive unless `case_sensitive`.
+/// Empty input means "no search".
+pub fn compile_log_search(pattern: &str, case_sensitive: bool) -> Result<Option<Regex>, String> {
+    if pattern.is_empty() { return Ok(None); }
+    RegexBuilder::new(pattern)
+        .case_insensitive(!case_sensitive)
+        .build()
+        .map(Some)
+        // regex syntax errors span several lines (pattern, caret, message); keep the message
+        .map_err(|e| format!("Invalid regex: {}", e.to_string().lines().last().unwrap_or("").trim_start_matches("error: ")))
+}
+
+/// Non-empty matches of `re` in one line of a document.
+fn line_matches<'a>(re: &'a Regex, at: u64, text: &'a str) -> impl Iterator<Item = LogMatch> + 'a {
+    re.find_iter(text).filter(|m| m.start() < m.end()).map(move |m| LogMatch { line: at, start: m.start(), end: m.end() })
+}
+
+/// A regex search over a `LogDoc`. The document is scanned from the top a slice at a time
+/// (`scan`); the first `MAX_MATCHES` matches are kept for n/N and the rest only counted.
+pub struct LogSearch {
+    pub re: Regex,
+    matches: Vec<LogMatch>,
+    count: u64,
+    scanned: u64, // first line not scanned yet
+    hunt: Option<((u64, usize), u64)>, // `find_after` in progress: (from, first line not read yet)
+}
+
+impl LogSearch {
+    pub fn new(re: Regex) -> LogSearch {
+        LogSearch { re, matches: Vec::new(), count: 0, scanned: 0, hunt: None }
+    }
+
+    /// Scan about `budget` more bytes; true once the whole document has been scanned.
+    pub fn scan(&mut self, doc: &LogDoc, budget: u64) -> bool {
+        let (re, matches, count) = (&self.re, &mut self.matches, &mut self.count);
+        if self.scanned < doc.len() {
+            self.scanned = doc.for_lines(self.scanned, budget, |at, line| {
+                if !doc.shows(line) { return true; }
+                for m in line_matches(re, at, &String::from_utf8_lossy(line)) {
+                    *count += 1;
+                    if matches.len() < MAX_MATCHES { matches.push(m); }
+                }
+                true
+            });
+        }
+        self.done(doc)
+    }
+
+    pub fn done(&self, doc: &LogDoc) -> bool {
+        self.scanned >= doc.len()
+    }
+
+    /// Matches found so far.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// 0-based rank of `m` among all matches, if it is one of those remembered.
+    pub fn position(&self, m: LogMatch) -> Option<usize> {
+        self.matches.binary_search_by_key(&(m.line, m.start), |x| (x.line, x.start)).ok()
+    }
+
+    /// The match after (or before) `from`, wrapping around; without `from`, the first match
+    /// at or below the line at `top`. Scans ahead as needed, about `budget` bytes per call.
+    pub fn jump(&mut self, doc: &LogDoc, from: Option<LogMatch>, top: u64, forward: bool, budget: u64) -> Jump {
+        let key = match from { Some(c) => (c.line, c.start), None => (top, 0) };
+        let forward = forward || from.is_none();
+        let stop = self.scanned.saturating_add(budget);
+        loop {
+            let i = self.matches.partition_point(|m| (m.line, m.start) < key);
+            let found = if forward {
+                // Strictly after the current match, or at/after `top`
+                let i = if from.is_some() && self.matches.get(i).is_some_and(|m| (m.line, m.start) == key) { i + 1 } else { i };
+                self.matches.get(i)
+            } else {
+                i.checked_sub(1).and_then(|i| self.matches.get(i))
+            };
+            if let Some(m) = found { return Jump::Found(*m); }
+            if self.matches.len() >= MAX_MATCHES {
+                return if forward { self.find_after(doc, key, from.is_some(), budget) } else { Jump::NoMatch };
+            }
+            if self.done(doc) { break; }
+            if self.scanned >= stop { return Jump::Pending; }
+            self.scan(doc, (stop - self.scanned).min(READ_CHUNK as u64 * 64));
+        }
+        // Wrap around once everything is known
+        let wrapped = if forward { self.matches.first() } else { self.matches.last() };
+        wrapped.map_or(Jump::NoMatch, |m| Jump::Found(*m))
+    }
+
+    /// Past the remembered matches: look for the next one directly in the document, going on
+    /// where the last call for the same `key` stopped.
+    fn find_after(&mut self, doc: &LogDoc, key: (u64, usize), strictly: bool, budget: u64) -> Jump {
+        let start = match self.hunt { Some((k, at)) if k == key => at, _ => key.0 };
+        let mut found = None;
+        let reached = doc.for_lines(start, budget, |at, line| {
+            if !doc.shows(line) { return true; }
+            found = line_matches(&self.re, at, &String::from_utf8_lossy(line))
+                .find(|m| if strictly { (m.line, m.start) > key } else { (m.line, m.start) >= key });
+            found.is_none()
+        });
+        self.hunt = None;
+        match found {
+            Some(m) => Jump::Found(m),
+            None if reached >= doc.len() => Jump::NoMatch,
+            None => { self.hunt = Some((key, reached)); Jump::Pending }
+        }
+    }
+}
+
+// -------- Log viewer: severity --------
+
+/// Severity of a log line, least severe first (syslog levels, with emerg/alert/crit merged).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl Severity {
+    /// Filter steps for the log popup, loosest first (`None`: all lines).
+    pub const FILTERS: [Option<Severity>; 6] = [None, Some(Severity::Info), Some(Severity::Notice), Some(Severity::Warning), Some(Severity::Error), Some(Severity::Critical)];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Debug => "debug",
+            Severity::Info => "info",
+            Severity::Notice => "notice",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+            Severity::Critical => "critical",
+        }
+    }
+
+    /// From a syslog priority (0 emerg .. 7 debug).
+    fn from_priority(p: u32) -> Severity {
+        match p {
+            0..=2 => Severity::Critical,
+            3 => Severity::Error,
+            4 => Severity::Warning,
+            5 => Severity::Notice,
+            6 => Severity::Info,
+            _ => Severity::Debug,
+        }
+    }
+
+    /// From a level name as loggers write it (`ERROR`, `warn`, `crit`, ...).
+    fn from_name(name: &str) -> Option<Severity> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "emerg" | "emergency" | "alert" | "crit" | "critical" | "fatal" | "panic" => Severity::Critical,
+            "err" | "error" => Severity::Error,
+            "warn" | "warning" => Severity::Warning,
+            "notice" => Severity::Notice,
+            "info" | "information" | "informational" => Severity::Info,
+            "debug" | "trace" => Severity::Debug,
+            _ => return None,
+        })
+    }
+
+    /// From a level value in a JSON line: a name, a syslog priority (journal `PRIORITY`) or a
+    /// pino/bunyan number (10 trace .. 60 fatal).
+    fn from_value(value: &str) -> Option<Severity> {
+        match value.parse::<u32>() {
+            Ok(p) if p <= 7 => Some(Severity::from_priority(p)),
+            Ok(n) => Some(match n {
+                ..=20 => Severity::Debug,
+                21..=30 => Severity::Info,
+                31..=40 => Severity::Warning,
+                41..=50 => Severity::Error,
+                _ => Severity::Critical,
+            }),
+            Err(_) => Severity::from_name(value),
+        }
+    }
+}
+
+static PRIORITY_PREFIX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^<(\d{1,3})>").unwrap());
+static JSON_LEVEL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(?i)"(?:level|severity|lvl|loglevel|log\.level|levelname|priority)"\s*:\s*"?(\w+)"#).unwrap());
+static ACCESS_STATUS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#""[A-Z]+ [^"]* HTTP/[\d.]+" (\d{3}) "#).unwrap());
+static BRACKET_LEVEL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[(?:[A-Za-z0-9_-]+:)?[A-Za-z]+\]").unwrap());
+// ASCII word boundaries keep these on the regex crate's fast DFA path
+static LOGFMT_LEVEL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(?i)(?-u:\b)(?:level|lvl|severity)="?(\w+)"#).unwrap());
+
+/// Severity of one log line, recognizing (in this order) a `<N>` syslog priority prefix, JSON
+/// lines with a level field, nginx/apache access logs (5xx error, 4xx warning, else info),
+/// bracketed levels as in nginx/apache error logs (`[error]`, `[core:warn]`) and logfmt
+/// `level=...`. Words in the message are not looked at ("Started Failed-Login-Notifier"
+/// is no error), so plain syslog and journal short-iso lines are `None` (unknown).
+pub fn line_severity(line: &str) -> Option<Severity> {
+    // Cheap checks first: this runs on every line when a severity filter is set
+    if line.starts_with('<') && let Some(c) = PRIORITY_PREFIX.captures(line) && let Ok(p) = c[1].parse::<u32>() {
+        return Some(Severity::from_priority(p & 7));
+    }
+    if line.trim_start().starts_with('{') {
+        return JSON_LEVEL.captures(line).and_then(|c| Severity::from_value(&c[1]));
+    }
+    if line.contains(" HTTP/") && let Some(c) = ACCESS_STATUS.captures(line) {
+        return Some(match c[1].as_bytes()[0] {
+            b'5' => Severity::Error,
+            b'4' => Severity::Warning,
+            _ => Severity::Info,
+        });
+    }
+    let bracketed = |m: regex::Match| Severity::from_name(m.as_str().trim_matches(['[', ']']).rsplit(':').next().unwrap_or(""));
+    if let Some(sev) = BRACKET_LEVEL.find_iter(line).find_map(bracketed) { return Some(sev); }
+    if !line.contains('=') { return None; }
+    LOGFMT_LEVEL.captures(line).and_then(|c| Severity::from_name(&c[1]))
+}
+
+// -------- Log viewer: compressed and rotated logs --------
+
+/// Decompressor for a rotated log (`<tool> -dc <file>`), from the file's magic bytes when it
+/// is readable, else from its extension. `None` for plain text.
+pub fn log_decompressor(path: &str) -> Option<&'static str> {
+    let mut magic = [0u8; 6];
+    let n = File::open(path).and_then(|mut f| f.read(&mut magic)).unwrap_or(0);
+    match &magic[..n] {
+        [0x1f, 0x8b, ..] => return Some("gzip"),
+        [0xfd, b'7', b'z', b'X', b'Z', 0x00] => return Some("xz"),
+        [0x28, 0xb5, 0x2f, 0xfd, ..] => return Some("zstd"),
+        [b'B', b'Z', b'h', ..] => return Some("bzip2"),
+        _ if n > 0 => return None,
+        _ => {}
+    }
+    match path.rsplit('.').next() {
+        Some("gz") => Some("gzip"),
+        Some("xz") => Some("xz"),
+        Some("zst") => Some("zstd"),
+        Some("bz2") => Some("bzip2"),
+        _ => None,
+    }
+}
+
+/// Where the text of one log file comes from.
+enum LogSource {
+    /// Readable plain file, read in place
+    File(File, u64),
+    /// Command printing it: a decompressor and/or sudo (then with the password for `sudo -S`)
+    Command { cmd: Box<Command>, password: Option<String>, what: &'static str, context: Option<String> },
+}
+
+/// Plain files are read in place, while gzip/xz/zstd/bzip2 rotations are decompressed by a
+/// command. With a sudo password, files we may not read are read (or decompressed) through sudo.
+fn log_source(path: &str, sudo_pass: Option<&str>) -> Result<LogSource, String> {
+    let password = sudo_pass.map(str::to_string);
+    let readable = File::open(path);
+    Ok(match (log_decompressor(path), readable, sudo_pass) {
+        (None, Ok(file), _) => {
+            let len = file.metadata().map_err(|e| e.to_string())?.len();
+            LogSource::File(file, len)
+        }
+        (None, Err(e), Some(_)) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            let mut cmd = Command::new("sudo");
+            cmd.args(["-S", "-p", "", "--", "cat", "--", path]);
+            LogSource::Command { cmd: Box::new(cmd), password, what: "reading", context: None }
+        }
+        (None, Err(e), _) => return Err(e.to_string()),
+        (Some(tool), Err(_), Some(_)) => {
+            let mut cmd = Command::new("sudo");
+            cmd.args(["-S", "-p", "", "--", tool, "-dc", "--", path]);
+            LogSource::Command { cmd: Box::new(cmd), password, what: "decompressing", context: None }
+        }
+        // Unreadable without a password: fail now rather than once the decompressor runs
+        (Some(_), Err(e), None) => return Err(e.to_string()),
+        (Some(tool), Ok(_), _) => {
+            let mut cmd = Command::new(tool);
+            cmd.args(["-dc", "--", path]);
+            LogSource::Command { cmd: Box::new(cmd), password: None, what: "decompressing", context: Some(format!("Cannot decompress {} with {}", path, tool)) }
+        }
+    })
+}
+
+/// Reads logs into a document in the background: plain files are added at once, while each
+/// command writes to a spool file that the document grows into whenever it is polled (like
+/// follow mode), so the popup can show the first lines while the rest is decompressed.
+pub struct LogLoad {
+    pending: std::collections::VecDeque<(Option<String>, LogSource)>, // header line, source
+    running: Option<Spooling>,
+}
+
+/// Command of a `LogLoad` still writing to the document's last segment.
+struct Spooling {
+    child: Child,
+    program: String,
+    spool: File,
+    what: &'static str,
+    context: Option<String>, // prefix for its errors
+}
+
+impl LogLoad {
+    /// Start reading `paths` into a new document, each introduced by its header when `headers`
+    /// is set. Unreadable files fail here already; the load is None when nothing is left to run.
+    pub fn start(paths: &[PathBuf], headers: bool, sudo_pass: Option<&str>) -> Result<(LogDoc, Option<LogLoad>), String> {
+        let mut pending = std::collections::VecDeque::new();
+        for p in paths {
+            pending.push_back((headers.then(|| log_header(p)), log_source(&p.to_string_lossy(), sudo_pass)?));
+        }
+        LogLoad::begin(pending)
+    }
+
+    /// Start reading what `cmd` prints (journal output) into a new document; `password` is
+    /// written to its stdin (for `sudo -S`).
+    pub fn command(cmd: Command, password: Option<&str>) -> Result<(LogDoc, Option<LogLoad>), String> {
+        let source = LogSource::Command { cmd: Box::new(cmd), password: password.map(str::to_string), what: "reading", context: None };
+        LogLoad::begin([(None, source)].into())
+    }
+
+    fn begin(pending: std::collections::VecDeque<(Option<String>, LogSource)>) -> Result<(LogDoc, Option<LogLoad>), String> {
+        let mut doc = LogDoc::default();
+        let mut load = LogLoad { pending, running: None };
+        let done = load.step(&mut doc, false)?;
+        Ok((doc, (!done).then_some(load)))
+    }
+
+    /// Add what was written since the last poll; true once everything has been read.
+    pub fn poll(&mut self, doc: &mut LogDoc) -> Result<bool, String> {
+        self.step(doc, false)
+    }
+
+    /// What the running command does ("decompressing", "reading").
+    pub fn what(&self) -> &'static str {
+        self.running.as_ref().map_or("reading", |r| r.what)
+    }
+
+    /// Move on through the sources, waiting for each command when `block` is set.
+    fn step(&mut self, doc: &mut LogDoc, block: bool) -> Result<bool, String> {
+        loop {
+            if let Some(run) = &mut self.running {
+                let status = if block { run.child.wait().map(Some) } else { run.child.try_wait() };
+                let status = status.map_err(|e| e.to_string())?;
+                let written = run.spool.metadata().map_err(|e| e.to_string())?.len();
+                doc.grow_last(written, status.is_some());
+                let Some(status) = status else { return Ok(false); };
+                let Some(mut run) = self.running.take() else { return Ok(false); };
+                if !status.success() {
+                    let mut err = String::new();
+                    if let Some(mut e) = run.child.stderr.take() { let _ = e.read_to_string(&mut err); }
+                    let err = if err.trim().is_empty() { format!("{} failed", run.program) } else { err.trim().to_string() };
+                    return Err(with_context(run.context.as_deref(), err));
+                }
+            }
+            let Some((header, source)) = self.pending.pop_front() else { return Ok(true); };
+            if let Some(header) = header { doc.append(&header); }
+            match source {
+                LogSource::File(file, len) => doc.push_store(Store::File(file), len),
+                LogSource::Command { cmd, password, what, context } => match spool_file() {
+                    Ok(spool) => {
+                        let program = cmd.get_program().to_string_lossy().into_owned();
+                        let stdout = spool.try_clone().map_err(|e| e.to_string())?;
+                        let child = spawn_command(*cmd, password.as_deref(), Stdio::from(stdout)).map_err(|e| with_context(context.as_deref(), e))?;
+                        // The document reads its own handle; ours is for the size written so far
+                        doc.push_store(Store::File(spool.try_clone().map_err(|e| e.to_string())?), 0);
+                        self.running = Some(Spooling { child, program, spool, what, context });
+                    }
+                    // No temporary file: read it into memory in one go
+                    Err(_) => {
+                        let (store, len) = spool_command(*cmd, password.as_deref()).map_err(|e| with_context(context.as_deref(), e))?;
+                        doc.push_store(store, len);
+                    }
+                },
+            }
+        }
+    }
+}
+impl Drop for LogLoad {
+    fn drop(&mut self) {
+        // Closed before it finished: stop the command (SIGTERM, so sudo passes it on)
+        if let Some(run) = &mut self.running && matches!(run.child.try_wait(), Ok(None)) {
+            let _ = crate::processes::send_signal(run.child.id() as i32, 15);
+            let _ = run.child.wait();
+        }
+    }
+}
+
+/// `err` prefixed with `context` ("Cannot decompress ...: err").
+fn with_context(context: Option<&str>, err: String) -> String {
+    match context {
+        Some(c) => format!("{}: {}", c, err),
+        None => err,
+    }
+}
+
+/// Name of the live log a rotation belongs to: `syslog.2.gz`, `syslog.1` and
+/// `syslog-20240101.xz` all map to `syslog`.
+pub fn rotation_base(name: &str) -> &str {
+    let name = [".gz", ".xz", ".zst", ".bz2"].iter().find_map(|ext| name.strip_suffix(ext)).unwrap_or(name);
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    match name.rsplit_once('.') {
+        Some((base, n)) if digits(n) && !base.is_empty() => return base,
+        _ => {}
+    }
+    match name.rsplit_once('-') {
+        // dateext: -YYYYMMDD or -YYYYMMDDHH
+        Some((base, d)) if digits(d) && (d.len() == 8 || d.len() == 10) && !base.is_empty() => base,
+        _ => name,
+    }
+}
+
+/// The log at `path` and all its rotations in the same directory, oldest first (by
+/// modification time; the live file, if present, comes last).
+pub fn log_rotations(path: &str) -> Vec<PathBuf> {
+    let path = Path::new(path);
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name().and_then(|n| n.to_str())) else { return vec![path.to_path_buf()]; };
+    let base = rotation_base(name);
+    let mut found: Vec<(bool, std::time::SystemTime, PathBuf)> = std::fs::read_dir(dir)
+        .map(|rd| rd.flatten().filter_map(|e| {
+            let n = e.file_name().to_str()?.to_string();
+            if rotation_base(&n) != base { return None; }
+            let md = e.metadata().ok().filter(|m| m.is_file())?;
+            Some((n == base, md.modified().unwrap_or(std::time::UNIX_EPOCH), e.path()))
+        }).collect())
+        .unwrap_or_default();
+    if found.is_empty() { return vec![path.to_path_buf()]; }
+    found.sort();
+    found.into_iter().map(|(_, _, p)| p).collect()
+}
+
+/// Separator line introducing one file in a stitched view, like `tail` prints.
+pub fn log_header(path: &Path) -> String {
+    format!("==> {} <==", path.file_name().map(|n| n.to_string_lossy()).unwrap_or_else(|| path.to_string_lossy()))
+}
+
+/// Several logs (decompressed as needed) one after another, each introduced by its header.
+pub fn open_log_docs(paths: &[PathBuf], sudo_pass: Option<&str>) -> Result<LogDoc, String> {
+    let (mut doc, load) = LogLoad::start(paths, true, sudo_pass)?;
+    if let Some(mut load) = load { load.step(&mut doc, true)?; }
+    Ok(doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// A fresh directory under the system temp dir, removed again when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> TempDir {
+            let dir = std::env::temp_dir().join(format!("rtop-test-{}-{}", std::process::id(), name));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+
+        fn write(&self, name: &str, bytes: &[u8]) -> PathBuf {
+            let path = self.0.join(name);
+            std::fs::write(&path, bytes).unwrap();
+            path
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) { let _ = std::fs::remove_dir_all(&self.0); }
+    }
+
+    fn append(path: &Path, bytes: &[u8]) {
+        OpenOptions::new().append(true).open(path).unwrap().write_all(bytes).unwrap();
+    }
+
+    fn texts(doc: &LogDoc) -> Vec<String> {
+        doc.lines_from(0, usize::MAX).into_iter().map(|(_, l)| l).collect()
+    }
+
+    // -------- paged documents --------
+
+    /// Fixed-width lines ("line 00042 ...", `LINE` bytes each) and a last line without '\n'.
+    const LINE: u64 = 100;
+
+    fn numbered_doc(dir: &TempDir, lines: u64, last: &str) -> LogDoc {
+        let mut text = String::new();
+        for i in 0..lines { text.push_str(&format!("line {:05} {}\n", i, "x".repeat(LINE as usize - 12))); }
+        text.push_str(last);
+        let path = dir.write("numbered.log", text.as_bytes());
+        open_plain(&path)
+    }
+
+    /// A plain file is added at once, with nothing left to load.
+    fn open_plain(path: &Path) -> LogDoc {
+        let (doc, load) = LogLoad::start(&[path.to_path_buf()], false, None).unwrap();
+        assert!(load.is_none());
+        doc
+    }
+
+    #[test]
+    fn step_crosses_chunk_boundaries() {
+        let dir = TempDir::new("step");
+        // 2000 lines: a few READ_CHUNKs, with lines straddling the chunk edges
+        let doc = numbered_doc(&dir, 2000, "the end");
+        let end = 2000 * LINE;
+        assert_eq!(doc.len(), end + 7);
+        assert_eq!(doc.step(0, 1), LINE);
+        assert_eq!(doc.step(0, 700), 700 * LINE);
+        assert_eq!(doc.step(700 * LINE, -699), LINE);
+        assert_eq!(doc.step(1999 * LINE, 1), end);
+        // Clamped at the first and last line
+        assert_eq!(doc.step(LINE, -5), 0);
+        assert_eq!(doc.step(0, 5000), end);
+        assert_eq!(doc.step(end, -2000), 0);
+        assert_eq!(doc.last_line(), end);
+        assert_eq!(doc.last_page_top(10), 1991 * LINE);
+        assert_eq!(doc.shown_from(655 * LINE), 655 * LINE);
+        let tail: Vec<String> = doc.lines_from(1999 * LINE, 5).into_iter().map(|(_, l)| l).collect();
+        assert_eq!(tail.len(), 2);
+        assert!(tail[0].starts_with("line 01999 "));
+        assert_eq!(tail[1], "the end");
+    }
+
+    #[test]
+    fn line_numbers_count_an_unterminated_last_line() {
+        let dir = TempDir::new("index");
+        let mut doc = numbered_doc(&dir, 3000, "no newline");
+        assert_eq!(doc.total_lines(), None);
+        while !doc.index_step(10_000) {}
+        assert_eq!(doc.total_lines(), Some(3001));
+        assert_eq!(doc.line_number(2500 * LINE), Some(2500));
+        assert_eq!(doc.line_number(doc.last_line()), Some(3000));
+        // Appending terminates the unfinished line first
+        doc.append("more");
+        while !doc.index_step(u64::MAX) {}
+        assert_eq!(doc.total_lines(), Some(3002));
+        assert_eq!(doc.lines_from(doc.step(doc.last_line(), -1), 3).into_iter().map(|(_, l)| l).collect::<Vec<_>>(), ["no newline", "more"]);
+    }
+
+    #[test]
+    fn severity_filter_skips_hidden_lines() {
+        let mut text = String::new();
+        for i in 0..3000 { text.push_str(&if i % 700 == 350 { format!("[error] failure {}\n", i) } else { format!("[info] fine {}\n", i) }); }
+        let mut doc = LogDoc::from_text(&text);
+        doc.set_min_severity(Some(Severity::Error));
+        let shown = |doc: &LogDoc, from: u64, n: usize| doc.lines_from(from, n).into_iter().map(|(_, l)| l).collect::<Vec<_>>();
+        assert_eq!(shown(&doc, 0, 10), ["[error] failure 350", "[error] failure 1050", "[error] failure 1750", "[error] failure 2450"]);
+        let first = doc.shown_from(0);
+        assert_eq!(shown(&doc, first, 1), ["[error] failure 350"]);
+        assert_eq!(shown(&doc, doc.step(first, 2), 1), ["[error] failure 1750"]);
+        assert_eq!(doc.step(doc.step(first, 2), -1), doc.step(first, 1));
+        // Past the last shown line: back to it
+        assert_eq!(doc.shown_from(doc.len() - 5), doc.last_line());
+        assert_eq!(shown(&doc, doc.last_page_top(2), 5), ["[error] failure 1750", "[error] failure 2450"]);
+        doc.set_min_severity(None);
+        assert_eq!(doc.step(0, 1), shown(&doc, 0, 1)[0].len() as u64 + 1);
+    }
+
+    #[test]
+    fn severity_filter_on_short_iso_lines() {
+        // Journal short-iso lines carry no level: they count as info, so only a bracketed
+        // level in the message text keeps a line above info, and a warning filter hides the rest
+        let text = "2024-01-01T12:00:00+0000 host sshd[42]: error: maximum authentication attempts exceeded\n\
+                    2024-01-01T12:00:01+0000 host app[7]: [warn] cache almost full\n\
+                    2024-01-01T12:00:02+0000 host systemd[1]: Started Failed-Login-Notifier.service.\n";
+        let mut doc = LogDoc::from_text(text);
+        let shown = |doc: &LogDoc| doc.lines_from(0, 10).into_iter().map(|(_, l)| l).collect::<Vec<_>>();
+        doc.set_min_severity(Some(Severity::Info));
+        assert_eq!(shown(&doc).len(), 3);
+        doc.set_min_severity(Some(Severity::Warning));
+        assert_eq!(shown(&doc), ["2024-01-01T12:00:01+0000 host app[7]: [warn] cache almost full"]);
+        doc.set_min_severity(Some(Severity::Error));
+        assert!(shown(&doc).is_empty());
+    }
+
+    #[test]
+    fn filter_budget_bounds_one_scan() {
+        // More than FILTER_BUDGET of hidden lines before the first shown one
+        let dir = TempDir::new("budget");
+        let filler = "plain line without a level, hidden below warning\n";
+        let count = FILTER_BUDGET as usize / filler.len() + 1000;
+        let mut text = filler.repeat(count);
+        text.push_str("[warn] finally\n");
+        let path = dir.write("big.log", text.as_bytes());
+        let mut doc = open_plain(&path);
+        doc.set_min_severity(Some(Severity::Warning));
+        // One scan stops at the budget and hands back where to resume
+        assert!(doc.lines_from(0, 5).is_empty());
+        let resume = doc.step(0, 1);
+        assert!(resume >= FILTER_BUDGET && resume < doc.len());
+        assert_eq!(resume % filler.len() as u64, 0);
+        assert_eq!(doc.shown_from(0), resume);
+        let last = doc.step(resume, 1);
+        assert_eq!(doc.lines_from(last, 5).into_iter().map(|(_, l)| l).collect::<Vec<_>>(), ["[warn] finally"]);
+        // Upwards the scan also stops after the budget (give or take the block being read)
+        let up = doc.step(last, -1);
+        assert!(up < last && last - up <= FILTER_BUDGET + READ_CHUNK as u64);
+    }
+
+    // -------- search --------
+
+    #[test]
+    fn search_counts_incrementally_and_wraps() {
+        let mut text = String::new();
+        for i in 0..5000 { text.push_str(&format!("{} {}\n", i, if i % 1000 == 0 { "needle and needle" } else { "hay" })); }
+        let mut doc = LogDoc::from_text(&text);
+        let mut search = LogSearch::new(compile_log_search("NEEDLE", false).unwrap().unwrap());
+        let mut passes = 0;
+        while !search.scan(&doc, 4096) { passes += 1; }
+        assert!(passes > 5, "scanned in slices");
+        assert_eq!(search.count(), 10);
+        // Lines appended later (follow mode) are scanned on the next pass
+        doc.append("late needle");
+        assert!(!search.done(&doc));
+        assert!(search.scan(&doc, u64::MAX));
+        assert_eq!(search.count(), 11);
+
+        let found = |j: Jump| match j { Jump::Found(m) => m, other => panic!("{:?}", other) };
+        let first = found(search.jump(&doc, None, 0, true, 0));
+        assert_eq!((first.line, first.start, first.end), (0, 2, 8));
+        let second = found(search.jump(&doc, Some(first), 0, true, 0));
+        assert_eq!((second.line, second.start), (0, 13));
+        assert_eq!(search.position(second), Some(1));
+        // Backwards from the first match wraps to the last one
+        let last = found(search.jump(&doc, Some(first), 0, false, 0));
+        assert_eq!(doc.lines_from(last.line, 1)[0].1, "late needle");
+        assert_eq!(search.jump(&doc, Some(last), 0, true, 0), Jump::Found(first));
+        // Without a current match: the first one at or below the top line
+        let top = doc.step(0, 2500);
+        let below = found(search.jump(&doc, None, top, false, 0));
+        assert!(doc.lines_from(below.line, 1)[0].1.starts_with("3000 "));
+    }
+
+    #[test]
+    fn jump_stays_pending_until_scanned_far_enough() {
+        let re = compile_log_search("needle", false).unwrap().unwrap();
+        let doc = LogDoc::from_text(&format!("{}needle\n", "hay\n".repeat(50_000)));
+        let mut search = LogSearch::new(re.clone());
+        // Each call scans a slice at most; the scan goes on where the last one stopped
+        let mut calls = 1;
+        let mut jump = search.jump(&doc, None, 0, true, 4096);
+        while jump == Jump::Pending { jump = search.jump(&doc, None, 0, true, 4096); calls += 1; }
+        assert_eq!(jump, Jump::Found(LogMatch { line: 200_000, start: 0, end: 6 }));
+        assert!(calls > 10, "{} calls", calls);
+
+        let mut text = "needle\n".repeat(MAX_MATCHES);
+        text.push_str(&"hay\n".repeat(50_000));
+        text.push_str("needle at the end\n");
+        let doc = LogDoc::from_text(&text);
+        let mut search = LogSearch::new(re);
+        while !search.scan(&doc, u64::MAX) {}
+        // Past the remembered matches the document is searched directly, a slice per call too
+        let last_kept = LogMatch { line: (MAX_MATCHES as u64 - 1) * 7, start: 0, end: 6 };
+        assert_eq!(search.position(last_kept), Some(MAX_MATCHES - 1));
+        calls = 1;
+        let mut jump = search.jump(&doc, Some(last_kept), 0, true, 4096);
+        while jump == Jump::Pending { jump = search.jump(&doc, Some(last_kept), 0, true, 4096); calls += 1; }
+        let Jump::Found(m) = jump else { panic!("{:?}", jump) };
+        assert_eq!(doc.lines_from(m.line, 1)[0].1, "needle at the end");
+        assert!(calls > 10, "{} calls", calls);
+    }
+
+    #[test]
+    fn search_skips_filtered_lines() {
+        let mut doc = LogDoc::from_text("[info] disk ok\n[error] disk failed\n[info] disk ok\n");
+        doc.set_min_severity(Some(Severity::Error));
+        let mut search = LogSearch::new(compile_log_search("disk", true).unwrap().unwrap());
+        assert!(search.scan(&doc, u64::MAX));
+        assert_eq!(search.count(), 1);
+        assert!(compile_log_search("", false).unwrap().is_none());
+        assert!(compile_log_search("(", false).is_err());
+    }
+
+    // -------- follow mode --------
+
+    #[test]
+    fn tail_holds_partial_lines_back() {
+        let dir = TempDir::new("tail-partial");
+        let path = dir.write("app.log", b"one\ntw");
+        let mut doc = LogDoc::default();
+        let mut tail = LogTail::open(path.to_str().unwrap(), &mut doc).unwrap();
+        assert_eq!(texts(&doc), ["one"]);
+        assert_eq!(tail.poll().unwrap(), "");
+        append(&path, b"o\nthr");
+        assert_eq!(tail.poll().unwrap(), "two");
+        append(&path, b"ee\nfour\n");
+        assert_eq!(tail.poll().unwrap(), "three\nfour");
+        assert_eq!(tail.poll().unwrap(), "");
+    }
+
+    #[test]
+    fn tail_drops_partial_line_on_truncation() {
+        let dir = TempDir::new("tail-truncate");
+        let path = dir.write("app.log", b"first line\n");
+        let mut tail = LogTail::open(path.to_str().unwrap(), &mut LogDoc::default()).unwrap();
+        append(&path, b"half a li");
+        assert_eq!(tail.poll().unwrap(), "");
+        // copytruncate: emptied in place, then written again
+        std::fs::write(&path, b"new\n").unwrap();
+        assert_eq!(tail.poll().unwrap(), "new");
+    }
+
+    #[test]
+    fn tail_ends_old_file_line_on_rename() {
+        let dir = TempDir::new("tail-rename");
+        let path = dir.write("app.log", b"a\n");
+        let mut tail = LogTail::open(path.to_str().unwrap(), &mut LogDoc::default()).unwrap();
+        append(&path, b"old");
+        assert_eq!(tail.poll().unwrap(), "");
+        append(&path, b" end");
+        std::fs::rename(&path, dir.0.join("app.log.1")).unwrap();
+        dir.write("app.log", b"new\npart");
+        assert_eq!(tail.poll().unwrap(), "old end\nnew");
+        append(&path, b"ial\n");
+        assert_eq!(tail.poll().unwrap(), "partial");
+    }
+
+    // -------- severity --------
+
+    #[test]
+    fn line_severity_reads_level_positions() {
+        use Severity::*;
+        let cases: &[(&str, Option<Severity>)] = &[
+            // syslog priority prefix (facility bits ignored)
+            ("<3>kernel: oops", Some(Error)),
+            ("<86>sshd[12]: session opened", Some(Info)),
+            ("<0>panic", Some(Critical)),
+            // bracketed levels, any case, with a module prefix
+            ("2024-01-01 12:00:00 [ERROR] db: connection refused", Some(Error)),
+            ("[Mon Jan 01 12:00:00 2024] [core:warn] [pid 12] AH00098", Some(Warning)),
+            ("[Mon Jan 01 12:00:00 2024] [notice] resuming normal operations", Some(Notice)),
+            ("[build 42] [debug] cache hit", Some(Debug)),
+            // logfmt and JSON
+            ("ts=2024-01-01 level=warn msg=\"disk almost full\"", Some(Warning)),
+            ("time=\"now\" lvl=crit msg=down", Some(Critical)),
+            (r#"{"level":"error","msg":"boom"}"#, Some(Error)),
+            (r#"{"level":50,"msg":"pino error"}"#, Some(Error)),
+            (r#"{"PRIORITY":"4","MESSAGE":"journal json"}"#, Some(Warning)),
+            // access logs by status
+            (r#"1.2.3.4 - - [01/Jan/2024:12:00:00 +0000] "GET / HTTP/1.1" 503 12 "-" "curl""#, Some(Error)),
+            (r#"1.2.3.4 - - [01/Jan/2024:12:00:00 +0000] "GET /x HTTP/1.1" 404 12 "-" "curl""#, Some(Warning)),
+            (r#"1.2.3.4 - - [01/Jan/2024:12:00:00 +0000] "GET / HTTP/1.1" 200 12 "-" "curl""#, Some(Info)),
+            // Plain syslog and journal short-iso lines carry no level
+            ("Jan  1 12:00:00 host systemd[1]: Started Failed-Login-Notifier.service.", None),
+            ("2024-01-01T12:00:00+0000 host sshd[42]: error: maximum authentication attempts exceeded", None),
+            ("2024-01-01T12:00:00+0000 host app[7]: warning about [nothing] in particular", None),
+            ("Jan  1 12:00:00 host cron[9]: (root) CMD (run-parts /etc/cron.hourly)", None),
+            ("", None),
+        ];
+        for (line, want) in cases {
+            assert_eq!(line_severity(line), *want, "{:?}", line);
+        }
+    }
+
+    // -------- compressed and rotated logs --------
+
+    #[test]
+    fn rotation_base_strips_rotation_suffixes() {
+        assert_eq!(rotation_base("syslog"), "syslog");
+        assert_eq!(rotation_base("syslog.1"), "syslog");
+        assert_eq!(rotation_base("syslog.2.gz"), "syslog");
+        assert_eq!(rotation_base("syslog.13.zst"), "syslog");
+        assert_eq!(rotation_base("auth.log.1"), "auth.log");
+        assert_eq!(rotation_base("messages-20240101"), "messages");
+        assert_eq!(rotation_base("messages-2024010112.xz"), "messages");
+        // Neither a rotation number nor a date
+        assert_eq!(rotation_base("syslog-ng.log"), "syslog-ng.log");
+        assert_eq!(rotation_base("app-2024.log"), "app-2024.log");
+        assert_eq!(rotation_base(".1"), ".1");
+    }
+
+    #[test]
+    fn log_rotations_oldest_first_live_last() {
+        let dir = TempDir::new("rotations");
+        let now = std::time::SystemTime::now();
+        let day = Duration::from_secs(86_400);
+        for (name, age) in [("syslog", 0), ("syslog.1", 1), ("syslog.2.gz", 2), ("syslog-20240101", 9), ("syslog-ng.log", 30), ("auth.log", 5)] {
+            let f = File::create(dir.0.join(name)).unwrap();
+            f.set_modified(now - day * age).unwrap();
+        }
+        let names: Vec<String> = log_rotations(dir.0.join("syslog.1").to_str().unwrap())
+            .iter().map(|p| p.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        assert_eq!(names, ["syslog-20240101", "syslog.2.gz", "syslog.1", "syslog"]);
+        // A file without rotations is returned alone
+        let alone = log_rotations(dir.0.join("auth.log").to_str().unwrap());
+        assert_eq!(alone, [dir.0.join("auth.log")]);
+    }
+
+    #[test]
+    fn log_decompressor_prefers_magic_bytes() {
+        let dir = TempDir::new("magic");
+        let path = |name: &str, bytes: &[u8]| dir.write(name, bytes).to_string_lossy().into_owned();
+        assert_eq!(log_decompressor(&path("syslog.1", &[0x1f, 0x8b, 8, 0])), Some("gzip"));
+        assert_eq!(log_decompressor(&path("a.log", &[0xfd, b'7', b'z', b'X', b'Z', 0, 1])), Some("xz"));
+        assert_eq!(log_decompressor(&path("b.log", &[0x28, 0xb5, 0x2f, 0xfd, 0])), Some("zstd"));
+        assert_eq!(log_decompressor(&path("c.log", b"BZh91AY")), Some("bzip2"));
+        // Content wins over a misleading extension
+        assert_eq!(log_decompressor(&path("plain.gz", b"just text\n")), None);
+        // Empty or unreadable files fall back to the extension
+        assert_eq!(log_decompressor(&path("empty.zst", b"")), Some("zstd"));
+        assert_eq!(log_decompressor(&dir.0.join("missing.bz2").to_string_lossy()), Some("bzip2"));
+        assert_eq!(log_decompressor(&path("short.log", &[0x1f])), None);
+    }
+
+    #[test]
+    fn log_load_decompresses_in_background() {
+        let dir = TempDir::new("load");
+        let old = dir.write("syslog.2", b"old 1\nold 2\n");
+        let live = dir.write("syslog", b"live 1\n");
+        // Skip where gzip is not installed
+        if !Command::new("gzip").arg("--").arg(&old).status().is_ok_and(|s| s.success()) { return; }
+        let gz = dir.0.join("syslog.2.gz");
+        let (mut doc, load) = LogLoad::start(&[gz, live], true, None).unwrap();
+        let mut load = load.expect("gzip runs in the background");
+        assert_eq!(load.what(), "decompressing");
+        let until = std::time::Instant::now() + Duration::from_secs(10);
+        while !load.poll(&mut doc).unwrap() {
+            assert!(std::time::Instant::now() < until);
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let lines: Vec<String> = doc.lines_from(0, 10).into_iter().map(|(_, l)| l).collect();
+        assert_eq!(lines, ["==> syslog.2.gz <==", "old 1", "old 2", "==> syslog <==", "live 1"]);
+        // A corrupt file fails when its decompressor does
+        let bad = dir.write("bad.gz", &[0x1f, 0x8b, 8, 0, 1, 2, 3]);
+        let (mut doc, load) = LogLoad::start(&[bad], false, None).unwrap();
+        let mut load = load.unwrap();
+        let err = loop {
+            match load.poll(&mut doc) {
+                Ok(done) => assert!(!done && std::time::Instant::now() < until),
+                Err(e) => break e,
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        assert!(err.starts_with("Cannot decompress"), "{}", err);
+    }
+}
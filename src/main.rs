@@ -4,8 +4,9 @@
 use std::error::Error;
 use std::io;
 use std::time::{Duration, Instant};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::process::Command;
-use std::process::Stdio;
 
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
@@ -21,9 +22,22 @@ use std::fs;
 mod shell;
 mod app;
 mod ui_popups;
-use app::App;
+mod processes;
+mod sudo;
+mod config;
+mod services;
+#[cfg(target_os = "linux")]
+mod dbus;
+mod collector;
+mod logview;
+use collector::Collector;
+use app::{App, SignalStage, TuneMode};
+use config::Config;
+use services::{SERVICE_DETAIL_TABS, get_unit_file, get_unit_show, get_unit_dependencies, JOURNAL_PRIORITIES, read_unit_journal, unit_journal_follow_command, ServiceCollector, ServiceColumn, ServiceFilter, UnitInfo, UNIT_TYPES, sort_services, format_cpu_usec, get_service_status, run_service_action, run_service_action_sudo, ServiceActionError, SERVICE_ACTIONS, list_failed_units, FAILED_UNITS_INTERVAL};
+use processes::{format_local_datetime, read_process_cgroup, container_from_cgroup, fit_process_columns, read_process_extra, format_start_time, ProcExtra, read_process_io, read_process_threads, clock_ticks_per_sec, IO_CLASSES, get_process_affinity, get_process_ioprio, set_process_nice, set_process_affinity, set_process_ioprio, set_process_nice_sudo, set_process_affinity_sudo, set_process_ioprio_sudo, ProcFilter, ProcColumn, load_uid_names, get_process_user, get_process_pri_nice, get_process_name, get_process_detail_tab, PROCESS_DETAIL_TABS, get_process_ppid, build_process_tree, SIGNALS, signal_name, send_signal, send_signal_sudo};
 use shell::ShellSession;
-use ui_popups::{draw_help_popup, draw_service_popup, draw_process_popup, draw_log_popup, draw_logs_password_prompt};
+use logview::{compile_log_search, log_decompressor, log_header, log_rotations, open_log_docs, rotation_base, Jump, LogDoc, LogLoad, LogSearch, LogTail, Severity};
+use ui_popups::{LogSearchView, draw_help_popup, draw_service_popup, draw_process_popup, draw_log_popup, draw_logs_password_prompt, draw_signal_popup, draw_tune_popup, draw_threads_popup, draw_columns_popup, draw_service_action_popup};
 
 
 
@@ -65,66 +79,121 @@ fn main() -> Result<(), Box<dyn Error>> {
 
 /// Main application loop: handles periodic refresh, input events, and drawing.
 fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<(), Box<dyn Error>> {
-    let mut app = App::default();
-    // Cache GPU detection once at startup
+    // Persisted settings (process table columns)
+    let mut app = App { procs_columns: Config::load().process_columns, ..App::default() };
+    ensure_sort_column_visible(&mut app);
+    // Cache GPU and DMI detection once at startup
     app.gpus = detect_gpus();
-
-    // Prepare sysinfo system with specific refresh kinds to be efficient
-    let refresh = RefreshKind::nothing()
-        .with_cpu(CpuRefreshKind::everything())
-        .with_processes(ProcessRefreshKind::everything());
-    let mut sys = System::new_with_specifics(refresh);
+    app.hw_model = get_hw_manufacturer_and_model();
+    app.uid_names = load_uid_names();
+
+    // sysinfo instance drawn by the UI; refreshed once here so the first frame has data,
+    // afterwards refreshed copies arrive from the system collector
+    let mut sys = new_system();
+    sys.refresh_cpu_all();
+    sys.refresh_processes(ProcessesToUpdate::All, true);
+    sys.refresh_memory();
+    // Worker threads for every data source (system, services, network, disks, logs, ...)
+    let mut collectors = Collectors::spawn();
 
     let tick_rate = Duration::from_millis(800);
     let mut last_tick = Instant::now();
+    let mut ticked = true; // the tick fired since the last pass (key presses also start passes)
 
-    // Note: sysinfo 0.30 does not expose refresh_users_list; users info will be read from sys.users()
-    // Initialize network prev counters once before entering loop
-    #[cfg(target_os = "linux")]
-    {
-        for (iface, rx, tx) in read_network_counters() {
-            app.net_prev.insert(iface, (rx, tx));
-        }
-        app.net_last = Instant::now();
-    }
     loop {
-        // Update network rates (Linux)
-        #[cfg(target_os = "linux")]
-        {
-            let now = Instant::now();
-            let dt = now.saturating_duration_since(app.net_last).as_secs_f64();
-            if dt > 0.0 {
-                let mut new_prev = app.net_prev.clone();
-                for (iface, rx, tx) in read_network_counters() {
-                    if let Some((prx, ptx)) = app.net_prev.get(&iface).cloned() {
-                        let drx = rx.saturating_sub(prx) as f64;
-                        let dtx = tx.saturating_sub(ptx) as f64;
-                        app.net_rates.insert(iface.clone(), (drx / dt, dtx / dt));
-                    } else {
-                        // No previous data; show 0 for first update
-                        app.net_rates.insert(iface.clone(), (0.0, 0.0));
-                    }
-                    new_prev.insert(iface, (rx, tx));
-                }
-                app.net_prev = new_prev;
-                app.net_last = now;
-            }
+        // Pick up whatever the collector threads have produced since the last pass
+        let sys_updated = collectors.collect(&mut app, &mut sys);
+        app.cpu_count = sys.cpus().len();
+        poll_service_action(&mut app);
+        // Sparklines get one point per system snapshot; /proc views are re-read once per tick
+        if app.process_popup && sys_updated {
+            sample_process_detail(&mut app, &sys);
+        }
+        if std::mem::take(&mut ticked) {
+            // Thread CPU% spans about one tick: neither key presses nor opening the view just
+            // before a tick may shorten the interval
+            if app.threads_popup && app.threads_last.elapsed() >= tick_rate / 2 { refresh_thread_view(&mut app); }
+            if app.process_popup { reread_process_detail_tab(&mut app); }
+        }
+        if app.log_popup && app.log_follow && app.log_follow_last.elapsed() >= Duration::from_secs(1) {
+            follow_log_tail(&mut app);
+        }
+        if app.log_popup && app.log_load.is_some() { poll_log_load(&mut app); }
+        // Index lines and count search matches in the log popup a slice per pass
+        let mut log_scanning = false;
+        let scan_until = Instant::now() + LOG_SCAN_SLICE;
+        while app.log_popup {
+            let indexed = app.log_doc.index_step(LOG_SCAN_CHUNK);
+            let searched = app.log_search.as_mut().is_none_or(|s| s.scan(&app.log_doc, LOG_SCAN_CHUNK));
+            log_scanning = !(indexed && searched);
+            if !log_scanning || Instant::now() >= scan_until { break; }
+        }
+        if app.log_popup && let Some(forward) = app.log_search_pending {
+            log_search_jump(&mut app, forward);
+            log_scanning |= app.log_search_pending.is_some();
         }
 
-        // Refresh data periodically
-        sys.refresh_cpu_all();
-        sys.refresh_processes(ProcessesToUpdate::All, true);
-        sys.refresh_memory();
-
-        // Build processes cache: PIDs sorted by CPU% (descending)
+        // Build processes cache: PIDs sorted by the selected column, keeping the selected PID
         {
-            let mut pairs: Vec<(i32, f32)> = sys
-                .processes()
-                .iter()
-                .map(|(pid, p)| (pid.as_u32() as i32, p.cpu_usage()))
+            let selected_pid = app.procs_pids_sorted.get(app.procs_selected).cloned();
+            // Values of a /proc sort column, once the system collector has read them for this key
+            let proc_values = app.procs_sort_values.as_ref().filter(|(k, _)| *k == app.procs_sort_key).map(|(_, v)| v);
+            let pairs: Vec<(i32, f32)> = sort_process_pids(&sys, app.procs_sort_key, app.procs_sort_desc, &app.procs_io_rates, proc_values)
+                .into_iter()
+                .map(|pid| (pid, sys.process(sysinfo::Pid::from_u32(pid as u32)).map(|p| p.cpu_usage()).unwrap_or(0.0)))
                 .collect();
-            pairs.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-            app.procs_pids_sorted = pairs.into_iter().map(|(pid, _)| pid).collect();
+            // Apply the "/" filter: the set of PIDs that match it (None = no filter)
+            let matched: Option<std::collections::HashSet<i32>> = app.procs_filter.as_ref().map(|flt| {
+                pairs
+                    .iter()
+                    .filter(|(pid, _)| {
+                        let cmd = sys.process(sysinfo::Pid::from_u32(*pid as u32)).map(process_cmd_string).unwrap_or_default();
+                        let user = if flt.needs_user() { get_process_user(*pid, &app.uid_names) } else { String::new() };
+                        flt.matches(*pid, &cmd, &user)
+                    })
+                    .map(|(pid, _)| *pid)
+                    .collect()
+            });
+            if app.procs_tree_mode {
+                // Nest by PPid; siblings keep the sort order from above
+                let mut entries: Vec<(i32, Option<i32>, f32, u64)> = pairs
+                    .iter()
+                    .map(|(pid, cpu)| {
+                        let mem = sys.process(sysinfo::Pid::from_u32(*pid as u32)).map(|p| p.memory()).unwrap_or(0);
+                        (*pid, app.procs_ppids.get(pid).copied().flatten(), *cpu, mem)
+                    })
+                    .collect();
+                // Forget collapsed PIDs that no longer exist
+                app.procs_collapsed.retain(|pid| entries.iter().any(|e| e.0 == *pid));
+                if let Some(m) = &matched {
+                    // Keep matches plus their ancestors so the tree stays connected
+                    let ppid_of: std::collections::HashMap<i32, Option<i32>> = entries.iter().map(|e| (e.0, e.1)).collect();
+                    let mut keep: std::collections::HashSet<i32> = std::collections::HashSet::new();
+                    for pid in m {
+                        let mut cur = Some(*pid);
+                        while let Some(p) = cur {
+                            if !keep.insert(p) { break; }
+                            cur = ppid_of.get(&p).cloned().flatten();
+                        }
+                    }
+                    entries.retain(|e| keep.contains(&e.0));
+                }
+                app.procs_tree_rows = build_process_tree(&entries, &app.procs_collapsed);
+                app.procs_pids_sorted = app.procs_tree_rows.iter().map(|r| r.pid).collect();
+            } else {
+                app.procs_tree_rows.clear();
+                app.procs_pids_sorted = pairs
+                    .into_iter()
+                    .map(|(pid, _)| pid)
+                    .filter(|pid| matched.as_ref().is_none_or(|m| m.contains(pid)))
+                    .collect();
+            }
+            // Rows that match the filter themselves (tree mode also shows their ancestors)
+            app.procs_match_flags = app.procs_pids_sorted.iter().map(|pid| matched.as_ref().is_none_or(|m| m.contains(pid))).collect();
+            // Follow the selected PID to its new position after re-sorting
+            if let Some(pid) = selected_pid && let Some(idx) = app.procs_pids_sorted.iter().position(|p| *p == pid) {
+                app.procs_selected = idx;
+            }
             // Clamp selection to available items
             if !app.procs_pids_sorted.is_empty() {
                 let max_idx = app.procs_pids_sorted.len().saturating_sub(1);
@@ -176,24 +245,56 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<(),
                 draw_help_popup(f, size);
             }
             if app.service_popup {
-                draw_service_popup(f, size, &app.service_detail_title, &app.service_detail_text);
+                draw_service_popup(f, size, &app);
             }
             if app.process_popup {
-                draw_process_popup(f, size, &app.process_detail_title, &app.process_detail_text);
+                draw_process_popup(f, size, &app);
+            }
+            if app.threads_popup {
+                draw_threads_popup(f, size, &app);
             }
             if app.log_popup {
-                draw_log_popup(f, size, &app.log_detail_title, &app.log_detail_text, app.log_popup_scroll);
+                let search = LogSearchView { re: app.log_search.as_ref().map(|s| &s.re), current: app.log_search_current, footer: log_search_footer(&app) };
+                // One line more than fits tells whether the whole document is on screen
+                let rows = size.height.saturating_sub(2) as usize;
+                let mut lines = app.log_doc.lines_from(app.log_top, rows + 1);
+                let whole = app.log_top == 0 && lines.len() <= rows && log_last_page_top(&app) == 0;
+                lines.truncate(rows);
+                draw_log_popup(f, size, &log_popup_title(&app, lines.is_empty()), &lines, whole, &search);
+            }
+            if app.signal_popup {
+                draw_signal_popup(f, size, &app);
+            }
+            if app.tune_popup {
+                draw_tune_popup(f, size, &app);
+            }
+            if app.service_action_popup {
+                draw_service_action_popup(f, size, &app);
+            }
+            if app.columns_popup {
+                draw_columns_popup(f, size, &app);
             }
             if app.logs_password_prompt {
-                draw_logs_password_prompt(f, size, &app.logs_password_error, app.logs_password_input.chars().count());
+                let prompt = if app.signal_sudo_pending {
+                    "Enter sudo password to signal the process:"
+                } else if app.tune_sudo_pending {
+                    "Enter sudo password to change process scheduling:"
+                } else if app.service_action_sudo_pending {
+                    "Enter sudo password to manage the unit:"
+                } else {
+                    "Enter sudo password to read protected logs:"
+                };
+                draw_logs_password_prompt(f, size, prompt, &app.logs_password_error, app.logs_password_input.chars().count());
             }
         })?;
 
 
         // Handle input with non-blocking poll, but ensure a minimum tick rate
-        let timeout = tick_rate
+        let mut timeout = tick_rate
             .checked_sub(last_tick.elapsed())
             .unwrap_or_else(|| Duration::from_millis(0));
+        // Come back soon while a large log is still being decompressed or scanned
+        if log_scanning || app.log_load.is_some() { timeout = timeout.min(LOG_SCAN_PAUSE); }
 
         if event::poll(timeout)? {
             match event::read()? {
@@ -217,12 +318,312 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<(),
         }
         if last_tick.elapsed() >= tick_rate {
             last_tick = Instant::now();
+            ticked = true;
         }
     }
 
     Ok(())
 }
 
+// -------- Background collectors --------
+/// How often the system collector refreshes CPU, memory and the process list.
+const SYSTEM_INTERVAL: Duration = Duration::from_millis(800);
+/// How often network counters are sampled for the Tx/Rx rates.
+const NET_INTERVAL: Duration = Duration::from_secs(1);
+/// How often mounts are statvfs'ed for the Disks frame.
+const DISKS_INTERVAL: Duration = Duration::from_secs(5);
+/// How often the Applications frame re-checks installed/active state.
+const APPS_INTERVAL: Duration = Duration::from_secs(10);
+/// How often /var/log and /var/log/journal are re-listed.
+const LOG_FILES_INTERVAL: Duration = Duration::from_secs(5);
+
+/// sysinfo instance with the refresh kinds rtop uses.
+fn new_system() -> System {
+    let refresh = RefreshKind::nothing()
+        .with_cpu(CpuRefreshKind::everything())
+        .with_processes(ProcessRefreshKind::everything());
+    System::new_with_specifics(refresh)
+}
+
+/// A freshly refreshed sysinfo instance plus sensors and per-process data read alongside it.
+struct SystemSnapshot {
+    sys: System,
+    cpu_temp_c: Option<f32>,
+    io_rates: Option<std::collections::HashMap<i32, (f64, f64)>>, // set while I/O columns are shown
+    ppids: Option<std::collections::HashMap<i32, Option<i32>>>,  // set in tree mode
+    sort_values: Option<(ProcColumn, std::collections::HashMap<i32, ProcSortVal>)>, // set while sorted by a /proc column
+}
+
+/// One worker thread per data source, so a slow `systemctl`, a hung mount or a large /var/log
+/// only delays its own frame; the UI loop just takes the newest snapshots.
+struct Collectors {
+    services: ServiceCollector,
+    system: Collector<SystemSnapshot>,
+    system_return: std::sync::mpsc::Sender<System>, // drawn instances go back to be refreshed
+    want_io: Arc<AtomicBool>,    // READ/s or WRITE/s column shown
+    want_ppids: Arc<AtomicBool>, // process tree shown
+    want_sort: Arc<Mutex<Option<ProcColumn>>>, // /proc column the process table is sorted by
+    services_sort: Option<(ServiceColumn, bool)>, // order app.services was last sorted in
+    net: Collector<std::collections::HashMap<String, (f64, f64)>>,
+    disks: Collector<Vec<DiskInfo>>,
+    apps: Collector<Vec<(String, String, String)>>,
+    failed_units: Collector<Vec<services::FailedUnit>>,
+    log_files: Collector<Vec<LogEntry>>,
+    journal_files: Collector<Vec<LogEntry>>,
+}
+
+impl Collectors {
+    fn spawn() -> Self {
+        // sysinfo computes CPU usage from consecutive refreshes of the same instance, so two
+        // instances take turns: the worker refreshes one while the UI draws the other (usage
+        // figures therefore span the last two refresh intervals)
+        let (system_return, returned) = std::sync::mpsc::channel::<System>();
+        let mut spare = Some(new_system());
+        let want_io = Arc::new(AtomicBool::new(false));
+        let want_ppids = Arc::new(AtomicBool::new(false));
+        let want_sort = Arc::new(Mutex::new(None));
+        let (io_wanted, ppids_wanted, sort_wanted) = (want_io.clone(), want_ppids.clone(), want_sort.clone());
+        let mut io_prev: std::collections::HashMap<i32, (u64, u64)> = std::collections::HashMap::new();
+        let mut io_last = Instant::now();
+        let uid_name = load_uid_names();
+        let system = Collector::spawn("system", SYSTEM_INTERVAL, move |_| {
+            let mut sys = returned.try_recv().ok().or_else(|| spare.take())?;
+            sys.refresh_cpu_all();
+            sys.refresh_processes(ProcessesToUpdate::All, true);
+            sys.refresh_memory();
+            // Forget old counters while hidden so the columns start from 0 when shown again
+            if !io_wanted.load(Ordering::Relaxed) { io_prev.clear(); }
+            let io_rates = io_wanted.load(Ordering::Relaxed).then(|| process_io_rates(&sys, &mut io_prev, &mut io_last));
+            let ppids = ppids_wanted.load(Ordering::Relaxed).then(|| {
+                sys.processes().keys().map(|pid| pid.as_u32() as i32).map(|pid| (pid, get_process_ppid(pid))).collect()
+            });
+            let sort_key = sort_wanted.lock().ok().and_then(|k| *k);
+            let sort_values = sort_key.map(|key| {
+                (key, sys.processes().keys().map(|pid| pid.as_u32() as i32).map(|pid| (pid, read_proc_sort_value(key, pid, &uid_name))).collect())
+            });
+            Some(SystemSnapshot { sys, cpu_temp_c: read_cpu_temperature_c(), io_rates, ppids, sort_values })
+        });
+        let mut prev: std::collections::HashMap<String, (u64, u64)> = std::collections::HashMap::new();
+        let mut last = Instant::now();
+        let net = Collector::spawn("net", NET_INTERVAL, move |_| {
+            let now = Instant::now();
+            let dt = now.saturating_duration_since(last).as_secs_f64();
+            last = now;
+            let mut rates = std::collections::HashMap::new();
+            let mut counters = std::collections::HashMap::new();
+            for (iface, rx, tx) in read_network_counters() {
+                // No previous data (first sample or new interface): show 0
+                let rate = match prev.get(&iface) {
+                    Some((prx, ptx)) if dt > 0.0 => (rx.saturating_sub(*prx) as f64 / dt, tx.saturating_sub(*ptx) as f64 / dt),
+                    _ => (0.0, 0.0),
+                };
+                rates.insert(iface.clone(), rate);
+                counters.insert(iface, (rx, tx));
+            }
+            prev = counters;
+            Some(rates)
+        });
+        Collectors {
+            // Unit list (systemd D-Bus API, or systemctl when the bus is unavailable)
+            services: ServiceCollector::new(false),
+            system,
+            system_return,
+            want_io,
+            want_ppids,
+            want_sort,
+            services_sort: None,
+            net,
+            disks: Collector::spawn("disks", DISKS_INTERVAL, |_| Some(list_disks_best_effort())),
+            apps: Collector::spawn("apps", APPS_INTERVAL, |_| Some(build_applications_status())),
+            failed_units: Collector::spawn("failed-units", FAILED_UNITS_INTERVAL, |_| Some(list_failed_units())),
+            log_files: Collector::spawn("log-files", LOG_FILES_INTERVAL, |_| Some(list_var_log_files())),
+            journal_files: Collector::spawn("journal-files", LOG_FILES_INTERVAL, |_| Some(list_journal_files())),
+        }
+    }
+
+    /// Move the newest snapshots into the app. Dashboard-only sources pause on other tabs.
+    /// Returns whether `sys` was replaced by a new system snapshot.
+    fn collect(&mut self, app: &mut App, sys: &mut System) -> bool {
+        if self.services.is_user() != app.services_user {
+            // Switched between the system and user manager: start over with the other bus
+            self.services = ServiceCollector::new(app.services_user);
+            app.services.clear();
+            app.services_usage.clear();
+        }
+        // Per-unit cgroup usage is only read while the Services tab is visible
+        self.services.set_usage_wanted(app.selected_top_tab == 2);
+        if std::mem::take(&mut app.services_dirty) { self.services.reload(); }
+        if let Some(unit) = app.service_details_wanted.take() { self.services.request_details(unit); }
+        // Re-sort only when a new list or usage arrives or the sort column changes, keeping the
+        // selected unit selected
+        let services_snapshot = self.services.take();
+        let services_sort = (app.services_sort_key, app.services_sort_desc);
+        let resort = services_snapshot.as_ref().is_some_and(|s| s.units.is_some() || s.usage.is_some()) || self.services_sort != Some(services_sort);
+        let selected_unit = resort.then(|| shown_services(app).get(app.services_selected).map(|u| u.name.clone())).flatten();
+        if let Some(snapshot) = services_snapshot {
+            if let Some(list) = snapshot.units { app.services = list; }
+            if let Some(usage) = snapshot.usage { app.services_usage = usage; }
+            // Unless the popup was closed or moved on to another unit (or an action report) meanwhile
+            if let Some((unit, text)) = snapshot.details && app.service_popup && app.service_detail_title == unit && app.service_detail_text == SERVICE_DETAILS_LOADING {
+                app.service_detail_text = text;
+            }
+            app.services_source = snapshot.source;
+        }
+        if resort {
+            sort_services(&mut app.services, &app.services_usage, app.services_sort_key, app.services_sort_desc);
+            self.services_sort = Some(services_sort);
+            let shown = shown_services(app);
+            app.services_selected = selected_unit
+                .and_then(|name| shown.iter().position(|u| u.name == name))
+                .unwrap_or(app.services_selected)
+                .min(shown.len().saturating_sub(1));
+        }
+        let dashboard = app.selected_top_tab == 0;
+        self.disks.set_enabled(dashboard);
+        self.apps.set_enabled(dashboard);
+        self.failed_units.set_enabled(dashboard);
+        // Per-process I/O rates, parents and /proc sort values are only read while needed;
+        // refresh at once when turned on
+        let io = app.procs_columns.iter().any(|c| c.is_io());
+        let tree = app.procs_tree_mode;
+        let sort = Some(app.procs_sort_key).filter(|k| k.sorts_from_proc());
+        let io_shown = !self.want_io.swap(io, Ordering::Relaxed) && io;
+        let tree_shown = !self.want_ppids.swap(tree, Ordering::Relaxed) && tree;
+        let sort_changed = self.want_sort.lock().is_ok_and(|mut k| std::mem::replace(&mut *k, sort) != sort) && sort.is_some();
+        if io_shown || tree_shown || sort_changed { self.system.wake(); }
+        let sys_updated = if let Some(snapshot) = self.system.take() {
+            let drawn = std::mem::replace(sys, snapshot.sys);
+            let _ = self.system_return.send(drawn);
+            app.cpu_temp_c = snapshot.cpu_temp_c;
+            if let Some(rates) = snapshot.io_rates { app.procs_io_rates = rates; }
+            if let Some(ppids) = snapshot.ppids { app.procs_ppids = ppids; }
+            app.procs_sort_values = snapshot.sort_values;
+            true
+        } else {
+            false
+        };
+        if let Some(rates) = self.net.take() { app.net_rates = rates; }
+        if let Some(disks) = self.disks.take() { app.disks = disks; }
+        if let Some(apps) = self.apps.take() { app.apps_status = apps; }
+        if let Some(failed) = self.failed_units.take() { app.failed_units = failed; }
+        if let Some(files) = self.log_files.take() { app.log_files = files; }
+        if let Some(files) = self.journal_files.take() { app.journal_files = files; }
+        sys_updated
+    }
+}
+
+/// Command line shown in the CMD column (falls back to the process name).
+fn process_cmd_string(p: &sysinfo::Process) -> String {
+    if !p.cmd().is_empty() { p.cmd().join(std::ffi::OsStr::new(" ")).to_string_lossy().into_owned() } else { p.name().to_string_lossy().into_owned() }
+}
+
+/// Fall back to CPU% (or the first column) when the sort column is no longer shown.
+fn ensure_sort_column_visible(app: &mut App) {
+    if app.procs_columns.contains(&app.procs_sort_key) { return; }
+    app.procs_sort_key = if app.procs_columns.contains(&ProcColumn::Cpu) { ProcColumn::Cpu } else { app.procs_columns.first().cloned().unwrap_or(ProcColumn::Pid) };
+    app.procs_sort_desc = app.procs_sort_key.default_descending();
+}
+
+/// Per-process READ/s and WRITE/s from /proc/<pid>/io deltas since the previous call.
+fn process_io_rates(sys: &System, prev: &mut std::collections::HashMap<i32, (u64, u64)>, last: &mut Instant) -> std::collections::HashMap<i32, (f64, f64)> {
+    let now = Instant::now();
+    let dt = now.saturating_duration_since(*last).as_secs_f64();
+    let mut new_prev = std::collections::HashMap::new();
+    let mut io_rates = std::collections::HashMap::new();
+    for pid in sys.processes().keys() {
+        let pid = pid.as_u32() as i32;
+        let Some((rd, wr)) = read_process_io(pid) else { continue; };
+        let rates = match prev.get(&pid) {
+            Some((prd, pwr)) if dt > 0.0 => (rd.saturating_sub(*prd) as f64 / dt, wr.saturating_sub(*pwr) as f64 / dt),
+            // No previous data; show 0 for the first update
+            _ => (0.0, 0.0),
+        };
+        io_rates.insert(pid, rates);
+        new_prev.insert(pid, (rd, wr));
+    }
+    *prev = new_prev;
+    *last = now;
+    io_rates
+}
+
+/// Format a bytes/sec rate for the READ/s and WRITE/s columns ("-" when unreadable).
+fn fmt_io_rate(rate: Option<f64>) -> String {
+    match rate {
+        None => String::from("-"),
+        Some(r) if r < 1.0 => String::from("0"),
+        Some(r) => fmt_bytes(r as u64),
+    }
+}
+
+/// Per-process sort value: numbers for usage/identity columns, text for USER/CMD/etc.,
+/// Missing for unreadable I/O counters and /proc values not collected yet.
+#[derive(Clone)]
+enum ProcSortVal { Num(f64), Text(String), Missing }
+
+/// Sort value of a column that `ProcColumn::sorts_from_proc` (read on the system collector thread).
+fn read_proc_sort_value(key: ProcColumn, pid: i32, uid_name: &std::collections::HashMap<u32, String>) -> ProcSortVal {
+    match key {
+        ProcColumn::User => ProcSortVal::Text(get_process_user(pid, uid_name)),
+        ProcColumn::Pri => ProcSortVal::Num(get_process_pri_nice(pid).0 as f64),
+        ProcColumn::Ni => ProcSortVal::Num(get_process_pri_nice(pid).1 as f64),
+        ProcColumn::State => ProcSortVal::Text(read_process_extra(pid, false).state.to_string()),
+        ProcColumn::Threads => ProcSortVal::Num(read_process_extra(pid, false).threads as f64),
+        ProcColumn::Shr => ProcSortVal::Num(read_process_extra(pid, false).shared as f64),
+        ProcColumn::Tty => ProcSortVal::Text(read_process_extra(pid, false).tty),
+        ProcColumn::Cgroup => ProcSortVal::Text(read_process_cgroup(pid)),
+        ProcColumn::Container => ProcSortVal::Text(container_from_cgroup(&read_process_cgroup(pid))),
+        _ => ProcSortVal::Missing,
+    }
+}
+
+/// Return all PIDs ordered by the given process table column.
+///
+/// Ties fall back to ascending PID so the order is stable between ticks. For the I/O
+/// columns, processes whose /proc/<pid>/io is unreadable always sort last (like iotop,
+/// the busiest processes come first when descending). Columns read from /proc come from
+/// `proc_values`, the system snapshot's values for `key`.
+fn sort_process_pids(sys: &System, key: ProcColumn, desc: bool, io_rates: &std::collections::HashMap<i32, (f64, f64)>, proc_values: Option<&std::collections::HashMap<i32, ProcSortVal>>) -> Vec<i32> {
+    use std::cmp::Ordering;
+    let mut keyed: Vec<(i32, ProcSortVal)> = sys
+        .processes()
+        .iter()
+        .map(|(pid, p)| {
+            let pid = pid.as_u32() as i32;
+            let val = match key {
+                ProcColumn::Pid => ProcSortVal::Num(pid as f64),
+                ProcColumn::Cpu => ProcSortVal::Num(p.cpu_usage() as f64),
+                ProcColumn::Mem => ProcSortVal::Num(p.memory() as f64),
+                ProcColumn::Read => io_rates.get(&pid).map(|r| ProcSortVal::Num(r.0)).unwrap_or(ProcSortVal::Missing),
+                ProcColumn::Write => io_rates.get(&pid).map(|r| ProcSortVal::Num(r.1)).unwrap_or(ProcSortVal::Missing),
+                ProcColumn::Time => ProcSortVal::Num(p.run_time() as f64),
+                ProcColumn::Cmd => ProcSortVal::Text(process_cmd_string(p).to_lowercase()),
+                ProcColumn::Ppid => ProcSortVal::Num(p.parent().map(|pp| pp.as_u32() as f64).unwrap_or(0.0)),
+                ProcColumn::Rss => ProcSortVal::Num(p.memory() as f64),
+                ProcColumn::Virt => ProcSortVal::Num(p.virtual_memory() as f64),
+                ProcColumn::Start => ProcSortVal::Num(p.start_time() as f64),
+                ProcColumn::Exe => ProcSortVal::Text(p.exe().map(|e| e.to_string_lossy().into_owned()).unwrap_or_default()),
+                ProcColumn::User | ProcColumn::Pri | ProcColumn::Ni | ProcColumn::State | ProcColumn::Threads | ProcColumn::Shr | ProcColumn::Tty | ProcColumn::Cgroup | ProcColumn::Container => {
+                    proc_values.and_then(|v| v.get(&pid)).cloned().unwrap_or(ProcSortVal::Missing)
+                }
+            };
+            (pid, val)
+        })
+        .collect();
+    keyed.sort_by(|a, b| {
+        let ord = match (&a.1, &b.1) {
+            (ProcSortVal::Num(x), ProcSortVal::Num(y)) => x.partial_cmp(y).unwrap_or(Ordering::Equal),
+            (ProcSortVal::Text(x), ProcSortVal::Text(y)) => x.cmp(y),
+            (ProcSortVal::Missing, ProcSortVal::Missing) => Ordering::Equal,
+            (ProcSortVal::Missing, _) => return Ordering::Greater,
+            (_, ProcSortVal::Missing) => return Ordering::Less,
+            _ => Ordering::Equal,
+        };
+        let ord = if desc { ord.reverse() } else { ord };
+        ord.then(a.0.cmp(&b.0))
+    });
+    keyed.into_iter().map(|(pid, _)| pid).collect()
+}
+
 /// Handle a single key event.
 ///
 /// Returns Ok(true) to request application exit.
@@ -234,71 +635,309 @@ fn handle_key(key: KeyEvent, app: &mut App) -> Result<bool, Box<dyn Error>> {
             return Ok(false);
         }
     }
-    // Close service details popup on Esc or Enter
-    if app.service_popup {
+    // Unit action menu (Services tab): choose an action, then confirm
+    if app.service_action_popup {
+        let n = SERVICE_ACTIONS.len();
+        if app.service_action_confirm {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                    app.service_action_popup = false;
+                    run_selected_service_action(app);
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => { app.service_action_confirm = false; }
+                _ => {}
+            }
+        } else {
+            match key.code {
+                KeyCode::Esc => { app.service_action_popup = false; }
+                KeyCode::Up => { app.service_action_selected = (app.service_action_selected + n - 1) % n; }
+                KeyCode::Down => { app.service_action_selected = (app.service_action_selected + 1) % n; }
+                KeyCode::Enter => { app.service_action_confirm = true; }
+                _ => {}
+            }
+        }
+        return Ok(false);
+    }
+    // Service details popup: Left/Right/Tab switch tabs, arrows scroll; in Dependencies the
+    // arrows select a unit, Enter descends into it, Backspace goes back and r reverses
+    if app.service_popup && !app.logs_password_prompt {
+        let deps_tab = app.service_detail_tab == 2;
+        let text_lines = match app.service_detail_tab { 0 => app.service_detail_text.lines().count(), _ => app.service_tab_text.lines().count() };
+        let max_scroll = text_lines.saturating_sub(1);
+        let max_sel = app.service_deps.len().saturating_sub(1);
         match key.code {
-            KeyCode::Esc | KeyCode::Enter => {
+            KeyCode::Esc => { app.service_popup = false; }
+            KeyCode::Enter if deps_tab => {
+                if let Some(row) = app.service_deps.get(app.service_deps_selected).filter(|_| app.service_deps_selected > 0) {
+                    let unit = row.unit.clone();
+                    let prev = std::mem::replace(&mut app.service_deps_root, unit);
+                    app.service_deps_history.push(prev);
+                    load_service_deps(app);
+                }
+            }
+            KeyCode::Enter => { app.service_popup = false; }
+            KeyCode::Backspace if deps_tab => {
+                if let Some(prev) = app.service_deps_history.pop() {
+                    app.service_deps_root = prev;
+                    load_service_deps(app);
+                }
+            }
+            KeyCode::Char('r') if deps_tab => {
+                app.service_deps_reverse = !app.service_deps_reverse;
+                load_service_deps(app);
+            }
+            KeyCode::Char('j') => {
+                // Switch to the unit's journal
                 app.service_popup = false;
-                return Ok(false);
+                let unit = app.service_detail_title.clone();
+                open_unit_journal(app, &unit);
+            }
+            KeyCode::Left | KeyCode::BackTab | KeyCode::Right | KeyCode::Tab => {
+                let n = SERVICE_DETAIL_TABS.len();
+                let tab = match key.code {
+                    KeyCode::Left | KeyCode::BackTab => (app.service_detail_tab + n - 1) % n,
+                    _ => (app.service_detail_tab + 1) % n,
+                };
+                select_service_tab(app, tab);
+            }
+            KeyCode::Char(c) if c.is_ascii_digit() && c != '0' => {
+                // 1..4 jump straight to a tab
+                let idx = c as usize - '1' as usize;
+                if idx < SERVICE_DETAIL_TABS.len() { select_service_tab(app, idx); }
             }
+            KeyCode::Up if deps_tab => { app.service_deps_selected = app.service_deps_selected.saturating_sub(1); }
+            KeyCode::Down if deps_tab => { app.service_deps_selected = (app.service_deps_selected + 1).min(max_sel); }
+            KeyCode::PageUp if deps_tab => { app.service_deps_selected = app.service_deps_selected.saturating_sub(10); }
+            KeyCode::PageDown if deps_tab => { app.service_deps_selected = (app.service_deps_selected + 10).min(max_sel); }
+            KeyCode::Home if deps_tab => { app.service_deps_selected = 0; }
+            KeyCode::End if deps_tab => { app.service_deps_selected = max_sel; }
+            KeyCode::Up => { app.service_detail_scroll = app.service_detail_scroll.saturating_sub(1); }
+            KeyCode::Down => { app.service_detail_scroll = (app.service_detail_scroll + 1).min(max_scroll); }
+            KeyCode::PageUp => { app.service_detail_scroll = app.service_detail_scroll.saturating_sub(10); }
+            KeyCode::PageDown => { app.service_detail_scroll = (app.service_detail_scroll + 10).min(max_scroll); }
+            KeyCode::Home => { app.service_detail_scroll = 0; }
+            KeyCode::End => { app.service_detail_scroll = max_scroll; }
+            KeyCode::F(10) => return Ok(true),
             _ => {}
         }
+        return Ok(false);
     }
-    // Close process details popup on Esc or Enter
+    // Thread view popup: scrolling and close
+    if app.threads_popup {
+        let max_scroll = app.threads_rows.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter => { app.threads_popup = false; }
+            KeyCode::Up => { app.threads_scroll = app.threads_scroll.saturating_sub(1); }
+            KeyCode::Down => { app.threads_scroll = (app.threads_scroll + 1).min(max_scroll); }
+            KeyCode::PageUp => { app.threads_scroll = app.threads_scroll.saturating_sub(10); }
+            KeyCode::PageDown => { app.threads_scroll = (app.threads_scroll + 10).min(max_scroll); }
+            KeyCode::Home => { app.threads_scroll = 0; }
+            KeyCode::End => { app.threads_scroll = max_scroll; }
+            _ => {}
+        }
+        return Ok(false);
+    }
+    // Process details popup: Left/Right/Tab switch tabs, arrows scroll, T drills down into threads
     if app.process_popup {
+        let line_count = app.process_detail_text.lines().count();
+        let max_scroll = line_count.saturating_sub(1);
         match key.code {
-            KeyCode::Esc | KeyCode::Enter => {
+            KeyCode::Esc | KeyCode::Enter => { app.process_popup = false; }
+            KeyCode::Char('T') => {
                 app.process_popup = false;
-                return Ok(false);
+                open_thread_view(app, app.process_detail_pid);
             }
+            KeyCode::Left | KeyCode::BackTab | KeyCode::Right | KeyCode::Tab => {
+                let n = PROCESS_DETAIL_TABS.len();
+                app.process_detail_tab = match key.code {
+                    KeyCode::Left | KeyCode::BackTab => (app.process_detail_tab + n - 1) % n,
+                    _ => (app.process_detail_tab + 1) % n,
+                };
+                app.process_detail_scroll = 0;
+                app.process_detail_text = get_process_detail_tab(app.process_detail_pid, app.process_detail_tab);
+            }
+            KeyCode::Char(c) if c.is_ascii_digit() && c != '0' => {
+                // 1..8 jump straight to a tab
+                let idx = c as usize - '1' as usize;
+                if idx < PROCESS_DETAIL_TABS.len() {
+                    app.process_detail_tab = idx;
+                    app.process_detail_scroll = 0;
+                    app.process_detail_text = get_process_detail_tab(app.process_detail_pid, idx);
+                }
+            }
+            KeyCode::Up => { app.process_detail_scroll = app.process_detail_scroll.saturating_sub(1); }
+            KeyCode::Down => { app.process_detail_scroll = (app.process_detail_scroll + 1).min(max_scroll); }
+            KeyCode::PageUp => { app.process_detail_scroll = app.process_detail_scroll.saturating_sub(10); }
+            KeyCode::PageDown => { app.process_detail_scroll = (app.process_detail_scroll + 10).min(max_scroll); }
+            KeyCode::Home => { app.process_detail_scroll = 0; }
+            KeyCode::End => { app.process_detail_scroll = max_scroll; }
             _ => {}
         }
+        return Ok(false);
     }
-    // Log/Journal popup: scrolling and close
-    if app.log_popup {
+    // Log popup search prompt: the pattern is compiled (and highlighted) as it is typed
+    if app.log_popup && app.log_search_prompt && !app.logs_password_prompt {
         match key.code {
+            KeyCode::Esc => {
+                app.log_search_prompt = false;
+                app.log_search_input.clear();
+                app.log_search = None;
+                app.log_search_error.clear();
+                app.log_search_current = None;
+                app.log_search_pending = None;
+            }
+            KeyCode::Enter => {
+                app.log_search_prompt = false;
+                app.log_search_current = None;
+                app.log_search_pending = None;
+                log_search_jump(app, true);
+            }
+            KeyCode::Tab => {
+                app.log_search_case = !app.log_search_case;
+                apply_log_search_input(app);
+            }
+            KeyCode::F(10) => return Ok(true),
+            KeyCode::Backspace => {
+                app.log_search_input.pop();
+                apply_log_search_input(app);
+            }
+            KeyCode::Char(c) if !c.is_control() => {
+                app.log_search_input.push(c);
+                apply_log_search_input(app);
+            }
+            _ => {}
+        }
+        return Ok(false);
+    }
+    // Log/Journal popup: scrolling, search and close; unit journals also take follow/priority/boot keys
+    if app.log_popup && !app.logs_password_prompt {
+        match key.code {
+            KeyCode::Esc if app.log_search.is_some() => {
+                // First Esc clears the search, the next one closes the popup
+                app.log_search_input.clear();
+                app.log_search = None;
+                app.log_search_current = None;
+                app.log_search_pending = None;
+                return Ok(false);
+            }
             KeyCode::Esc | KeyCode::Enter => {
                 app.log_popup = false;
-                app.log_popup_scroll = 0;
+                app.log_doc = LogDoc::default();
+                app.log_top = 0;
+                app.log_severity = None;
+                app.log_journal_unit = None;
+                app.log_journal_file = None;
+                app.log_follow = false;
+                app.log_file_path = None;
+                app.log_tail = None;
+                app.log_load = None;
+                app.log_search_input.clear();
+                app.log_search = None;
+                app.log_search_error.clear();
+                app.log_search_current = None;
+                app.log_search_pending = None;
+                return Ok(false);
+            }
+            KeyCode::Char('/') => {
+                app.log_search_prompt = true;
+                app.log_search_input.clear();
+                apply_log_search_input(app);
+                return Ok(false);
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') if app.log_search.is_some() => {
+                log_search_jump(app, key.code == KeyCode::Char('n'));
+                return Ok(false);
+            }
+            KeyCode::Char('c') => {
+                app.log_search_case = !app.log_search_case;
+                apply_log_search_input(app);
+                return Ok(false);
+            }
+            KeyCode::Char('f') if app.log_file_path.is_some() && app.log_load.is_none() => {
+                toggle_log_file_follow(app);
+                return Ok(false);
+            }
+            KeyCode::Char('R') if app.log_file_path.is_some() => {
+                // Switch between the file alone and the file with all its rotations
+                let Some(path) = app.log_file_path.clone() else { return Ok(false); };
+                let stitched = !app.log_stitched;
+                let (doc, load) = open_log_file_best_effort(&path, stitched, app.logs_sudo_password.as_deref())
+                    .unwrap_or_else(|e| (LogDoc::from_text(&format!("Failed to read: {}", e)), None));
+                show_log_doc(app, log_file_title(&path, stitched), doc, Some(path));
+                app.log_stitched = stitched;
+                app.log_load = load;
+                app.log_load_to_end = true;
+                log_popup_scroll_to_end(app);
+                return Ok(false);
+            }
+            KeyCode::Char('f') if app.log_journal_unit.is_some() => {
+                // Reload, so following continues right after the entries shown
+                app.log_follow = !app.log_follow;
+                if app.log_follow { load_unit_journal(app); } else { app.log_tail = None; }
+                app.log_detail_title = unit_journal_title(app);
+                return Ok(false);
+            }
+            KeyCode::Char('p') | KeyCode::Char('P') if app.log_journal_unit.is_some() || app.log_journal_file.is_some() => {
+                // p: stricter (fewer, more severe entries), P: looser; short-iso lines carry no
+                // level, so journals are filtered by journalctl
+                let n = JOURNAL_PRIORITIES.len();
+                app.log_journal_priority = if key.code == KeyCode::Char('p') { (app.log_journal_priority + 1).min(n - 1) } else { app.log_journal_priority.saturating_sub(1) };
+                if app.log_journal_unit.is_some() { load_unit_journal(app); } else { load_journal_file(app); }
+                return Ok(false);
+            }
+            KeyCode::Char('p') | KeyCode::Char('P') => {
+                // Log files: hide lines below the chosen severity
+                let steps = &Severity::FILTERS;
+                let i = steps.iter().position(|s| *s == app.log_severity).unwrap_or(0);
+                let i = if key.code == KeyCode::Char('p') { (i + 1).min(steps.len() - 1) } else { i.saturating_sub(1) };
+                set_log_severity(app, steps[i]);
+                return Ok(false);
+            }
+            KeyCode::Char('b') | KeyCode::Char('B') if app.log_journal_unit.is_some() => {
+                // Boot offsets run newer to older: all boots, 0 (current), -1, -2, ...
+                app.log_journal_boot = match (key.code, app.log_journal_boot) {
+                    (KeyCode::Char('b'), None) => Some(0),
+                    (KeyCode::Char('b'), Some(b)) => Some(b - 1),
+                    (_, Some(0)) | (_, None) => None,
+                    (_, Some(b)) => Some(b + 1),
+                };
+                load_unit_journal(app);
+                return Ok(false);
+            }
+            KeyCode::Char('s') if app.log_journal_unit.is_some() && app.log_journal_limited && !app.log_journal_user => {
+                // Re-read through sudo to include entries we may not read
+                if app.logs_sudo_password.is_some() {
+                    app.log_journal_sudo = true;
+                    load_unit_journal(app);
+                } else {
+                    app.log_journal_sudo_pending = true;
+                    app.logs_password_prompt = true;
+                    app.logs_password_error.clear();
+                }
                 return Ok(false);
             }
             KeyCode::Up | KeyCode::Left => {
-                if app.log_popup_scroll > 0 { app.log_popup_scroll -= 1; }
+                log_popup_scroll_by(app, -1);
                 return Ok(false);
             }
             KeyCode::Down | KeyCode::Right => {
-                // compute visible rows and clamp
-                let (_, term_h) = crossterm::terminal::size().unwrap_or((80, 24));
-                let vis = term_h.saturating_sub(2) as usize; // inner height (popup borders)
-                let total = app.log_detail_text.lines().count();
-                let max_start = total.saturating_sub(vis);
-                if app.log_popup_scroll < max_start { app.log_popup_scroll += 1; }
+                log_popup_scroll_by(app, 1);
                 return Ok(false);
             }
             KeyCode::PageUp => {
-                let (_, term_h) = crossterm::terminal::size().unwrap_or((80, 24));
-                let vis = term_h.saturating_sub(2) as usize;
-                app.log_popup_scroll = app.log_popup_scroll.saturating_sub(vis.max(1));
+                log_popup_scroll_by(app, -(log_popup_rows() as i64));
                 return Ok(false);
             }
             KeyCode::PageDown => {
-                let (_, term_h) = crossterm::terminal::size().unwrap_or((80, 24));
-                let vis = term_h.saturating_sub(2) as usize;
-                let total = app.log_detail_text.lines().count();
-                let max_start = total.saturating_sub(vis);
-                let step = vis.max(1);
-                app.log_popup_scroll = (app.log_popup_scroll + step).min(max_start);
+                log_popup_scroll_by(app, log_popup_rows() as i64);
                 return Ok(false);
             }
             KeyCode::Home => {
-                app.log_popup_scroll = 0;
+                app.log_top = 0;
                 return Ok(false);
             }
             KeyCode::End => {
-                let (_, term_h) = crossterm::terminal::size().unwrap_or((80, 24));
-                let vis = term_h.saturating_sub(2) as usize;
-                let total = app.log_detail_text.lines().count();
-                app.log_popup_scroll = total.saturating_sub(vis);
+                log_popup_scroll_to_end(app);
                 return Ok(false);
             }
             _ => {}
@@ -311,6 +950,19 @@ fn handle_key(key: KeyEvent, app: &mut App) -> Result<bool, Box<dyn Error>> {
                 app.logs_password_prompt = false;
                 app.logs_password_input.clear();
                 app.logs_password_error.clear();
+                if app.signal_sudo_pending {
+                    app.signal_sudo_pending = false;
+                    app.signal_result = String::from("Cancelled: no sudo password given.");
+                }
+                if app.tune_sudo_pending {
+                    app.tune_sudo_pending = false;
+                    app.tune_result = String::from("Cancelled: no sudo password given.");
+                }
+                if app.service_action_sudo_pending {
+                    app.service_action_sudo_pending = false;
+                    app.service_detail_text.push_str("\nCancelled: no sudo password given.");
+                }
+                app.log_journal_sudo_pending = false;
                 return Ok(false);
             }
             KeyCode::Enter => {
@@ -326,17 +978,46 @@ fn handle_key(key: KeyEvent, app: &mut App) -> Result<bool, Box<dyn Error>> {
                         let path = app.logs_pending_path.clone();
                         let title = std::path::Path::new(&path).file_name().and_then(|s| s.to_str()).unwrap_or(&path).to_string();
                         let is_journal = path.starts_with("/var/log/journal");
-                        let res = if is_journal {
-                            read_journal_file_best_effort(&path, app.logs_sudo_password.as_deref())
+                        let stitched = app.logs_pending_stitched;
+                        if is_journal {
+                            match read_journal_file_best_effort(&path, 0, app.logs_sudo_password.as_deref()) {
+                                Ok((doc, load)) => show_journal_file(app, &path, doc, load),
+                                Err(err) => show_log_doc(app, title, LogDoc::from_text(&format!("Failed to read: {}", err)), None),
+                            }
                         } else {
-                            read_log_file_best_effort(&path, app.logs_sudo_password.as_deref())
-                        };
-                        match res {
-                            Ok(text) => { app.log_detail_title = title; app.log_detail_text = text; app.log_popup_scroll = 0; app.log_popup = true; }
-                            Err(err) => { app.log_detail_title = title; app.log_detail_text = format!("Failed to read: {}", err); app.log_popup = true; }
+                            let title = log_file_title(&path, stitched);
+                            match open_log_file_best_effort(&path, stitched, app.logs_sudo_password.as_deref()) {
+                                Ok((doc, load)) => {
+                                    show_log_doc(app, title, doc, Some(path));
+                                    app.log_stitched = stitched;
+                                    app.log_load = load;
+                                }
+                                Err(err) => show_log_doc(app, title, LogDoc::from_text(&format!("Failed to read: {}", err)), None),
+                            }
                         }
                         app.logs_pending_path.clear();
                     }
+                    // Or retry a signal that was denied
+                    if app.signal_sudo_pending {
+                        app.signal_sudo_pending = false;
+                        deliver_signal_sudo(app);
+                    }
+                    // Or retry a scheduling change that was denied
+                    if app.tune_sudo_pending {
+                        app.tune_sudo_pending = false;
+                        apply_tuning_sudo(app);
+                    }
+                    // Or retry a unit action that was denied
+                    if app.service_action_sudo_pending {
+                        app.service_action_sudo_pending = false;
+                        run_selected_service_action_sudo(app);
+                    }
+                    // Or re-read a unit journal with sudo
+                    if app.log_journal_sudo_pending {
+                        app.log_journal_sudo_pending = false;
+                        app.log_journal_sudo = true;
+                        load_unit_journal(app);
+                    }
                 }
                 return Ok(false);
             }
@@ -355,7 +1036,168 @@ fn handle_key(key: KeyEvent, app: &mut App) -> Result<bool, Box<dyn Error>> {
         }
     }
 
+    // Signal menu popup (top/htop): choose, confirm, and report
+    if app.signal_popup && !app.logs_password_prompt {
+        match app.signal_stage {
+            SignalStage::Menu => match key.code {
+                KeyCode::Esc => { app.signal_popup = false; }
+                KeyCode::Up => { app.signal_selected = app.signal_selected.saturating_sub(1); }
+                KeyCode::Down => { app.signal_selected = (app.signal_selected + 1).min(SIGNALS.len()); }
+                KeyCode::Home => { app.signal_selected = 0; }
+                KeyCode::End => { app.signal_selected = SIGNALS.len(); }
+                KeyCode::Enter => {
+                    if let Some((_, num)) = SIGNALS.get(app.signal_selected) {
+                        app.signal_number = *num;
+                        app.signal_stage = SignalStage::Confirm;
+                    } else {
+                        // Last entry: arbitrary signal number
+                        app.signal_custom_input.clear();
+                        app.signal_result.clear();
+                        app.signal_stage = SignalStage::Custom;
+                    }
+                }
+                _ => {}
+            },
+            SignalStage::Custom => match key.code {
+                KeyCode::Esc => { app.signal_stage = SignalStage::Menu; }
+                KeyCode::Backspace => { app.signal_custom_input.pop(); }
+                KeyCode::Char(c) if c.is_ascii_digit() && app.signal_custom_input.len() < 3 => {
+                    app.signal_custom_input.push(c);
+                }
+                KeyCode::Enter => {
+                    match app.signal_custom_input.parse::<i32>() {
+                        Ok(n) if (1..=64).contains(&n) => {
+                            app.signal_number = n;
+                            app.signal_result.clear();
+                            app.signal_stage = SignalStage::Confirm;
+                        }
+                        _ => { app.signal_result = String::from("Enter a signal number between 1 and 64."); }
+                    }
+                }
+                _ => {}
+            },
+            SignalStage::Confirm => match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => { deliver_signal(app); }
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => { app.signal_stage = SignalStage::Menu; }
+                _ => {}
+            },
+            SignalStage::Result => match key.code {
+                KeyCode::Char('s') | KeyCode::Char('S') if app.signal_denied => {
+                    if app.logs_sudo_password.is_some() {
+                        deliver_signal_sudo(app);
+                    } else {
+                        // Reuse the Logs sudo prompt; the signal is retried once a password is entered
+                        app.signal_sudo_pending = true;
+                        app.logs_password_prompt = true;
+                        app.logs_password_error.clear();
+                    }
+                }
+                KeyCode::Esc | KeyCode::Enter => { app.signal_popup = false; }
+                _ => {}
+            },
+        }
+        return Ok(false);
+    }
 
+    // Column chooser (top/htop): Space toggles, [ and ] reorder, Enter applies and saves
+    if app.columns_popup {
+        let n = app.columns_edit.len();
+        let cur = app.columns_cursor.min(n.saturating_sub(1));
+        match key.code {
+            KeyCode::Esc => { app.columns_popup = false; }
+            KeyCode::Up => { app.columns_cursor = cur.saturating_sub(1); }
+            KeyCode::Down => { app.columns_cursor = (cur + 1).min(n.saturating_sub(1)); }
+            KeyCode::Home => { app.columns_cursor = 0; }
+            KeyCode::End => { app.columns_cursor = n.saturating_sub(1); }
+            KeyCode::Char(' ') => {
+                // Keep at least one column visible
+                let shown = app.columns_edit.iter().filter(|(_, on)| *on).count();
+                if let Some(entry) = app.columns_edit.get_mut(cur) && !(entry.1 && shown <= 1) { entry.1 = !entry.1; }
+            }
+            KeyCode::Char('[') if cur > 0 => {
+                app.columns_edit.swap(cur, cur - 1);
+                app.columns_cursor = cur - 1;
+            }
+            KeyCode::Char(']') if cur + 1 < n => {
+                app.columns_edit.swap(cur, cur + 1);
+                app.columns_cursor = cur + 1;
+            }
+            KeyCode::Char('r') => {
+                // Reset to the default column set
+                app.columns_edit = ProcColumn::DEFAULT.iter().map(|c| (*c, true)).collect();
+                for c in ProcColumn::ALL.iter() {
+                    if !ProcColumn::DEFAULT.contains(c) { app.columns_edit.push((*c, false)); }
+                }
+            }
+            KeyCode::Enter => {
+                let had_io = app.procs_columns.iter().any(|c| c.is_io());
+                app.procs_columns = app.columns_edit.iter().filter(|(_, on)| *on).map(|(c, _)| *c).collect();
+                if !had_io {
+                    app.procs_io_rates.clear();
+                }
+                ensure_sort_column_visible(app);
+                match (Config { process_columns: app.procs_columns.clone() }).save() {
+                    Ok(()) => { app.columns_popup = false; }
+                    Err(e) => { app.columns_error = format!("Applied, but not saved: {}", e); }
+                }
+            }
+            _ => {}
+        }
+        return Ok(false);
+    }
+    // Scheduling popup (top/htop): renice, CPU affinity, I/O priority
+    if app.tune_popup && !app.logs_password_prompt {
+        if !app.tune_result.is_empty() {
+            // Showing the outcome of the last attempt
+            match key.code {
+                KeyCode::Char('s') | KeyCode::Char('S') if app.tune_denied => {
+                    if app.logs_sudo_password.is_some() {
+                        apply_tuning_sudo(app);
+                    } else {
+                        app.tune_sudo_pending = true;
+                        app.logs_password_prompt = true;
+                        app.logs_password_error.clear();
+                    }
+                }
+                KeyCode::Esc | KeyCode::Enter => { app.tune_popup = false; }
+                _ => {}
+            }
+            return Ok(false);
+        }
+        match (app.tune_mode, key.code) {
+            (_, KeyCode::Esc) => { app.tune_popup = false; }
+            (_, KeyCode::Enter) => { apply_tuning(app); }
+            (_, KeyCode::Char('t')) => { app.tune_all_threads = !app.tune_all_threads; }
+            // Nice: Up/+ raises the nice value (lower priority), Down/- lowers it
+            (TuneMode::Nice, KeyCode::Up) | (TuneMode::Nice, KeyCode::Char('+')) => { app.tune_nice = (app.tune_nice + 1).min(19); }
+            (TuneMode::Nice, KeyCode::Down) | (TuneMode::Nice, KeyCode::Char('-')) => { app.tune_nice = (app.tune_nice - 1).max(-20); }
+            (TuneMode::Nice, KeyCode::PageUp) => { app.tune_nice = (app.tune_nice + 5).min(19); }
+            (TuneMode::Nice, KeyCode::PageDown) => { app.tune_nice = (app.tune_nice - 5).max(-20); }
+            // Affinity: move over the core grid (column-major, up to 8 rows like the gauges)
+            (TuneMode::Affinity, KeyCode::Up) => { app.tune_cpu_cursor = app.tune_cpu_cursor.saturating_sub(1); }
+            (TuneMode::Affinity, KeyCode::Down) => { app.tune_cpu_cursor = (app.tune_cpu_cursor + 1).min(app.tune_cpus.len().saturating_sub(1)); }
+            (TuneMode::Affinity, KeyCode::Left) => { app.tune_cpu_cursor = app.tune_cpu_cursor.saturating_sub(app.tune_cpus.len().clamp(1, 8)); }
+            (TuneMode::Affinity, KeyCode::Right) => {
+                let next = app.tune_cpu_cursor + app.tune_cpus.len().clamp(1, 8);
+                if next < app.tune_cpus.len() { app.tune_cpu_cursor = next; }
+            }
+            (TuneMode::Affinity, KeyCode::Char(' ')) => {
+                if let Some(c) = app.tune_cpus.get_mut(app.tune_cpu_cursor) { *c = !*c; }
+            }
+            (TuneMode::Affinity, KeyCode::Char('a')) => {
+                // Select all, or clear all when everything is already selected
+                let all = app.tune_cpus.iter().all(|c| *c);
+                app.tune_cpus.iter_mut().for_each(|c| *c = !all);
+            }
+            // I/O priority: Left/Right change class, Up/Down change level (0 = highest)
+            (TuneMode::IoPrio, KeyCode::Left) => { app.tune_io_class = app.tune_io_class.saturating_sub(1); }
+            (TuneMode::IoPrio, KeyCode::Right) => { app.tune_io_class = (app.tune_io_class + 1).min(IO_CLASSES.len() as u8 - 1); }
+            (TuneMode::IoPrio, KeyCode::Up) => { app.tune_io_level = app.tune_io_level.saturating_sub(1); }
+            (TuneMode::IoPrio, KeyCode::Down) => { app.tune_io_level = (app.tune_io_level + 1).min(7); }
+            _ => {}
+        }
+        return Ok(false);
+    }
 
     // If Shell tab active, forward most keys to the PTY instead of handling as app hotkeys
     if app.selected_top_tab == 3 {
@@ -403,6 +1245,55 @@ fn handle_key(key: KeyEvent, app: &mut App) -> Result<bool, Box<dyn Error>> {
         }
     }
 
+    // Process filter prompt (top/htop): edit the filter live
+    if app.selected_top_tab == 1 && app.procs_filter_prompt {
+        match key.code {
+            KeyCode::Esc => {
+                // Cancel: drop the filter entirely
+                app.procs_filter_prompt = false;
+                app.procs_filter_input.clear();
+                app.procs_filter = None;
+                app.procs_filter_error.clear();
+            }
+            KeyCode::Enter => { app.procs_filter_prompt = false; }
+            KeyCode::F(10) => return Ok(true),
+            KeyCode::Backspace => {
+                app.procs_filter_input.pop();
+                apply_process_filter_input(app);
+            }
+            KeyCode::Char(c) if !c.is_control() => {
+                app.procs_filter_input.push(c);
+                apply_process_filter_input(app);
+            }
+            _ => {}
+        }
+        return Ok(false);
+    }
+
+    // Services name search prompt: edit the search live
+    if app.selected_top_tab == 2 && app.services_search_prompt {
+        match key.code {
+            KeyCode::Esc => {
+                app.services_search_prompt = false;
+                // The list only changes (and the selection restarts at the top) if there was a search
+                if !app.services_search.is_empty() { app.services_selected = 0; }
+                app.services_search.clear();
+            }
+            KeyCode::Enter => { app.services_search_prompt = false; }
+            KeyCode::F(10) => return Ok(true),
+            KeyCode::Backspace if !app.services_search.is_empty() => {
+                app.services_search.pop();
+                app.services_selected = 0;
+            }
+            KeyCode::Char(c) if !c.is_control() => {
+                app.services_search.push(c);
+                app.services_selected = 0;
+            }
+            _ => {}
+        }
+        return Ok(false);
+    }
+
     // Selection and actions for top/htop Processes table
     if app.selected_top_tab == 1 {
         match key.code {
@@ -439,39 +1330,177 @@ fn handle_key(key: KeyEvent, app: &mut App) -> Result<bool, Box<dyn Error>> {
                     let idx = app.procs_selected.min(app.procs_pids_sorted.len().saturating_sub(1));
                     let pid = app.procs_pids_sorted[idx];
                     let title = get_process_name(pid);
-                    let text = get_process_details(pid);
                     app.process_detail_title = if title.is_empty() { format!("PID {}", pid) } else { format!("{} (PID {})", title, pid) };
-                    app.process_detail_text = text;
+                    app.process_detail_pid = pid;
+                    app.process_detail_tab = 0;
+                    app.process_detail_scroll = 0;
+                    app.process_detail_text = get_process_detail_tab(pid, 0);
+                    app.process_detail_cpu_hist.clear();
+                    app.process_detail_rss_hist.clear();
+                    app.process_detail_exited = false;
                     app.process_popup = true;
                 }
                 return Ok(false);
             }
+            KeyCode::Char('I') => {
+                // Toggle the READ/s and WRITE/s columns; turning them on sorts by I/O like iotop
+                if app.procs_columns.iter().any(|c| c.is_io()) {
+                    app.procs_columns.retain(|c| !c.is_io());
+                } else {
+                    // Insert after MEM% (or CPU%), else before TIME/CMD
+                    let at = app.procs_columns.iter().position(|c| *c == ProcColumn::Mem || *c == ProcColumn::Cpu).map(|i| i + 1)
+                        .or_else(|| app.procs_columns.iter().position(|c| *c == ProcColumn::Time || *c == ProcColumn::Cmd))
+                        .unwrap_or(app.procs_columns.len());
+                    app.procs_columns.splice(at..at, [ProcColumn::Read, ProcColumn::Write]);
+                    app.procs_io_rates.clear();
+                    app.procs_sort_key = ProcColumn::Read;
+                    app.procs_sort_desc = true;
+                }
+                ensure_sort_column_visible(app);
+                match (Config { process_columns: app.procs_columns.clone() }).save() {
+                    Ok(()) => app.columns_error.clear(),
+                    Err(e) => app.columns_error = format!("Columns not saved: {}", e),
+                }
+                return Ok(false);
+            }
+            KeyCode::Char('C') => {
+                // Column chooser: shown columns first (in order), then the hidden ones
+                app.columns_edit = app.procs_columns.iter().map(|c| (*c, true)).collect();
+                for c in ProcColumn::ALL.iter() {
+                    if !app.procs_columns.contains(c) { app.columns_edit.push((*c, false)); }
+                }
+                app.columns_cursor = 0;
+                app.columns_error.clear();
+                app.columns_popup = true;
+                return Ok(false);
+            }
+            KeyCode::Char('T') => {
+                // Per-thread view of the selected process
+                if !app.procs_pids_sorted.is_empty() {
+                    let idx = app.procs_selected.min(app.procs_pids_sorted.len().saturating_sub(1));
+                    open_thread_view(app, app.procs_pids_sorted[idx]);
+                }
+                return Ok(false);
+            }
+            KeyCode::Char('k') => {
+                // Open the signal menu for the selected process
+                if !app.procs_pids_sorted.is_empty() {
+                    let idx = app.procs_selected.min(app.procs_pids_sorted.len().saturating_sub(1));
+                    app.signal_pid = app.procs_pids_sorted[idx];
+                    app.signal_proc_name = get_process_name(app.signal_pid);
+                    app.signal_selected = 0;
+                    app.signal_custom_input.clear();
+                    app.signal_result.clear();
+                    app.signal_denied = false;
+                    app.signal_stage = SignalStage::Menu;
+                    app.signal_popup = true;
+                }
+                return Ok(false);
+            }
+            KeyCode::Char('>') | KeyCode::Char('<') => {
+                // Cycle the sort column; each column starts in its natural direction
+                app.procs_sort_key = if key.code == KeyCode::Char('>') { app.procs_sort_key.next(&app.procs_columns) } else { app.procs_sort_key.prev(&app.procs_columns) };
+                app.procs_sort_desc = app.procs_sort_key.default_descending();
+                return Ok(false);
+            }
+            KeyCode::Char('r') => {
+                // Reverse the sort direction
+                app.procs_sort_desc = !app.procs_sort_desc;
+                return Ok(false);
+            }
+            KeyCode::Char('/') => {
+                // Open the filter prompt (keeps the current filter text for editing)
+                app.procs_filter_prompt = true;
+                return Ok(false);
+            }
+            KeyCode::Esc if app.procs_filter.is_some() || !app.procs_filter_input.is_empty() => {
+                app.procs_filter_input.clear();
+                app.procs_filter = None;
+                app.procs_filter_error.clear();
+                return Ok(false);
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') if app.procs_filter.is_some() && app.procs_tree_mode => {
+                // Jump to the next/previous row that matches the filter (wrapping); only tree view
+                // keeps non-matching rows (dimmed ancestors), the flat view shows matches only
+                let total = app.procs_match_flags.len();
+                if total > 0 {
+                    let cur = app.procs_selected.min(total - 1);
+                    let forward = key.code == KeyCode::Char('n');
+                    for step in 1..=total {
+                        let idx = if forward { (cur + step) % total } else { (cur + total - step % total) % total };
+                        if app.procs_match_flags[idx] { app.procs_selected = idx; break; }
+                    }
+                }
+                return Ok(false);
+            }
+            KeyCode::Char('p') | KeyCode::Char('a') | KeyCode::Char('i') => {
+                // Scheduling popup for the selected process: p = nice, a = CPU affinity, i = I/O priority
+                if !app.procs_pids_sorted.is_empty() {
+                    let idx = app.procs_selected.min(app.procs_pids_sorted.len().saturating_sub(1));
+                    let pid = app.procs_pids_sorted[idx];
+                    app.tune_pid = pid;
+                    app.tune_proc_name = get_process_name(pid);
+                    app.tune_result.clear();
+                    app.tune_denied = false;
+                    app.tune_all_threads = true;
+                    match key.code {
+                        KeyCode::Char('p') => {
+                            app.tune_mode = TuneMode::Nice;
+                            app.tune_nice = get_process_pri_nice(pid).1 as i32;
+                        }
+                        KeyCode::Char('a') => {
+                            app.tune_mode = TuneMode::Affinity;
+                            let ncpus = app.cpu_count.max(1);
+                            app.tune_cpus = get_process_affinity(pid, ncpus).unwrap_or_else(|_| vec![true; ncpus]);
+                            app.tune_cpu_cursor = 0;
+                        }
+                        _ => {
+                            app.tune_mode = TuneMode::IoPrio;
+                            let (class, level) = get_process_ioprio(pid).unwrap_or((0, 4));
+                            app.tune_io_class = class;
+                            app.tune_io_level = level;
+                        }
+                    }
+                    app.tune_popup = true;
+                }
+                return Ok(false);
+            }
+            KeyCode::Char('t') => {
+                // Toggle tree mode; the tree is rebuilt on the next refresh
+                app.procs_tree_mode = !app.procs_tree_mode;
+                return Ok(false);
+            }
+            KeyCode::Char('+') | KeyCode::Char('-') | KeyCode::Char(' ') if app.procs_tree_mode => {
+                // Expand/collapse the selected subtree
+                let idx = app.procs_selected.min(app.procs_tree_rows.len().saturating_sub(1));
+                if let Some(row) = app.procs_tree_rows.get(idx).filter(|r| r.has_children) {
+                    let pid = row.pid;
+                    match key.code {
+                        KeyCode::Char('+') => { app.procs_collapsed.remove(&pid); }
+                        KeyCode::Char('-') => { app.procs_collapsed.insert(pid); }
+                        _ => { if !app.procs_collapsed.remove(&pid) { app.procs_collapsed.insert(pid); } }
+                    }
+                }
+                return Ok(false);
+            }
             _ => {}
         }
     }
 
-    // Selection and actions for Services tab
+    // Selection and actions for Services tab (list cached in app.services, refreshed each tick;
+    // indices refer to the filtered list)
     if app.selected_top_tab == 2 {
+        let shown = shown_services(app);
+        let total = shown.len();
+        let selected_unit = shown.get(app.services_selected.min(total.saturating_sub(1))).map(|u| u.name.clone());
         match key.code {
             KeyCode::Up => {
                 if app.services_selected > 0 { app.services_selected -= 1; }
                 return Ok(false);
             }
             KeyCode::Down => {
-                // Increase selection but cap to last item based on current listing (best effort)
-                #[cfg(target_os = "linux")]
-                {
-                    let total = get_all_services().len();
-                    if total == 0 { /* no-op */ }
-                    else {
-                        let max_idx = total.saturating_sub(1);
-                        if app.services_selected < max_idx { app.services_selected += 1; }
-                    }
-                }
-                #[cfg(not(target_os = "linux"))]
-                {
-                    // nothing to select on non-linux view
-                }
+                // Increase selection but cap to last item
+                if total > 0 && app.services_selected < total - 1 { app.services_selected += 1; }
                 return Ok(false);
             }
             KeyCode::Home => {
@@ -479,11 +1508,7 @@ fn handle_key(key: KeyEvent, app: &mut App) -> Result<bool, Box<dyn Error>> {
                 return Ok(false);
             }
             KeyCode::End => {
-                #[cfg(target_os = "linux")]
-                {
-                    let total = get_all_services().len();
-                    if total > 0 { app.services_selected = total.saturating_sub(1); }
-                }
+                if total > 0 { app.services_selected = total - 1; }
                 return Ok(false);
             }
             KeyCode::PageUp => {
@@ -493,29 +1518,84 @@ fn handle_key(key: KeyEvent, app: &mut App) -> Result<bool, Box<dyn Error>> {
             }
             KeyCode::PageDown => {
                 let step: usize = 10;
-                app.services_selected = app.services_selected.saturating_add(step);
-                #[cfg(target_os = "linux")]
-                {
-                    let total = get_all_services().len();
-                    if total > 0 {
-                        let max_idx = total.saturating_sub(1);
-                        if app.services_selected > max_idx { app.services_selected = max_idx; }
-                    }
+                app.services_selected = app.services_selected.saturating_add(step).min(total.saturating_sub(1));
+                return Ok(false);
+            }
+            KeyCode::Char('>') | KeyCode::Char('<') => {
+                // Cycle the sort column; usage columns start with the heaviest units
+                let columns = service_columns(app);
+                app.services_sort_key = if key.code == KeyCode::Char('>') { app.services_sort_key.next(columns) } else { app.services_sort_key.prev(columns) };
+                app.services_sort_desc = app.services_sort_key.default_descending();
+                return Ok(false);
+            }
+            KeyCode::Char('r') => {
+                app.services_sort_desc = !app.services_sort_desc;
+                return Ok(false);
+            }
+            KeyCode::Char('R') | KeyCode::Char('F') | KeyCode::Char('I') | KeyCode::Char('E') => {
+                // Quick state filters; pressing the active filter's key again shows all units
+                let filter = match key.code {
+                    KeyCode::Char('R') => ServiceFilter::Running,
+                    KeyCode::Char('F') => ServiceFilter::Failed,
+                    KeyCode::Char('I') => ServiceFilter::Inactive,
+                    _ => ServiceFilter::EnabledDead,
+                };
+                app.services_filter = if app.services_filter == filter { ServiceFilter::All } else { filter };
+                app.services_selected = 0;
+                return Ok(false);
+            }
+            KeyCode::Char('/') => {
+                // Name search prompt (keeps the current text for editing)
+                app.services_search_prompt = true;
+                return Ok(false);
+            }
+            KeyCode::Char('t') | KeyCode::Char('T') => {
+                // Cycle the unit type (service, timer, socket, mount, path, target)
+                let n = UNIT_TYPES.len();
+                app.services_type = if key.code == KeyCode::Char('t') { (app.services_type + 1) % n } else { (app.services_type + n - 1) % n };
+                if !service_columns(app).contains(&app.services_sort_key) {
+                    app.services_sort_key = ServiceColumn::Unit;
+                    app.services_sort_desc = false;
+                }
+                app.services_selected = 0;
+                return Ok(false);
+            }
+            KeyCode::Char('j') => {
+                // Journal of the selected unit in the log viewer
+                if let Some(unit) = selected_unit {
+                    open_unit_journal(app, &unit);
+                }
+                return Ok(false);
+            }
+            KeyCode::Char('u') => {
+                // Switch between the system manager and the current user's manager
+                app.services_user = !app.services_user;
+                app.services_selected = 0;
+                return Ok(false);
+            }
+            KeyCode::Esc if app.services_filter != ServiceFilter::All || !app.services_search.is_empty() => {
+                app.services_filter = ServiceFilter::All;
+                app.services_search.clear();
+                return Ok(false);
+            }
+            KeyCode::Char('a') => {
+                // Action menu (start/stop/restart/...) for the selected unit
+                if let Some(unit) = selected_unit {
+                    app.service_action_unit = unit;
+                    app.service_action_selected = 0;
+                    app.service_action_confirm = false;
+                    app.service_action_popup = true;
                 }
                 return Ok(false);
             }
             KeyCode::Enter => {
                 // Open popup with selected service details
                 #[cfg(target_os = "linux")]
-                {
-                    let services = get_all_services();
-                    if !services.is_empty() {
-                        let idx = app.services_selected.min(services.len().saturating_sub(1));
-                        let (unit, _active, _desc) = &services[idx];
-                        app.service_detail_title = unit.clone();
-                        app.service_detail_text = get_service_status(unit);
-                        app.service_popup = true;
-                    }
+                if let Some(unit) = selected_unit {
+                    // Read on the services collector thread; the text arrives with its next snapshot
+                    open_service_popup(app, &unit);
+                    app.service_detail_text = String::from(SERVICE_DETAILS_LOADING);
+                    app.service_details_wanted = Some(unit);
                 }
                 #[cfg(not(target_os = "linux"))]
                 {
@@ -536,43 +1616,40 @@ fn handle_key(key: KeyEvent, app: &mut App) -> Result<bool, Box<dyn Error>> {
             KeyCode::Down => { app.logs_selected = app.logs_selected.saturating_add(1); return Ok(false); }
             KeyCode::Home => { app.logs_selected = 0; return Ok(false); }
             KeyCode::End => {
-                let total = list_var_log_files().len();
+                let total = app.log_files.len();
                 if total > 0 { app.logs_selected = total.saturating_sub(1); }
                 return Ok(false);
             }
             KeyCode::PageUp => { let step: usize = 10; app.logs_selected = app.logs_selected.saturating_sub(step); return Ok(false); }
             KeyCode::PageDown => {
                 let step: usize = 10; app.logs_selected = app.logs_selected.saturating_add(step);
-                let total = list_var_log_files().len();
+                let total = app.log_files.len();
                 if total > 0 {
                     let max_idx = total.saturating_sub(1);
                     if app.logs_selected > max_idx { app.logs_selected = max_idx; }
                 }
                 return Ok(false);
             }
-            KeyCode::Enter => {
-                // Build file list and attempt to read selected file
-                let files = list_var_log_files();
-                if !files.is_empty() {
-                    let idx = app.logs_selected.min(files.len().saturating_sub(1));
-                    let ent = &files[idx];
+            KeyCode::Enter | KeyCode::Char('R') => {
+                // Attempt to read the selected file from the collected list (R: with all its rotations)
+                let idx = app.logs_selected.min(app.log_files.len().saturating_sub(1));
+                if let Some(ent) = app.log_files.get(idx).cloned() {
+                    let stitched = key.code == KeyCode::Char('R');
                     // Try normal read first, then sudo if we have password; if denied and no password, prompt
-                    match read_log_file_best_effort(&ent.path, app.logs_sudo_password.as_deref()) {
-                        Ok(text) => {
-                            app.log_detail_title = ent.name.clone();
-                            app.log_detail_text = text;
-                            app.log_popup_scroll = 0;
-                            app.log_popup = true;
+                    match open_log_file_best_effort(&ent.path, stitched, app.logs_sudo_password.as_deref()) {
+                        Ok((doc, load)) => {
+                            show_log_doc(app, log_file_title(&ent.path, stitched), doc, Some(ent.path.clone()));
+                            app.log_stitched = stitched;
+                            app.log_load = load;
                         }
                         Err(_err) => {
                             if app.logs_sudo_password.is_none() {
                                 app.logs_pending_path = ent.path.clone();
+                                app.logs_pending_stitched = stitched;
                                 app.logs_password_prompt = true;
                                 app.logs_password_error.clear();
                             } else {
-                                app.log_detail_title = ent.name.clone();
-                                app.log_detail_text = String::from("Failed to read (permission?)");
-                                app.log_popup = true;
+                                show_log_doc(app, ent.name.clone(), LogDoc::from_text("Failed to read (permission?)"), None);
                             }
                         }
                     }
@@ -590,14 +1667,14 @@ fn handle_key(key: KeyEvent, app: &mut App) -> Result<bool, Box<dyn Error>> {
             KeyCode::Down => { app.journal_selected = app.journal_selected.saturating_add(1); return Ok(false); }
             KeyCode::Home => { app.journal_selected = 0; return Ok(false); }
             KeyCode::End => {
-                let total = list_journal_files().len();
+                let total = app.journal_files.len();
                 if total > 0 { app.journal_selected = total.saturating_sub(1); }
                 return Ok(false);
             }
             KeyCode::PageUp => { let step: usize = 10; app.journal_selected = app.journal_selected.saturating_sub(step); return Ok(false); }
             KeyCode::PageDown => {
                 let step: usize = 10; app.journal_selected = app.journal_selected.saturating_add(step);
-                let total = list_journal_files().len();
+                let total = app.journal_files.len();
                 if total > 0 {
                     let max_idx = total.saturating_sub(1);
                     if app.journal_selected > max_idx { app.journal_selected = max_idx; }
@@ -605,27 +1682,18 @@ fn handle_key(key: KeyEvent, app: &mut App) -> Result<bool, Box<dyn Error>> {
                 return Ok(false);
             }
             KeyCode::Enter => {
-                // Build file list and attempt to read selected journal file
-                let files = list_journal_files();
-                if !files.is_empty() {
-                    let idx = app.journal_selected.min(files.len().saturating_sub(1));
-                    let ent = &files[idx];
-                    match read_journal_file_best_effort(&ent.path, app.logs_sudo_password.as_deref()) {
-                        Ok(text) => {
-                            app.log_detail_title = ent.name.clone();
-                            app.log_detail_text = text;
-                            app.log_popup_scroll = 0;
-                            app.log_popup = true;
-                        }
+                // Attempt to read the selected journal file from the collected list
+                let idx = app.journal_selected.min(app.journal_files.len().saturating_sub(1));
+                if let Some(ent) = app.journal_files.get(idx).cloned() {
+                    match read_journal_file_best_effort(&ent.path, 0, app.logs_sudo_password.as_deref()) {
+                        Ok((doc, load)) => show_journal_file(app, &ent.path, doc, load),
                         Err(err) => {
                             if app.logs_sudo_password.is_none() {
                                 app.logs_pending_path = ent.path.clone();
                                 app.logs_password_prompt = true;
                                 app.logs_password_error.clear();
                             } else {
-                                app.log_detail_title = ent.name.clone();
-                                app.log_detail_text = format!("Failed to read: {}", err);
-                                app.log_popup = true;
+                                show_log_doc(app, ent.name.clone(), LogDoc::from_text(&format!("Failed to read: {}", err)), None);
                             }
                         }
                     }
@@ -654,45 +1722,675 @@ fn handle_key(key: KeyEvent, app: &mut App) -> Result<bool, Box<dyn Error>> {
         (KeyCode::Left, _) => {
             if app.selected_top_tab > 0 { app.selected_top_tab -= 1; }
         }
-        (KeyCode::Right, _) => {
-            if app.selected_top_tab < 5 { app.selected_top_tab += 1; }
+        (KeyCode::Right, _) => {
+            if app.selected_top_tab < 5 { app.selected_top_tab += 1; }
+        }
+        // Vim-style: h = left, l = right
+        (KeyCode::Char('h'), _) => {
+            if app.selected_top_tab > 0 { app.selected_top_tab -= 1; }
+        }
+        (KeyCode::Char('H'), _) => {
+            if app.selected_top_tab > 0 { app.selected_top_tab -= 1; }
+        }
+        (KeyCode::Char('l'), _) => {
+            if app.selected_top_tab < 5 { app.selected_top_tab += 1; }
+        }
+        (KeyCode::Char('L'), _) => {
+            if app.selected_top_tab < 5 { app.selected_top_tab += 1; }
+        }
+        (KeyCode::Tab, _) => {
+            app.selected_top_tab = (app.selected_top_tab + 1) % 6;
+        }
+        (KeyCode::BackTab, _) => {
+            app.selected_top_tab = (app.selected_top_tab + 5) % 6; // -1 mod 6
+        }
+        (KeyCode::Char('1'), _) => { app.selected_top_tab = 0; }
+        (KeyCode::Char('2'), _) => { app.selected_top_tab = 1; }
+        (KeyCode::Char('3'), _) => { app.selected_top_tab = 2; }
+        (KeyCode::Char('4'), _) => { app.selected_top_tab = 3; }
+        (KeyCode::Char('5'), _) => { app.selected_top_tab = 4; }
+        (KeyCode::Char('6'), _) => { app.selected_top_tab = 5; }
+        _ => {}
+    }
+
+    // If we just left the Shell tab, terminate the session
+    if app.selected_top_tab != 3 {
+        if let Some(mut sess) = app.shell.take() { sess.terminate(); }
+    }
+
+    Ok(false)
+}
+
+/// Re-parse the process filter after its input changed. Invalid input keeps the
+/// previous filter and shows the error in the filter bar.
+fn apply_process_filter_input(app: &mut App) {
+    match ProcFilter::parse(&app.procs_filter_input) {
+        Ok(flt) => { app.procs_filter = flt; app.procs_filter_error.clear(); }
+        Err(err) => { app.procs_filter_error = err; }
+    }
+}
+
+/// Maximum number of CPU%/RSS samples kept for the process details sparklines.
+const PROCESS_DETAIL_HISTORY: usize = 300;
+
+/// Add the CPU%/RSS of the process in the details popup from a new system snapshot. Once the
+/// PID is gone the last snapshot stays on screen, marked as exited.
+fn sample_process_detail(app: &mut App, sys: &System) {
+    if app.process_detail_exited { return; }
+    let Some(p) = sys.process(sysinfo::Pid::from_u32(app.process_detail_pid as u32)) else {
+        app.process_detail_exited = true;
+        return;
+    };
+    app.process_detail_cpu_hist.push(p.cpu_usage());
+    app.process_detail_rss_hist.push(p.memory());
+    if app.process_detail_cpu_hist.len() > PROCESS_DETAIL_HISTORY { app.process_detail_cpu_hist.remove(0); }
+    if app.process_detail_rss_hist.len() > PROCESS_DETAIL_HISTORY { app.process_detail_rss_hist.remove(0); }
+}
+
+/// Reload the active tab of the details popup (until the process exits).
+fn reread_process_detail_tab(app: &mut App) {
+    if app.process_detail_exited { return; }
+    app.process_detail_text = get_process_detail_tab(app.process_detail_pid, app.process_detail_tab);
+    app.process_detail_scroll = app.process_detail_scroll.min(app.process_detail_text.lines().count().saturating_sub(1));
+}
+
+/// Open the thread view popup for a process and take the first sample.
+fn open_thread_view(app: &mut App, pid: i32) {
+    let name = get_process_name(pid);
+    app.threads_pid = pid;
+    app.threads_title = if name.is_empty() { format!("PID {}", pid) } else { format!("{} (PID {})", name, pid) };
+    app.threads_rows.clear();
+    app.threads_cpu.clear();
+    app.threads_prev.clear();
+    app.threads_exited = false;
+    app.threads_scroll = 0;
+    app.threads_popup = true;
+    refresh_thread_view(app);
+}
+
+/// Re-read the threads of the viewed process and compute CPU% over the interval since the
+/// previous sample (one tick, once the view is open).
+fn refresh_thread_view(app: &mut App) {
+    let now = Instant::now();
+    let Some(mut threads) = read_process_threads(app.threads_pid) else {
+        // Keep the last sample on screen, but mark the process as gone
+        app.threads_exited = true;
+        app.threads_cpu.clear();
+        return;
+    };
+    let dt = now.saturating_duration_since(app.threads_last).as_secs_f64();
+    let ticks_per_sec = clock_ticks_per_sec();
+    let mut new_prev = std::collections::HashMap::new();
+    app.threads_cpu.clear();
+    for t in &threads {
+        // First sample for a thread shows 0 (no previous data), like the network rates
+        let pct = match app.threads_prev.get(&t.tid) {
+            Some(prev) if dt > 0.0 => t.cpu_ticks.saturating_sub(*prev) as f64 / ticks_per_sec / dt * 100.0,
+            _ => 0.0,
+        };
+        app.threads_cpu.insert(t.tid, pct);
+        new_prev.insert(t.tid, t.cpu_ticks);
+    }
+    // Busiest threads first, then by TID
+    threads.sort_by(|a, b| {
+        let ca = app.threads_cpu.get(&a.tid).cloned().unwrap_or(0.0);
+        let cb = app.threads_cpu.get(&b.tid).cloned().unwrap_or(0.0);
+        cb.partial_cmp(&ca).unwrap_or(std::cmp::Ordering::Equal).then(a.tid.cmp(&b.tid))
+    });
+    app.threads_scroll = app.threads_scroll.min(threads.len().saturating_sub(1));
+    app.threads_rows = threads;
+    app.threads_prev = new_prev;
+    app.threads_last = now;
+}
+
+/// Apply the edited scheduling setting to the target process and record the outcome.
+fn apply_tuning(app: &mut App) {
+    if app.tune_mode == TuneMode::Affinity && !app.tune_cpus.iter().any(|c| *c) {
+        app.tune_result = String::from("Select at least one CPU.");
+        app.tune_denied = false;
+        return;
+    }
+    let what = tuning_description(app);
+    let res = match app.tune_mode {
+        TuneMode::Nice => set_process_nice(app.tune_pid, app.tune_nice, app.tune_all_threads),
+        TuneMode::Affinity => set_process_affinity(app.tune_pid, &app.tune_cpus, app.tune_all_threads),
+        TuneMode::IoPrio => set_process_ioprio(app.tune_pid, app.tune_io_class, app.tune_io_level, app.tune_all_threads),
+    };
+    app.tune_denied = false;
+    app.tune_result = match res {
+        Ok(()) => format!("Set {} for PID {}.", what, app.tune_pid),
+        Err(e) if e.raw_os_error() == Some(1) || e.raw_os_error() == Some(13) => {
+            // EPERM/EACCES: another user's process, or raising priority without privileges
+            app.tune_denied = true;
+            format!("Permission denied setting {} for PID {} (EPERM).", what, app.tune_pid)
+        }
+        Err(e) if e.raw_os_error() == Some(3) => format!("PID {} no longer exists (ESRCH).", app.tune_pid),
+        Err(e) => format!("Failed to set {} for PID {}: {}", what, app.tune_pid, e),
+    };
+}
+
+/// Retry the pending scheduling change through sudo using the cached password.
+fn apply_tuning_sudo(app: &mut App) {
+    let Some(pw) = app.logs_sudo_password.clone() else { return; };
+    let what = tuning_description(app);
+    let res = match app.tune_mode {
+        TuneMode::Nice => set_process_nice_sudo(app.tune_pid, app.tune_nice, app.tune_all_threads, &pw),
+        TuneMode::Affinity => set_process_affinity_sudo(app.tune_pid, &app.tune_cpus, app.tune_all_threads, &pw),
+        TuneMode::IoPrio => set_process_ioprio_sudo(app.tune_pid, app.tune_io_class, app.tune_io_level, app.tune_all_threads, &pw),
+    };
+    app.tune_result = match res {
+        Ok(()) => { app.tune_denied = false; format!("Set {} for PID {} via sudo.", what, app.tune_pid) }
+        Err(err) => {
+            if err.contains("incorrect password") { app.logs_sudo_password = None; }
+            format!("sudo failed: {}", err.trim())
+        }
+    };
+}
+
+/// Short description of the pending scheduling change, e.g. "nice 5 (all threads)".
+fn tuning_description(app: &App) -> String {
+    let scope = if app.tune_all_threads { " (all threads)" } else { "" };
+    match app.tune_mode {
+        TuneMode::Nice => format!("nice {}{}", app.tune_nice, scope),
+        TuneMode::Affinity => format!("CPU affinity {}/{} CPUs{}", app.tune_cpus.iter().filter(|c| **c).count(), app.tune_cpus.len(), scope),
+        TuneMode::IoPrio => {
+            let class = IO_CLASSES.get(app.tune_io_class as usize).cloned().unwrap_or("?");
+            if app.tune_io_class == 1 || app.tune_io_class == 2 { format!("I/O priority {}/{}{}", class, app.tune_io_level, scope) } else { format!("I/O priority {}{}", class, scope) }
+        }
+    }
+}
+
+/// Units shown in the Services table: the cached list narrowed to the selected unit type,
+/// the state filter and the name search.
+fn shown_services(app: &App) -> Vec<&UnitInfo> {
+    let needle = app.services_search.to_lowercase();
+    let unit_type = UNIT_TYPES.get(app.services_type).cloned().unwrap_or("service");
+    app.services
+        .iter()
+        .filter(|u| u.unit_type() == unit_type && app.services_filter.matches(u) && (needle.is_empty() || u.name.to_lowercase().contains(&needle)))
+        .collect()
+}
+
+/// Columns of the Services table for the selected unit type.
+fn service_columns(app: &App) -> &'static [ServiceColumn] {
+    ServiceColumn::for_type(UNIT_TYPES.get(app.services_type).cloned().unwrap_or("service"))
+}
+
+/// Status tab text until the services collector has read the unit's details.
+const SERVICE_DETAILS_LOADING: &str = "loading...";
+
+/// Show the service details popup for `unit` on its Status tab (text set by the caller).
+fn open_service_popup(app: &mut App, unit: &str) {
+    app.service_detail_title = unit.to_string();
+    app.service_detail_user = app.services_user;
+    app.service_detail_tab = 0;
+    app.service_detail_scroll = 0;
+    app.service_deps_root = unit.to_string();
+    app.service_deps_history.clear();
+    app.service_deps_reverse = false;
+    app.service_popup = true;
+}
+
+/// Switch the service popup to a tab, loading its content (Status is already loaded).
+fn select_service_tab(app: &mut App, tab: usize) {
+    app.service_detail_tab = tab;
+    app.service_detail_scroll = 0;
+    let unit = app.service_detail_title.clone();
+    match tab {
+        1 => app.service_tab_text = get_unit_file(&unit, app.service_detail_user),
+        2 => load_service_deps(app),
+        3 => app.service_tab_text = get_unit_show(&unit, app.service_detail_user),
+        _ => {}
+    }
+}
+
+/// Load the dependency tree of the current root unit and select its first row.
+fn load_service_deps(app: &mut App) {
+    app.service_deps_selected = 0;
+    match get_unit_dependencies(&app.service_deps_root, app.service_detail_user, app.service_deps_reverse) {
+        Ok(rows) => { app.service_deps = rows; app.service_deps_error.clear(); }
+        Err(err) => { app.service_deps.clear(); app.service_deps_error = err.trim_end().to_string(); }
+    }
+}
+
+/// Open the log popup on a unit's journal (current boot, all priorities, not following).
+fn open_unit_journal(app: &mut App, unit: &str) {
+    app.log_journal_unit = Some(unit.to_string());
+    app.log_journal_user = app.services_user;
+    app.log_journal_priority = 0;
+    app.log_journal_boot = Some(0);
+    app.log_follow = false;
+    app.log_tail = None;
+    app.log_journal_sudo = false;
+    load_unit_journal(app);
+    app.log_popup = true;
+}
+
+/// (Re)read the unit journal for the current priority/boot selection and jump to the newest
+/// entry. When following, `journalctl -f` is (re)started after it.
+fn load_unit_journal(app: &mut App) {
+    let Some(unit) = app.log_journal_unit.clone() else { return; };
+    let pw = if app.log_journal_sudo { app.logs_sudo_password.clone() } else { None };
+    app.log_tail = None;
+    match read_unit_journal(&unit, app.log_journal_user, app.log_journal_priority, app.log_journal_boot, pw.as_deref()) {
+        Ok(chunk) => {
+            app.log_journal_limited = chunk.limited && pw.is_none();
+            let mut doc = LogDoc::default();
+            if app.log_journal_limited {
+                doc.append("(Entries from other users and the system may be hidden; press s to read the journal with sudo)");
+            }
+            doc.append(&chunk.text);
+            set_log_doc(app, doc);
+            app.log_journal_cursor = chunk.cursor;
+        }
+        Err(err) => {
+            if err.contains("incorrect password") { app.logs_sudo_password = None; app.log_journal_sudo = false; }
+            set_log_doc(app, LogDoc::from_text(&format!("Failed to read the journal of {}: {}", unit, err.trim_end())));
+            app.log_journal_cursor = None;
+            app.log_follow = false;
         }
-        // Vim-style: h = left, l = right
-        (KeyCode::Char('h'), _) => {
-            if app.selected_top_tab > 0 { app.selected_top_tab -= 1; }
+    }
+    if app.log_follow {
+        let cmd = unit_journal_follow_command(&unit, app.log_journal_user, app.log_journal_priority, app.log_journal_boot, app.log_journal_cursor.as_deref(), pw.is_some());
+        match LogTail::spawn(cmd, pw.as_deref(), "journalctl") {
+            Ok(tail) => app.log_tail = Some(tail),
+            Err(err) => { app.log_follow = false; append_log_text(app, &format!("(Cannot follow: {})", err)); }
         }
-        (KeyCode::Char('H'), _) => {
-            if app.selected_top_tab > 0 { app.selected_top_tab -= 1; }
+    }
+    app.log_follow_last = Instant::now();
+    app.log_detail_title = unit_journal_title(app);
+    log_popup_scroll_to_end(app);
+}
+
+/// Show a journal file read with all priorities in the log popup.
+fn show_journal_file(app: &mut App, path: &str, doc: LogDoc, load: Option<LogLoad>) {
+    show_log_doc(app, String::new(), doc, None);
+    app.log_load = load;
+    app.log_journal_file = Some(path.to_string());
+    app.log_journal_priority = 0;
+    app.log_detail_title = journal_file_title(app);
+}
+
+/// Re-read the journal file in the popup for the current priority filter.
+fn load_journal_file(app: &mut App) {
+    let Some(path) = app.log_journal_file.clone() else { return; };
+    let (doc, load) = read_journal_file_best_effort(&path, app.log_journal_priority, app.logs_sudo_password.as_deref())
+        .unwrap_or_else(|e| (LogDoc::from_text(&format!("Failed to read: {}", e)), None));
+    set_log_doc(app, doc);
+    app.log_load = load;
+    app.log_detail_title = journal_file_title(app);
+}
+
+/// Log popup title for a journal file: file name and priority filter.
+fn journal_file_title(app: &App) -> String {
+    let path = app.log_journal_file.as_deref().unwrap_or("");
+    let name = std::path::Path::new(path).file_name().and_then(|s| s.to_str()).unwrap_or(path);
+    format!("{} | {}", name, journal_priority_label(app.log_journal_priority))
+}
+
+/// "warning and above", or "all priorities", for an index into JOURNAL_PRIORITIES.
+fn journal_priority_label(priority: usize) -> String {
+    match JOURNAL_PRIORITIES.get(priority) { Some(p) if *p != "all" => format!("{} and above", p), _ => String::from("all priorities") }
+}
+
+/// Log popup title for a unit journal: unit, boot, priority filter and follow state.
+fn unit_journal_title(app: &App) -> String {
+    let unit = app.log_journal_unit.as_deref().unwrap_or("");
+    let boot = match app.log_journal_boot { None => String::from("all boots"), Some(0) => String::from("this boot"), Some(b) => format!("boot {}", b) };
+    let prio = journal_priority_label(app.log_journal_priority);
+    let mut title = format!("journal {}{} | {} | {}", if app.log_journal_user { "--user " } else { "" }, unit, boot, prio);
+    if app.log_journal_sudo { title.push_str(" | sudo"); }
+    title
+}
+
+/// Time spent per UI pass scanning the open log (line index, search match count), in
+/// steps of `LOG_SCAN_CHUNK` bytes, so typing stays responsive however slow the scan.
+const LOG_SCAN_SLICE: Duration = Duration::from_millis(30);
+const LOG_SCAN_CHUNK: u64 = 1 << 20;
+/// UI pass interval while that scan is under way.
+const LOG_SCAN_PAUSE: Duration = Duration::from_millis(50);
+
+/// Rows of text in the log popup when it fills the terminal (inner height).
+fn log_popup_rows() -> usize {
+    let (_, term_h) = crossterm::terminal::size().unwrap_or((80, 24));
+    term_h.saturating_sub(2).max(1) as usize
+}
+
+/// First line shown when the last line is at the bottom of the popup.
+fn log_last_page_top(app: &App) -> u64 {
+    app.log_doc.last_page_top(log_popup_rows())
+}
+
+/// Scroll the log popup so its last line is at the bottom.
+fn log_popup_scroll_to_end(app: &mut App) {
+    app.log_top = log_last_page_top(app);
+}
+
+/// Scroll the log popup by `n` lines (negative: up), never past the last page.
+fn log_popup_scroll_by(app: &mut App, n: i64) {
+    app.log_top = app.log_doc.step(app.log_top, n).min(log_last_page_top(app));
+}
+
+/// Whether the log popup shows its last line (follow mode keeps scrolling only then).
+fn log_popup_at_end(app: &App) -> bool {
+    app.log_top >= log_last_page_top(app)
+}
+
+/// Log popup title with the position in longer documents, the severity filter (with a hint
+/// when it leaves `nothing_shown`), the follow state (following pauses while scrolled up) and
+/// the search match counter.
+fn log_popup_title(app: &App, nothing_shown: bool) -> String {
+    let at_end = log_popup_at_end(app);
+    let mut title = app.log_detail_title.clone();
+    if app.log_top > 0 || !at_end {
+        let doc = &app.log_doc;
+        match (doc.line_number(app.log_top), doc.total_lines()) {
+            (Some(n), Some(total)) => title.push_str(&format!(" | line {}/{}", n + 1, total)),
+            (Some(n), None) => title.push_str(&format!(" | line {} ({}%)", n + 1, doc.percent(app.log_top))),
+            (None, _) => title.push_str(&format!(" | {}%", doc.percent(app.log_top))),
         }
-        (KeyCode::Char('l'), _) => {
-            if app.selected_top_tab < 5 { app.selected_top_tab += 1; }
+    }
+    if let Some(min) = app.log_severity {
+        title.push_str(&format!(" | {} and above", min.name()));
+        // Plain syslog lines have no level and count as info
+        if nothing_shown && app.log_doc.len() > 0 { title.push_str(" (nothing shown: these lines carry no level information)"); }
+    }
+    match (app.log_follow, at_end) {
+        (false, _) => {}
+        (true, true) => title.push_str(" | following"),
+        (true, false) => title.push_str(" | following (paused, End resumes)"),
+    }
+    if let Some(load) = &app.log_load { title.push_str(&format!(" | {}...", load.what())); }
+    if let Some(search) = &app.log_search {
+        // "+" while the document is still being scanned
+        let more = if search.done(&app.log_doc) { "" } else { "+" };
+        let n = search.count();
+        match app.log_search_current {
+            _ if n == 0 && more.is_empty() => title.push_str(" | no matches"),
+            _ if n == 0 || app.log_search_pending.is_some() => title.push_str(" | searching..."),
+            Some(c) => match search.position(c) {
+                Some(i) => title.push_str(&format!(" | match {}/{}{}", i + 1, n, more)),
+                None => title.push_str(&format!(" | match ?/{}{}", n, more)),
+            },
+            None => title.push_str(&format!(" | {}{} match{}", n, more, if n == 1 && more.is_empty() { "" } else { "es" })),
         }
-        (KeyCode::Char('L'), _) => {
-            if app.selected_top_tab < 5 { app.selected_top_tab += 1; }
+    }
+    title
+}
+
+/// Bottom border of the log popup: the search prompt while editing, else the active search.
+fn log_search_footer(app: &App) -> String {
+    let case = if app.log_search_case { "case-sensitive" } else { "ignoring case" };
+    if app.log_search_prompt {
+        let err = if app.log_search_error.is_empty() { String::new() } else { format!(" - {}", app.log_search_error) };
+        format!("/{}_  ({}; Tab toggles, Enter search, Esc cancel){}", app.log_search_input, case, err)
+    } else if app.log_search.is_some() {
+        format!("/{}  ({}; n/N next/prev, c case, Esc clear)", app.log_search_input, case)
+    } else {
+        String::new()
+    }
+}
+
+/// Recompile the log search from the prompt input and case setting.
+fn apply_log_search_input(app: &mut App) {
+    match compile_log_search(&app.log_search_input, app.log_search_case) {
+        Ok(re) => { app.log_search = re.map(LogSearch::new); app.log_search_error.clear(); }
+        Err(e) => { app.log_search = None; app.log_search_error = e; }
+    }
+    app.log_search_current = None;
+    app.log_search_pending = None;
+}
+
+/// Move to the next (or previous) match, wrapping around; without a current match the
+/// search starts from the top of the view. The match is scrolled to the middle of the popup.
+/// One call scans a slice at most: a jump not found yet stays pending and the main loop
+/// goes on with it on the next passes.
+fn log_search_jump(app: &mut App, forward: bool) {
+    app.log_search_pending = None;
+    let Some(search) = app.log_search.as_mut() else { return; };
+    match search.jump(&app.log_doc, app.log_search_current, app.log_top, forward, LOG_SCAN_CHUNK) {
+        Jump::Found(m) => {
+            app.log_search_current = Some(m);
+            app.log_top = app.log_doc.step(m.line, -((log_popup_rows() / 2) as i64)).min(log_last_page_top(app));
         }
-        (KeyCode::Tab, _) => {
-            app.selected_top_tab = (app.selected_top_tab + 1) % 6;
+        Jump::NoMatch => {}
+        Jump::Pending => app.log_search_pending = Some(forward),
+    }
+}
+
+/// Append new lines to the log popup, keeping the view at the bottom if it was there.
+fn append_log_text(app: &mut App, text: &str) {
+    if text.is_empty() { return; }
+    let at_end = log_popup_at_end(app);
+    app.log_doc.append(text);
+    if at_end { log_popup_scroll_to_end(app); }
+}
+
+/// Replace the document in the log popup, showing its top; an active search starts over on it.
+fn set_log_doc(app: &mut App, mut doc: LogDoc) {
+    doc.set_min_severity(app.log_severity);
+    app.log_doc = doc;
+    app.log_top = 0;
+    if let Some(search) = app.log_search.take() { app.log_search = Some(LogSearch::new(search.re)); }
+    app.log_search_current = None;
+    app.log_search_pending = None;
+}
+
+/// Apply a new severity filter to the log popup, staying near the same place in the document.
+fn set_log_severity(app: &mut App, level: Option<Severity>) {
+    app.log_severity = level;
+    app.log_doc.set_min_severity(level);
+    app.log_top = app.log_doc.shown_from(app.log_top).min(log_last_page_top(app));
+    if let Some(search) = app.log_search.take() { app.log_search = Some(LogSearch::new(search.re)); }
+    app.log_search_current = None;
+    app.log_search_pending = None;
+}
+
+/// Show a document in the log popup; `path` is the /var/log file it came from (enables follow mode).
+fn show_log_doc(app: &mut App, title: String, doc: LogDoc, path: Option<String>) {
+    app.log_detail_title = title;
+    app.log_journal_file = None;
+    set_log_doc(app, doc);
+    app.log_popup = true;
+    app.log_file_path = path;
+    app.log_stitched = false;
+    app.log_follow = false;
+    app.log_tail = None;
+    app.log_load = None;
+    app.log_load_to_end = false;
+}
+
+/// Add what the background decompression (or sudo read) of the popup's file wrote since the
+/// last pass.
+fn poll_log_load(app: &mut App) {
+    let at_end = app.log_load_to_end && log_popup_at_end(app);
+    let Some(load) = app.log_load.as_mut() else { return; };
+    match load.poll(&mut app.log_doc) {
+        Ok(false) => {}
+        Ok(true) => app.log_load = None,
+        Err(err) => {
+            if err.contains("incorrect password") { app.logs_sudo_password = None; }
+            app.log_load = None;
+            app.log_doc.append(&format!("(Failed to read: {})", err));
         }
-        (KeyCode::BackTab, _) => {
-            app.selected_top_tab = (app.selected_top_tab + 5) % 6; // -1 mod 6
+    }
+    if at_end { log_popup_scroll_to_end(app); }
+}
+
+/// Start or stop following the /var/log file in the popup. Starting reopens the file so
+/// nothing written since it was opened is missed; files only root may read are followed
+/// through `sudo tail -F`.
+fn toggle_log_file_follow(app: &mut App) {
+    if app.log_follow {
+        app.log_follow = false;
+        app.log_tail = None;
+        return;
+    }
+    let Some(path) = app.log_file_path.clone() else { return; };
+    // In the stitched view the older rotations are reopened and the live file is followed
+    let files = if app.log_stitched { log_rotations(&path) } else { vec![std::path::PathBuf::from(&path)] };
+    let Some((live, older)) = files.split_last() else { return; };
+    let live = live.to_string_lossy().to_string();
+    let live_name = std::path::Path::new(&live).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    if log_decompressor(&live).is_some() || rotation_base(&live_name) != live_name {
+        append_log_text(app, "(Cannot follow: not a live log file)");
+        return;
+    }
+    let pw = app.logs_sudo_password.clone();
+    let mut doc = match open_log_docs(older, pw.as_deref()) {
+        Ok(d) => d,
+        Err(e) => { append_log_text(app, &format!("(Cannot follow: {})", e)); return; }
+    };
+    if !older.is_empty() { doc.append(&log_header(std::path::Path::new(&live))); }
+    let opened = match (LogTail::open(&live, &mut doc), pw.as_deref()) {
+        (Err(e), Some(pw)) if e.kind() == std::io::ErrorKind::PermissionDenied => LogTail::open_sudo(&live, pw, &mut doc),
+        (res, _) => res.map_err(|e| e.to_string()),
+    };
+    match opened {
+        Ok(tail) => app.log_tail = Some(tail),
+        Err(err) => {
+            if err.contains("incorrect password") { app.logs_sudo_password = None; }
+            append_log_text(app, &format!("(Cannot follow: {})", err.trim()));
+            return;
         }
-        (KeyCode::Char('1'), _) => { app.selected_top_tab = 0; }
-        (KeyCode::Char('2'), _) => { app.selected_top_tab = 1; }
-        (KeyCode::Char('3'), _) => { app.selected_top_tab = 2; }
-        (KeyCode::Char('4'), _) => { app.selected_top_tab = 3; }
-        (KeyCode::Char('5'), _) => { app.selected_top_tab = 4; }
-        (KeyCode::Char('6'), _) => { app.selected_top_tab = 5; }
-        _ => {}
     }
+    set_log_doc(app, doc);
+    app.log_follow = true;
+    app.log_follow_last = Instant::now();
+    log_popup_scroll_to_end(app);
+}
 
-    // If we just left the Shell tab, terminate the session
-    if app.selected_top_tab != 3 {
-        if let Some(mut sess) = app.shell.take() { sess.terminate(); }
+/// Append lines written to the followed file (or printed by `journalctl -f`) since the last poll.
+fn follow_log_tail(app: &mut App) {
+    app.log_follow_last = Instant::now();
+    let Some(tail) = app.log_tail.as_mut() else { return; };
+    match tail.poll() {
+        Ok(text) => append_log_text(app, &text),
+        Err(err) => {
+            if err.contains("incorrect password") { app.logs_sudo_password = None; }
+            app.log_follow = false;
+            app.log_tail = None;
+            append_log_text(app, &format!("(Follow stopped: {})", err));
+        }
     }
+}
 
-    Ok(false)
+/// Start the confirmed unit action on a worker thread; `poll_service_action` shows its output
+/// plus the new status in the service popup, or handles a permission/polkit denial.
+fn run_selected_service_action(app: &mut App) {
+    let unit = app.service_action_unit.clone();
+    let action = SERVICE_ACTIONS.get(app.service_action_selected).cloned().unwrap_or("status");
+    open_service_popup(app, &unit);
+    let user = app.services_user;
+    // systemctl waits for the job (up to the unit's start/stop timeout), so it runs on a worker
+    app.service_detail_text = format!("$ systemctl {}{} {}\nrunning...", if user { "--user " } else { "" }, action, unit);
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let done = match run_service_action(&unit, action, user) {
+            Ok(out) => ServiceActionDone::Report(service_action_report(&unit, action, "", user, &out, true)),
+            Err(ServiceActionError::Failed(out)) => ServiceActionDone::Report(service_action_report(&unit, action, "", user, &out, false)),
+            Err(ServiceActionError::Denied(out)) => ServiceActionDone::Denied(out),
+        };
+        let _ = tx.send(done);
+    });
+    app.service_action_job = Some(rx);
+    app.service_popup = true;
+}
+
+/// Retry the pending unit action through sudo using the cached password.
+fn run_selected_service_action_sudo(app: &mut App) {
+    let Some(pw) = app.logs_sudo_password.clone() else { return; };
+    let unit = app.service_action_unit.clone();
+    let action = SERVICE_ACTIONS.get(app.service_action_selected).cloned().unwrap_or("status");
+    app.service_detail_text = format!("$ sudo systemctl {} {}\nrunning...", action, unit);
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let done = match run_service_action_sudo(&unit, action, &pw) {
+            Ok(out) => ServiceActionDone::Report(service_action_report(&unit, action, "sudo ", false, &out, true)),
+            Err(err) if err.contains("incorrect password") => ServiceActionDone::BadPassword(service_action_report(&unit, action, "sudo ", false, &err, false)),
+            Err(err) => ServiceActionDone::Report(service_action_report(&unit, action, "sudo ", false, &err, false)),
+        };
+        let _ = tx.send(done);
+    });
+    app.service_action_job = Some(rx);
+    app.service_popup = true;
+}
+
+/// Outcome of a unit action run on a worker thread.
+enum ServiceActionDone {
+    Report(String),      // popup text for the finished action
+    Denied(String),      // systemctl output; the action may be retried through sudo
+    BadPassword(String), // sudo rejected the cached password (popup text)
+}
+
+/// Show the outcome of a running unit action once it is done. The popup text is only
+/// replaced while the popup still shows the unit the action was run on.
+fn poll_service_action(app: &mut App) {
+    let Some(job) = &app.service_action_job else { return; };
+    let done = match job.try_recv() {
+        Ok(done) => done,
+        Err(std::sync::mpsc::TryRecvError::Empty) => return,
+        Err(std::sync::mpsc::TryRecvError::Disconnected) => ServiceActionDone::Report(String::from("The unit action stopped without a result.")),
+    };
+    app.service_action_job = None;
+    app.services_dirty = true;
+    if !app.service_popup || app.service_detail_title != app.service_action_unit { return; }
+    match done {
+        ServiceActionDone::Report(text) => app.service_detail_text = text,
+        ServiceActionDone::BadPassword(text) => { app.logs_sudo_password = None; app.service_detail_text = text; }
+        ServiceActionDone::Denied(out) => {
+            let action = SERVICE_ACTIONS.get(app.service_action_selected).cloned().unwrap_or("status");
+            app.service_detail_text = format!("$ systemctl {} {}\n{}\nPermission denied; retrying with sudo...", action, app.service_action_unit, out.trim_end());
+            if app.logs_sudo_password.is_some() {
+                run_selected_service_action_sudo(app);
+            } else {
+                app.service_action_sudo_pending = true;
+                app.logs_password_prompt = true;
+                app.logs_password_error.clear();
+            }
+        }
+    }
+}
+
+/// Popup text for a finished unit action: the command, its output, the outcome and the current status.
+fn service_action_report(unit: &str, action: &str, prefix: &str, user: bool, output: &str, ok: bool) -> String {
+    let output = if output.trim().is_empty() { "(no output)" } else { output.trim_end() };
+    let outcome = if ok { format!("{} {}: done.", action, unit) } else { format!("{} {}: FAILED.", action, unit) };
+    let manager = if user { "--user " } else { "" };
+    format!("$ {}systemctl {}{} {}\n{}\n{}\n\n{}", prefix, manager, action, unit, output, outcome, get_service_status(unit, user))
+}
+
+/// Send the confirmed signal to the target process and record the outcome.
+fn deliver_signal(app: &mut App) {
+    let what = signal_name(app.signal_number);
+    app.signal_denied = false;
+    app.signal_result = match send_signal(app.signal_pid, app.signal_number) {
+        Ok(()) => format!("Sent {} to PID {}.", what, app.signal_pid),
+        Err(e) if e.raw_os_error() == Some(1) => {
+            // EPERM: not our process; sudo can be tried from the result screen
+            app.signal_denied = true;
+            format!("Permission denied sending {} to PID {} (EPERM).", what, app.signal_pid)
+        }
+        Err(e) if e.raw_os_error() == Some(3) => format!("PID {} no longer exists (ESRCH).", app.signal_pid),
+        Err(e) => format!("Failed to send {} to PID {}: {}", what, app.signal_pid, e),
+    };
+    app.signal_stage = SignalStage::Result;
 }
 
+/// Retry the pending signal through sudo using the cached password.
+fn deliver_signal_sudo(app: &mut App) {
+    let what = signal_name(app.signal_number);
+    let Some(pw) = app.logs_sudo_password.clone() else { return; };
+    app.signal_result = match send_signal_sudo(app.signal_pid, app.signal_number, &pw) {
+        Ok(()) => { app.signal_denied = false; format!("Sent {} to PID {} via sudo.", what, app.signal_pid) }
+        Err(err) => {
+            // Drop a rejected password so the next attempt prompts again
+            if err.contains("incorrect password") { app.logs_sudo_password = None; }
+            format!("sudo kill failed: {}", err.trim())
+        }
+    };
+    app.signal_stage = SignalStage::Result;
+}
 
 // GPU detection helpers (Linux, best-effort via /sys/class/drm and /proc)
 /// Basic GPU information detected from the system (Linux best-effort).
@@ -1230,150 +2928,6 @@ fn read_network_counters() -> Vec<(String, u64, u64)> {
 #[cfg(not(target_os = "linux"))]
 fn read_network_counters() -> Vec<(String, u64, u64)> { Vec::new() }
 
-// All services listing (Linux)
-#[cfg(target_os = "linux")]
-fn get_all_services() -> Vec<(String, String, String)> {
-    // Use systemctl to list all services without pager/legend
-    let output = Command::new("systemctl")
-        .args(["list-units", "--type=service", "--all", "--no-legend", "--no-pager"])
-        .output();
-    let mut services: Vec<(String, String, String)> = Vec::new();
-    if let Ok(out) = output {
-        if out.status.success() {
-            if let Ok(text) = String::from_utf8(out.stdout) {
-                for line in text.lines() {
-                    let l = line.trim();
-                    if l.is_empty() { continue; }
-                    // Expected columns: UNIT LOAD ACTIVE SUB DESCRIPTION
-                    let toks: Vec<&str> = l.split_whitespace().collect();
-                    if toks.len() >= 5 {
-                        let unit = toks[0].to_string();
-                        let active = toks[2].to_string();
-                        let desc = toks[4..].join(" ");
-                        services.push((unit, active, desc));
-                    }
-                }
-            }
-        }
-    }
-    // Sort by unit name for stable display
-    services.sort_by(|a, b| a.0.cmp(&b.0));
-    services
-}
-
-#[cfg(not(target_os = "linux"))]
-fn get_all_services() -> Vec<(String, String, String)> { Vec::new() }
-
-// Fetch detailed status text for a service (Linux)
-#[cfg(target_os = "linux")]
-fn get_service_status(unit: &str) -> String {
-    let output = Command::new("systemctl")
-        .args(["status", unit, "--no-pager", "--full"]) 
-        .output();
-    match output {
-        Ok(out) if out.status.success() => String::from_utf8(out.stdout).unwrap_or_else(|_| String::from("(failed to decode output)")),
-        Ok(out) => {
-            let mut s = String::new();
-            if !out.stdout.is_empty() {
-                s = String::from_utf8_lossy(&out.stdout).into_owned();
-            }
-            if !out.stderr.is_empty() {
-                if !s.is_empty() { s.push_str("\n"); }
-                s.push_str(&String::from_utf8_lossy(&out.stderr));
-            }
-            if s.is_empty() { s = String::from("(no output)"); }
-            s
-        }
-        Err(_) => String::from("systemctl not available or failed to execute."),
-    }
-}
-
-#[cfg(not(target_os = "linux"))]
-fn get_service_status(_unit: &str) -> String { String::from("Service details are supported on Linux only.") }
-
-// -------- Process details helpers (Linux best-effort) --------
-#[cfg(target_os = "linux")]
-fn get_process_name(pid: i32) -> String {
-    let comm_path = format!("/proc/{}/comm", pid);
-    if let Ok(s) = std::fs::read_to_string(&comm_path) {
-        let name = s.trim();
-        if !name.is_empty() { return name.to_string(); }
-    }
-    let status_path = format!("/proc/{}/status", pid);
-    if let Ok(s) = std::fs::read_to_string(&status_path) {
-        if let Some(line) = s.lines().find(|l| l.starts_with("Name:")) {
-            return line[5..].trim().to_string();
-        }
-    }
-    String::new()
-}
-
-#[cfg(not(target_os = "linux"))]
-fn get_process_name(_pid: i32) -> String { String::new() }
-
-#[cfg(target_os = "linux")]
-fn get_process_details(pid: i32) -> String {
-    use std::path::PathBuf;
-    let mut out = String::new();
-    let status_path = format!("/proc/{}/status", pid);
-    let stat_path = format!("/proc/{}/stat", pid);
-    let cmdline_path = format!("/proc/{}/cmdline", pid);
-    let exe_path = PathBuf::from(format!("/proc/{}/exe", pid));
-    let cwd_path = PathBuf::from(format!("/proc/{}/cwd", pid));
-
-    // Name, State, PPID, Uid, Gid, Threads, VmSize, VmRSS
-    if let Ok(s) = std::fs::read_to_string(&status_path) {
-        for key in ["Name:", "State:", "PPid:", "Uid:", "Gid:", "Threads:", "VmSize:", "VmRSS:"].iter() {
-            if let Some(line) = s.lines().find(|l| l.starts_with(key)) {
-                out.push_str(line.trim());
-                out.push('\n');
-            }
-        }
-    } else {
-        out.push_str("(status not available)\n");
-    }
-
-    // Priority/Nice
-    if let Ok(s) = std::fs::read_to_string(&stat_path) {
-        if let Some(rparen) = s.rfind(')') {
-            let after = &s[rparen+2..];
-            let toks: Vec<&str> = after.split_whitespace().collect();
-            let pri = toks.get(15).and_then(|x| x.parse::<i64>().ok()).unwrap_or(0);
-            let ni = toks.get(16).and_then(|x| x.parse::<i64>().ok()).unwrap_or(0);
-            out.push_str(&format!("Priority: {}\nNice: {}\n", pri, ni));
-        }
-    }
-
-    // Exe and CWD
-    if let Ok(p) = std::fs::read_link(&exe_path) { out.push_str(&format!("Exe: {}\n", p.to_string_lossy())); }
-    if let Ok(p) = std::fs::read_link(&cwd_path) { out.push_str(&format!("Cwd: {}\n", p.to_string_lossy())); }
-
-    // Cmdline
-    if let Ok(raw) = std::fs::read(&cmdline_path) {
-        if !raw.is_empty() {
-            let parts: Vec<String> = raw.split(|b| *b == 0).filter(|s| !s.is_empty()).map(|s| String::from_utf8_lossy(s).into_owned()).collect();
-            if !parts.is_empty() {
-                out.push_str("Cmdline: ");
-                out.push_str(&parts.join(" "));
-                out.push('\n');
-            }
-        }
-    }
-
-    // Open file descriptors count
-    let fd_dir = format!("/proc/{}/fd", pid);
-    if let Ok(rd) = std::fs::read_dir(&fd_dir) {
-        let count = rd.filter(|e| e.is_ok()).count();
-        out.push_str(&format!("FDs: {}\n", count));
-    }
-
-    if out.is_empty() { out = String::from("(no details)"); }
-    out
-}
-
-#[cfg(not(target_os = "linux"))]
-fn get_process_details(pid: i32) -> String { format!("Process details are supported on Linux only. PID {}", pid) }
-
 // -------- Logs helpers --------
 #[derive(Clone)]
 struct LogEntry { name: String, path: String, size: u64, modified: String }
@@ -1517,123 +3071,43 @@ fn build_system_prompt() -> String {
     format!("{}@{}", user, host)
 }
 
-fn read_log_file_best_effort(path: &str, sudo_pass: Option<&str>) -> Result<String, String> {
-    // First try normal read
-    match std::fs::read_to_string(path) {
-        Ok(mut s) => {
-            cap_log_text(&mut s);
-            return Ok(s);
-        }
-        Err(e) => {
-            // If permission denied and sudo password provided, try sudo -S cat
-            if e.kind() == std::io::ErrorKind::PermissionDenied {
-                if let Some(pw) = sudo_pass {
-                    let mut child = match Command::new("sudo")
-                        .arg("-S")
-                        .arg("--")
-                        .arg("cat")
-                        .arg(path)
-                        .stdin(Stdio::piped())
-                        .stdout(Stdio::piped())
-                        .stderr(Stdio::piped())
-                        .spawn() {
-                        Ok(c) => c,
-                        Err(spawn_err) => return Err(format!("Failed to spawn sudo: {}", spawn_err)),
-                    };
-                    if let Some(mut stdin) = child.stdin.take() {
-                        use std::io::Write;
-                        let _ = stdin.write_all(pw.as_bytes());
-                        let _ = stdin.write_all(b"\n");
-                    }
-                    let out = child.wait_with_output().map_err(|e| format!("sudo error: {}", e))?;
-                    if out.status.success() {
-                        let mut text = String::from_utf8_lossy(&out.stdout).into_owned();
-                        cap_log_text(&mut text);
-                        return Ok(text);
-                    } else {
-                        let err = String::from_utf8_lossy(&out.stderr).into_owned();
-                        return Err(if err.trim().is_empty() { String::from("sudo failed") } else { err });
-                    }
-                }
-            }
-            // Other errors or no password
-            return Err(format!("{}", e));
-        }
-    }
+/// Open a /var/log file for the popup, or with `stitched` the file and all its rotations, oldest
+/// first. Rotated .gz/.xz/.zst/.bz2 files are decompressed in the background (see `poll_log_load`).
+/// Uses sudo when a password is cached.
+fn open_log_file_best_effort(path: &str, stitched: bool, sudo_pass: Option<&str>) -> Result<(LogDoc, Option<LogLoad>), String> {
+    let paths = if stitched { log_rotations(path) } else { vec![std::path::PathBuf::from(path)] };
+    LogLoad::start(&paths, stitched, sudo_pass)
 }
 
-fn read_journal_file_best_effort(path: &str, sudo_pass: Option<&str>) -> Result<String, String> {
-    // Use journalctl to read entries from a specific journal file.
-    // Try without sudo first.
-    let output_res = Command::new("journalctl")
-        .arg("--file").arg(path)
-        .arg("-n").arg("5000")
-        .arg("-o").arg("short-iso")
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output();
-    let need_sudo = match output_res {
-        Ok(out) => {
-            if out.status.success() {
-                let mut text = String::from_utf8_lossy(&out.stdout).into_owned();
-                cap_log_text(&mut text);
-                return Ok(text);
-            } else {
-                let err = String::from_utf8_lossy(&out.stderr).to_string();
-                if err.to_ascii_lowercase().contains("permission") && sudo_pass.is_some() {
-                    true
-                } else {
-                    return Err(if err.trim().is_empty() { String::from("journalctl failed") } else { err });
-                }
-            }
-        }
-        Err(e) => {
-            return Err(format!("Failed to run journalctl: {}", e));
-        }
-    };
-
-    if need_sudo {
-        if let Some(pw) = sudo_pass {
-            let mut child = match Command::new("sudo")
-                .arg("-S")
-                .arg("--")
-                .arg("journalctl")
-                .arg("--file").arg(path)
-                .arg("-n").arg("5000")
-                .arg("-o").arg("short-iso")
-                .stdin(Stdio::piped())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn() {
-                    Ok(c) => c,
-                    Err(spawn_err) => return Err(format!("Failed to spawn sudo: {}", spawn_err)),
-                };
-            if let Some(mut stdin) = child.stdin.take() {
-                use std::io::Write;
-                let _ = stdin.write_all(pw.as_bytes());
-                let _ = stdin.write_all(b"\n");
-            }
-            let out = child.wait_with_output().map_err(|e| format!("sudo error: {}", e))?;
-            if out.status.success() {
-                let mut text = String::from_utf8_lossy(&out.stdout).into_owned();
-                cap_log_text(&mut text);
-                return Ok(text);
-            } else {
-                let err = String::from_utf8_lossy(&out.stderr).into_owned();
-                return Err(if err.trim().is_empty() { String::from("sudo journalctl failed") } else { err });
-            }
-        }
+/// Log popup title for a /var/log file, or for the file and its rotations.
+fn log_file_title(path: &str, stitched: bool) -> String {
+    let name = path.strip_prefix("/var/log/").unwrap_or(path);
+    if !stitched { return name.to_string(); }
+    let base = match name.rsplit_once('/') { Some((dir, file)) => format!("{}/{}", dir, rotation_base(file)), None => rotation_base(name).to_string() };
+    match log_rotations(path).len().saturating_sub(1) {
+        0 => format!("{} (no rotations)", base),
+        n => format!("{} + {} rotation{}", base, n, if n == 1 { "" } else { "s" }),
     }
-    Err(String::from("Unable to read journal (no sudo password provided)"))
 }
 
-fn cap_log_text(s: &mut String) {
-    // Keep at most last ~5000 lines to avoid huge popups
-    let max_lines = 5000usize;
-    let lines: Vec<&str> = s.lines().collect();
-    if lines.len() > max_lines {
-        let tail = &lines[lines.len()-max_lines..];
-        *s = tail.join("\n");
+fn read_journal_file_best_effort(path: &str, priority: usize, sudo_pass: Option<&str>) -> Result<(LogDoc, Option<LogLoad>), String> {
+    // All entries of a specific journal file, spooled to disk in the background for paging like
+    // other logs; `priority` indexes JOURNAL_PRIORITIES. Files we may not read go through sudo.
+    let mut args = vec!["--file", path, "-o", "short-iso"];
+    if let Some(p) = JOURNAL_PRIORITIES.get(priority).filter(|p| **p != "all") { args.extend(["-p", p]); }
+    let denied = std::fs::File::open(path).is_err_and(|e| e.kind() == std::io::ErrorKind::PermissionDenied);
+    match (denied, sudo_pass) {
+        (false, _) => {
+            let mut cmd = Command::new("journalctl");
+            cmd.args(&args);
+            LogLoad::command(cmd, None)
+        }
+        (true, Some(pw)) => {
+            let mut cmd = Command::new("sudo");
+            cmd.args(["-S", "-p", "", "--", "journalctl"]).args(&args);
+            LogLoad::command(cmd, Some(pw))
+        }
+        (true, None) => Err(String::from("Permission denied")),
     }
 }
 
@@ -1749,6 +3223,39 @@ fn get_hw_manufacturer_and_model() -> (Option<String>, Option<String>) {
 #[cfg(not(target_os = "linux"))]
 fn get_hw_manufacturer_and_model() -> (Option<String>, Option<String>) { (None, None) }
 
+/// Failed units listed in the Dashboard alert panel before it summarizes the rest.
+const FAILED_PANEL_ROWS: usize = 8;
+
+/// Dashboard alert panel: failed system units with the time they failed and their result.
+fn draw_failed_units(f: &mut ratatui::Frame<'_>, area: Rect, failed: &[services::FailedUnit]) {
+    let red = Style::default().fg(Color::Red);
+    let unit_w = failed.iter().map(|u| u.name.chars().count()).max().unwrap_or(0).clamp(4, 40);
+    let mut lines: Vec<Line> = vec![Line::from(Span::styled(
+        format!("{:<unit_w$}  {:<19}  {:<12}  {}", "UNIT", "FAILED AT", "RESULT", "DESCRIPTION"),
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+    ))];
+    let shown = if failed.len() > FAILED_PANEL_ROWS { FAILED_PANEL_ROWS - 1 } else { failed.len() };
+    for u in &failed[..shown] {
+        let since = u.since.map(|us| format_local_datetime(us / 1_000_000)).unwrap_or_else(|| "-".to_string());
+        let result = if u.result.is_empty() { "-" } else { &u.result };
+        lines.push(Line::from(Span::styled(
+            format!("{:<unit_w$}  {:<19}  {:<12}  {}", u.name, since, result, u.description),
+            red,
+        )));
+    }
+    if shown < failed.len() {
+        lines.push(Line::from(Span::styled(
+            format!("... and {} more (F4, then F to list failed units)", failed.len() - shown),
+            red.add_modifier(Modifier::ITALIC),
+        )));
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(red)
+        .title(Span::styled(format!(" Failed units ({}) ", failed.len()), red.add_modifier(Modifier::BOLD)));
+    f.render_widget(ratatui::widgets::Paragraph::new(lines).block(block), area);
+}
+
 /// Draw the main content: top tabs (CPU/Graphics/Memory) and the Processes area.
 fn draw_header(
     f: &mut ratatui::Frame<'_>,
@@ -1757,7 +3264,7 @@ fn draw_header(
     app: &App,
 ) {
     // Best-effort CPU temperature (Linux): read from hwmon/thermal sysfs when available
-    let cpu_temp_c = read_cpu_temperature_c();
+    let cpu_temp_c = app.cpu_temp_c;
     let global_cpu = sys.global_cpu_usage(); // percent
     let used_mem = sys.used_memory(); // KiB
     let total_mem = sys.total_memory(); // KiB
@@ -1773,7 +3280,7 @@ fn draw_header(
     let gpus = &app.gpus;
     let gfx_height: u16 = (gpus.len() as u16 + 1 + 2).max(3); // header + rows + block borders
     // Applications frame target height (header + rows + borders). Ensure it can fit all apps.
-    let apps_rows: u16 = build_applications_status().len() as u16; // typically 6 rows
+    let apps_rows: u16 = app.apps_status.len() as u16; // typically 6 rows
     let apps_block_height: u16 = (apps_rows + 1 + 2).max(3);
     // Height for the combined Applications | GPU row should fit the larger of the two
     let gfx_app_height: u16 = gfx_height.max(apps_block_height);
@@ -1783,13 +3290,16 @@ fn draw_header(
     let top_frames_height: u16 = sys_block_height.max(cpu_block_height).max(mem_block_height);
 
     // Disks info for System tab Disks frame sizing (best-effort, Linux-focused)
-    let disks = list_disks_best_effort();
+    let disks = &app.disks;
     let disks_block_height: u16 = (disks.len() as u16 + 1 + 2).max(3); // header + rows + borders
     let proc_block_height: u16 = 12; // Process frame fixed height (inner ~10 rows) + borders
+    // Failed units alert panel: only shown when something has failed (header + up to 8 rows + borders)
+    let failed_rows: u16 = app.failed_units.len().min(FAILED_PANEL_ROWS) as u16;
+    let failed_block_height: u16 = if failed_rows == 0 { 0 } else { failed_rows + 1 + 2 };
 
     // Decide current top content height based on selected tab (0 = Dashboard, 1 = top/htop, 2 = Services, 3 = Shell, 4 = Logs, 5 = Journal)
     let top_content_height = match app.selected_top_tab {
-        0 => top_frames_height + gfx_height + disks_block_height + proc_block_height,
+        0 => failed_block_height + top_frames_height + gfx_height + disks_block_height + proc_block_height,
         1 => cpu_height,
         2 => cpu_height.max(5), // Services tab height (min)
         3 => cpu_height.max(5), // Shell tab height (min)
@@ -2020,10 +3530,19 @@ fn draw_header(
 
         // Render Processes as a scrollable selectable Table at the bottom of the top/htop tab
         {
-            use std::collections::HashMap;
-            use std::fs;
-
-            let proc_area = cpu_chunks[5];
+            // Reserve a one-line bar under the table while a filter is being edited or active, or
+            // the column layout could not be saved
+            let show_filter = app.procs_filter_prompt || !app.procs_filter_input.is_empty();
+            let show_bar = show_filter || !app.columns_error.is_empty();
+            let (proc_area, bar_area) = if show_bar && cpu_chunks[5].height > 2 {
+                let parts = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(1), Constraint::Length(1)])
+                    .split(cpu_chunks[5]);
+                (parts[0], Some(parts[1]))
+            } else {
+                (cpu_chunks[5], None)
+            };
             // Rows per page (minus header)
             let rows_per_page = proc_area.height.saturating_sub(1) as usize;
 
@@ -2035,42 +3554,34 @@ fn draw_header(
             if selected < start { start = selected; }
             if rows_per_page > 0 && selected >= start + rows_per_page { start = selected + 1 - rows_per_page; }
 
-            // Build a uid->username map from /etc/passwd (simple parser)
-            let mut uid_name: HashMap<u32, String> = HashMap::new();
-            if let Ok(passwd) = fs::read_to_string("/etc/passwd") {
-                for line in passwd.lines() {
-                    if line.trim().is_empty() || line.starts_with('#') { continue; }
-                    let parts: Vec<&str> = line.split(':').collect();
-                    if parts.len() > 3 {
-                        if let Ok(uid) = parts[2].parse::<u32>() {
-                            uid_name.insert(uid, parts[0].to_string());
-                        }
-                    }
+            // Configured columns that fit the terminal width (trailing ones are dropped first)
+            let columns = fit_process_columns(&app.procs_columns, proc_area.width);
+            let needs_extra = columns.iter().any(|c| matches!(c, ProcColumn::State | ProcColumn::Threads | ProcColumn::Tty | ProcColumn::Shr | ProcColumn::Cgroup | ProcColumn::Container));
+            let needs_cgroup = columns.iter().any(|c| matches!(c, ProcColumn::Cgroup | ProcColumn::Container));
+
+            // Header: the active sort column is highlighted with a direction arrow
+            let header = Row::new(columns.iter().map(|k| {
+                let mut label = k.label().to_string();
+                if *k == ProcColumn::Cmd && app.procs_tree_mode { label.push_str(" (tree)"); }
+                if *k == app.procs_sort_key {
+                    label.push(if app.procs_sort_desc { '▼' } else { '▲' });
+                    Cell::from(Span::styled(label, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)))
+                } else {
+                    Cell::from(Span::styled(label, Style::default().add_modifier(Modifier::BOLD)))
                 }
-            }
-
-            // Header
-            let header = Row::new(vec![
-                Cell::from(Span::styled("PID", Style::default().add_modifier(Modifier::BOLD))),
-                Cell::from(Span::styled("USER", Style::default().add_modifier(Modifier::BOLD))),
-                Cell::from(Span::styled("PRI", Style::default().add_modifier(Modifier::BOLD))),
-                Cell::from(Span::styled("NI", Style::default().add_modifier(Modifier::BOLD))),
-                Cell::from(Span::styled("CPU%", Style::default().add_modifier(Modifier::BOLD))),
-                Cell::from(Span::styled("MEM%", Style::default().add_modifier(Modifier::BOLD))),
-                Cell::from(Span::styled("TIME", Style::default().add_modifier(Modifier::BOLD))),
-                Cell::from(Span::styled("CMD", Style::default().add_modifier(Modifier::BOLD))),
-            ]);
+            }).collect::<Vec<Cell>>());
 
             // Build rows from cached PID ordering
             let mut rows: Vec<Row> = Vec::new();
             let total_mem_kib_f = total_mem as f32;
             for (i, pid) in app.procs_pids_sorted.iter().cloned().skip(start).take(rows_per_page).enumerate() {
                 // Find process by pid from sys
+                let proc_ref = sys.processes().iter().find(|(p, _)| p.as_u32() as i32 == pid).map(|(_, p)| p);
                 let mut cpu = 0.0f32;
                 let mut mem_pct = 0.0f32;
                 let mut time_str = String::new();
                 let mut cmd = String::new();
-                if let Some((_, p)) = sys.processes().iter().find(|(p, _)| p.as_u32() as i32 == pid) {
+                if let Some(p) = proc_ref {
                     cpu = p.cpu_usage();
                     let mem_kib = p.memory();
                     mem_pct = if total_mem_kib_f > 0.0 { (mem_kib as f32 / total_mem_kib_f) * 100.0 } else { 0.0 };
@@ -2080,85 +3591,107 @@ fn draw_header(
                     let minutes = (secs % 3_600) / 60;
                     let seconds = secs % 60;
                     time_str = if days > 0 { format!("{}d {:02}:{:02}:{:02}", days, hours, minutes, seconds) } else { format!("{:02}:{:02}:{:02}", hours, minutes, seconds) };
-                    cmd = if !p.cmd().is_empty() { p.cmd().join(std::ffi::OsStr::new(" ")).to_string_lossy().into_owned() } else { p.name().to_string_lossy().into_owned() };
-                }
-                // USER via /proc
-                let user = {
-                    let status_path = format!("/proc/{}/status", pid);
-                    if let Ok(s) = fs::read_to_string(&status_path) {
-                        if let Some(uid_line) = s.lines().find(|l| l.starts_with("Uid:")) {
-                            let mut it = uid_line.split_whitespace();
-                            it.next();
-                            if let Some(uid_str) = it.next() {
-                                if let Ok(uid) = uid_str.parse::<u32>() {
-                                    uid_name.get(&uid).cloned().unwrap_or_else(|| uid.to_string())
-                                } else { String::from("?") }
-                            } else { String::from("?") }
-                        } else { String::from("?") }
-                    } else { String::from("?") }
-                };
-                // PRI/NI via /proc
-                let (pri, ni) = {
-                    let stat_path = format!("/proc/{}/stat", pid);
-                    if let Ok(s) = fs::read_to_string(&stat_path) {
-                        if let Some(rparen) = s.rfind(')') {
-                            let after = &s[rparen+2..];
-                            let toks: Vec<&str> = after.split_whitespace().collect();
-                            let pri = toks.get(15).and_then(|x| x.parse::<i64>().ok()).unwrap_or(0);
-                            let ni = toks.get(16).and_then(|x| x.parse::<i64>().ok()).unwrap_or(0);
-                            (pri, ni)
-                        } else { (0, 0) }
-                    } else { (0, 0) }
-                };
+                    cmd = process_cmd_string(p);
+                }
+                // Tree mode: indent the command and show rolled-up usage for collapsed branches
+                if let Some(tr) = app.procs_tree_rows.get(start + i).filter(|tr| tr.pid == pid) {
+                    cmd = format!("{}{}", tr.prefix, cmd);
+                    if tr.collapsed {
+                        cpu = tr.subtree_cpu;
+                        mem_pct = if total_mem_kib_f > 0.0 { (tr.subtree_mem as f32 / total_mem_kib_f) * 100.0 } else { 0.0 };
+                    }
+                }
+                // USER and PRI/NI via /proc; optional columns from /proc/<pid>/{stat,statm,cgroup}
+                let user = get_process_user(pid, &app.uid_names);
+                let (pri, ni) = get_process_pri_nice(pid);
+                let extra = if needs_extra { read_process_extra(pid, needs_cgroup) } else { ProcExtra::default() };
                 // Build row
-                let mut row = Row::new(vec![
-                    Cell::from(Span::raw(format!("{:>6}", pid))),
-                    Cell::from(Span::raw(format!("{:<8}", user))),
-                    Cell::from(Span::raw(format!("{:>3}", pri))),
-                    Cell::from(Span::raw(format!("{:>3}", ni))),
-                    Cell::from(Span::raw(format!("{:>6.1}", cpu))),
-                    Cell::from(Span::raw(format!("{:>7.1}", mem_pct))),
-                    Cell::from(Span::raw(format!("{:>10}", time_str))),
-                    Cell::from(Span::raw(cmd)),
-                ]);
+                let io = app.procs_io_rates.get(&pid).cloned();
+                let mut row = Row::new(columns.iter().map(|k| match k {
+                    ProcColumn::Pid => Cell::from(Span::raw(format!("{:>7}", pid))),
+                    ProcColumn::Ppid => Cell::from(Span::raw(format!("{:>7}", proc_ref.and_then(|p| p.parent()).map(|pp| pp.as_u32().to_string()).unwrap_or_else(|| String::from("-"))))),
+                    ProcColumn::User => Cell::from(Span::raw(format!("{:<8}", user))),
+                    ProcColumn::Pri => Cell::from(Span::raw(format!("{:>4}", pri))),
+                    ProcColumn::Ni => Cell::from(Span::raw(format!("{:>3}", ni))),
+                    ProcColumn::State => Cell::from(Span::raw(extra.state.to_string())),
+                    ProcColumn::Threads => Cell::from(Span::raw(format!("{:>4}", extra.threads))),
+                    ProcColumn::Cpu => Cell::from(Span::raw(format!("{:>6.1}", cpu))),
+                    ProcColumn::Mem => Cell::from(Span::raw(format!("{:>7.1}", mem_pct))),
+                    ProcColumn::Rss => Cell::from(Span::raw(format!("{:>9}", fmt_bytes(proc_ref.map(|p| p.memory()).unwrap_or(0))))),
+                    ProcColumn::Virt => Cell::from(Span::raw(format!("{:>9}", fmt_bytes(proc_ref.map(|p| p.virtual_memory()).unwrap_or(0))))),
+                    ProcColumn::Shr => Cell::from(Span::raw(format!("{:>9}", fmt_bytes(extra.shared)))),
+                    ProcColumn::Read => Cell::from(Span::raw(format!("{:>9}", fmt_io_rate(io.map(|r| r.0))))),
+                    ProcColumn::Write => Cell::from(Span::raw(format!("{:>9}", fmt_io_rate(io.map(|r| r.1))))),
+                    ProcColumn::Start => Cell::from(Span::raw(format!("{:>6}", proc_ref.map(|p| format_start_time(p.start_time())).unwrap_or_default()))),
+                    ProcColumn::Time => Cell::from(Span::raw(format!("{:>10}", time_str))),
+                    ProcColumn::Tty => Cell::from(Span::raw(extra.tty.clone())),
+                    ProcColumn::Cgroup => Cell::from(Span::raw(extra.cgroup.clone())),
+                    ProcColumn::Container => Cell::from(Span::raw(extra.container.clone())),
+                    ProcColumn::Exe => Cell::from(Span::raw(proc_ref.and_then(|p| p.exe()).map(|e| e.to_string_lossy().into_owned()).unwrap_or_else(|| String::from("-")))),
+                    ProcColumn::Cmd => Cell::from(Span::raw(cmd.clone())),
+                }).collect::<Vec<Cell>>());
+                // Tree context rows kept only as ancestors of filter matches are dimmed
+                if !app.procs_match_flags.get(start + i).cloned().unwrap_or(true) {
+                    row = row.style(Style::default().fg(Color::DarkGray));
+                }
                 if start + i == selected {
                     row = row.style(Style::default().add_modifier(Modifier::REVERSED));
                 }
                 rows.push(row);
             }
             if rows.is_empty() {
-                rows.push(Row::new(vec![
-                    Cell::from(Span::raw("No processes.")),
-                    Cell::from(Span::raw("")),
-                    Cell::from(Span::raw("")),
-                    Cell::from(Span::raw("")),
-                    Cell::from(Span::raw("")),
-                    Cell::from(Span::raw("")),
-                    Cell::from(Span::raw("")),
-                    Cell::from(Span::raw("")),
-                ]));
+                let mut cells = vec![Cell::from(Span::raw(if app.procs_filter.is_some() { "No matching processes." } else { "No processes." }))];
+                cells.resize(columns.len(), Cell::from(Span::raw("")));
+                rows.push(Row::new(cells));
             }
 
-            // Column widths: PID 6, USER 8, PRI 3, NI 3, CPU% 6, MEM% 7, TIME 10, CMD fills rest
-            let table = Table::new(
-                rows,
-                [
-                    Constraint::Length(6),
-                    Constraint::Length(8),
-                    Constraint::Length(3),
-                    Constraint::Length(3),
-                    Constraint::Length(6),
-                    Constraint::Length(7),
-                    Constraint::Length(10),
-                    Constraint::Min(10),
-                ],
-            )
+            // Column widths: fixed for numeric columns (room for the sort arrow); CMD, EXE and
+            // CGROUP share what is left, CMD getting the largest part
+            let widths: Vec<Constraint> = columns.iter().map(|k| match k.width() {
+                Some(w) => Constraint::Length(w),
+                None => Constraint::Fill(k.fill_weight()),
+            }).collect();
+            let table = Table::new(rows, widths)
             .header(header)
             .block(Block::default());
 
             f.render_widget(table, proc_area);
+
+            // Filter bar: prompt while editing, otherwise the active filter and match count;
+            // then any filter or column save error
+            if let Some(bar) = bar_area {
+                let matches = app.procs_match_flags.iter().filter(|m| **m).count();
+                let mut spans: Vec<Span> = Vec::new();
+                if show_filter {
+                    spans.push(Span::styled(" Filter ", Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD)));
+                    if app.procs_filter_prompt {
+                        spans.push(Span::raw(format!(" /{}_", app.procs_filter_input)));
+                        spans.push(Span::styled("  (Enter apply, Esc clear; pid:N, user:NAME, re:REGEX)", Style::default().fg(Color::DarkGray)));
+                    } else {
+                        spans.push(Span::styled(format!(" {} ", app.procs_filter_input), Style::default().fg(Color::Cyan)));
+                        spans.push(Span::raw(format!(" {} match{}", matches, if matches == 1 { "" } else { "es" })));
+                        let hint = if app.procs_tree_mode { "  (/ edit, n/N next/prev, Esc clear)" } else { "  (/ edit, Esc clear)" };
+                        spans.push(Span::styled(hint, Style::default().fg(Color::DarkGray)));
+                    }
+                    if !app.procs_filter_error.is_empty() {
+                        spans.push(Span::styled(format!("  {}", app.procs_filter_error), Style::default().fg(Color::Red)));
+                    }
+                }
+                if !app.columns_error.is_empty() {
+                    spans.push(Span::styled(format!("  {}", app.columns_error), Style::default().fg(Color::Red)));
+                }
+                f.render_widget(ratatui::widgets::Paragraph::new(Line::from(spans)), bar);
+            }
         }
     } else if app.selected_top_tab == 0 {
+        // Failed units alert panel on top, only when there is something to report
+        let alert_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(failed_block_height), Constraint::Min(0)])
+            .split(top_area);
+        if failed_block_height > 0 {
+            draw_failed_units(f, alert_chunks[0], &app.failed_units);
+        }
         // System tab content: top frames row (System | CPU), then GPU frame
         let sys_gfx_chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -2168,7 +3701,7 @@ fn draw_header(
                 Constraint::Length(disks_block_height),// Disks block
                 Constraint::Min(5),                    // Process block fills remaining space
             ])
-            .split(top_area);
+            .split(alert_chunks[1]);
 
         // Split the top frames area into three columns: System (left), CPU (middle), Memory (right)
         let top_frames_cols = Layout::default()
@@ -2186,7 +3719,7 @@ fn draw_header(
         let os_ver = System::long_os_version().unwrap_or_else(|| System::os_version().unwrap_or_else(|| "Unknown".to_string()));
         let host = System::host_name().unwrap_or_else(|| "Unknown".to_string());
         let proc_model = get_processor_model_string(sys);
-        let (manu_opt, model_opt) = get_hw_manufacturer_and_model();
+        let (manu_opt, model_opt) = app.hw_model.clone();
         let manufacturer = manu_opt.unwrap_or_else(|| "N/A".to_string());
         let hw_model = model_opt.unwrap_or_else(|| "N/A".to_string());
 
@@ -2486,7 +4019,7 @@ fn draw_header(
             };
             if app_inner.width > 0 && app_inner.height > 0 {
                 // Build rows for Applications table
-                let rows_data = build_applications_status();
+                let rows_data = &app.apps_status;
                 let header = Row::new(vec![
                     Cell::from(Span::styled("Application", Style::default().add_modifier(Modifier::BOLD))),
                     Cell::from(Span::styled("Active", Style::default().add_modifier(Modifier::BOLD))),
@@ -2565,7 +4098,7 @@ fn draw_header(
         if disks.is_empty() {
             dlines.push(Line::from(Span::raw("No disks detected (or unsupported OS)")));
         } else {
-            for d in &disks {
+            for d in disks {
                 let dev = trunc(&d.dev, dev_w);
                 let mnt = trunc(&d.mount, mnt_w);
                 let fs = trunc(&d.fs, fs_w);
@@ -2647,13 +4180,38 @@ fn draw_header(
 
     } else if app.selected_top_tab == 2 {
         // Services tab
-        let block = Block::default().borders(Borders::ALL).title(" Services (SystemD) ");
+        let source = app.services_source;
+        let services = shown_services(app);
+        // Unit type and manager, then the filtered count whenever a state filter or name search is active
+        let unit_type = UNIT_TYPES.get(app.services_type).cloned().unwrap_or("service");
+        let manager = if app.services_user { "user" } else { "system" };
+        let type_total = app.services.iter().filter(|u| u.unit_type() == unit_type).count();
+        let count = if app.services_filter == ServiceFilter::All && app.services_search.is_empty() {
+            format!("{}", type_total)
+        } else {
+            format!("{}: {} of {}", app.services_filter.label(), services.len(), type_total)
+        };
+        let title_style = if app.services_filter == ServiceFilter::Failed && !services.is_empty() { Style::default().fg(Color::Red) } else { Style::default() };
+        let block = Block::default().borders(Borders::ALL).title(Span::styled(format!(" Services (SystemD) [{}] {} {} units: {} ", source, manager, unit_type, count), title_style));
         f.render_widget(block, top_area);
-        let inner = Rect { x: top_area.x + 1, y: top_area.y + 1, width: top_area.width.saturating_sub(2), height: top_area.height.saturating_sub(2) };
+        let mut inner = Rect { x: top_area.x + 1, y: top_area.y + 1, width: top_area.width.saturating_sub(2), height: top_area.height.saturating_sub(2) };
+        // One-line search bar at the bottom while the name search is being edited or active
+        if (app.services_search_prompt || !app.services_search.is_empty()) && inner.height > 2 {
+            inner.height -= 1;
+            let bar = Rect { y: inner.y + inner.height, height: 1, ..inner };
+            let mut spans = vec![Span::styled(" Search ", Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD))];
+            if app.services_search_prompt {
+                spans.push(Span::raw(format!(" /{}_", app.services_search)));
+                spans.push(Span::styled("  (Enter apply, Esc clear)", Style::default().fg(Color::DarkGray)));
+            } else {
+                spans.push(Span::styled(format!(" {} ", app.services_search), Style::default().fg(Color::Cyan)));
+                spans.push(Span::styled("  (/ edit, Esc clear)", Style::default().fg(Color::DarkGray)));
+            }
+            f.render_widget(ratatui::widgets::Paragraph::new(Line::from(spans)), bar);
+        }
         if inner.width > 0 && inner.height > 0 {
             #[cfg(target_os = "linux")]
             {
-                let services = get_all_services();
                 // Clamp selection within available items (compute effective selection)
                 let total = services.len();
                 let selected = app.services_selected.min(total.saturating_sub(1));
@@ -2663,44 +4221,57 @@ fn draw_header(
                 let mut start = app.services_scroll.min(max_start);
                 if selected < start { start = selected; }
                 if rows_per_page > 0 && selected >= start + rows_per_page { start = selected + 1 - rows_per_page; }
-                // Build header and rows
-                let header = Row::new(vec![
-                    Cell::from(Span::styled("UNIT", Style::default().add_modifier(Modifier::BOLD))),
-                    Cell::from(Span::styled("ACTIVE", Style::default().add_modifier(Modifier::BOLD))),
-                    Cell::from(Span::styled("DESCRIPTION", Style::default().add_modifier(Modifier::BOLD))),
-                ]);
+                // Header: the active sort column is highlighted with a direction arrow
+                let columns = service_columns(app);
+                let header = Row::new(columns.iter().map(|c| {
+                    let mut label = c.label().to_string();
+                    if *c == app.services_sort_key {
+                        label.push(if app.services_sort_desc { '▼' } else { '▲' });
+                        Cell::from(Span::styled(label, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)))
+                    } else {
+                        Cell::from(Span::styled(label, Style::default().add_modifier(Modifier::BOLD)))
+                    }
+                }).collect::<Vec<Cell>>());
                 let mut rows: Vec<Row> = Vec::new();
-                for (i, (unit, active, desc)) in services.into_iter().skip(start).take(rows_per_page).enumerate() {
-                    let unit_style = if active == "active" { Style::default().fg(Color::Green) } else { Style::default().fg(Color::Red) };
-                    let mut row = Row::new(vec![
-                        Cell::from(Span::styled(unit, unit_style)),
-                        Cell::from(Span::raw(active)),
-                        Cell::from(Span::raw(desc)),
-                    ]);
+                for (i, svc) in services.iter().skip(start).take(rows_per_page).enumerate() {
+                    let unit_style = if svc.active == "active" { Style::default().fg(Color::Green) } else { Style::default().fg(Color::Red) };
+                    let usage = app.services_usage.get(&svc.name).cloned().unwrap_or_default();
+                    let opt = |v: Option<String>, w: usize| format!("{:>w$}", v.unwrap_or_else(|| String::from("-")), w = w);
+                    let cells: Vec<Cell> = columns.iter().map(|c| match c {
+                        ServiceColumn::Unit => Cell::from(Span::styled(svc.name.clone(), unit_style)),
+                        ServiceColumn::Load => Cell::from(Span::raw(svc.load.clone())),
+                        ServiceColumn::Active => Cell::from(Span::raw(svc.active.clone())),
+                        ServiceColumn::Sub => Cell::from(Span::raw(svc.sub.clone())),
+                        ServiceColumn::MainPid => Cell::from(Span::raw(opt(svc.main_pid.map(|p| p.to_string()), 8))),
+                        ServiceColumn::Tasks => Cell::from(Span::raw(opt(usage.tasks.map(|n| n.to_string()), 6))),
+                        ServiceColumn::Memory => Cell::from(Span::raw(opt(usage.memory.map(fmt_bytes), 10))),
+                        ServiceColumn::Cpu => Cell::from(Span::raw(opt(usage.cpu_usec.map(format_cpu_usec), 11))),
+                        ServiceColumn::Next => Cell::from(Span::raw(opt(svc.next_elapse.map(|us| format_local_datetime(us / 1_000_000)), 19))),
+                        ServiceColumn::Last => Cell::from(Span::raw(opt(svc.last_trigger.map(|us| format_local_datetime(us / 1_000_000)), 19))),
+                        ServiceColumn::Description => Cell::from(Span::raw(svc.description.clone())),
+                    }).collect();
+                    let mut row = Row::new(cells);
                     if start + i == selected {
                         row = row.style(Style::default().add_modifier(Modifier::REVERSED));
                     }
                     rows.push(row);
                 }
                 if rows.is_empty() {
-                    rows.push(Row::new(vec![
-                        Cell::from(Span::raw("No services found.")),
-                        Cell::from(Span::raw("")),
-                        Cell::from(Span::raw("")),
-                    ]));
+                    let msg = if type_total == 0 { "No units found." } else { "No matching units." };
+                    rows.push(Row::new(vec![Cell::from(Span::raw(msg))]));
                 }
-                // Calculate column widths: UNIT ~ 40, ACTIVE ~ 10, DESCRIPTION fills rest
-                let unit_w = 40u16.min(inner.width.saturating_sub(12));
-                let table = Table::new(
-                    rows,
-                    [
-                        Constraint::Length(unit_w),
-                        Constraint::Length(10),
-                        Constraint::Min(10),
-                    ],
-                )
-                .header(header)
-                .block(Block::default());
+                // Fixed widths for the state/usage columns; UNIT takes up to 40 cells and
+                // DESCRIPTION fills the rest
+                let fixed: u16 = columns.iter().filter_map(|c| c.width()).map(|w| w + 1).sum();
+                let unit_w = 40u16.min(inner.width.saturating_sub(fixed + 11)).max(12);
+                let widths: Vec<Constraint> = columns.iter().map(|c| match (c, c.width()) {
+                    (_, Some(w)) => Constraint::Length(w),
+                    (ServiceColumn::Unit, None) => Constraint::Length(unit_w),
+                    _ => Constraint::Min(10),
+                }).collect();
+                let table = Table::new(rows, widths)
+                    .header(header)
+                    .block(Block::default());
                 f.render_widget(table, inner);
             }
             #[cfg(not(target_os = "linux"))]
@@ -2753,7 +4324,7 @@ fn draw_header(
         f.render_widget(block, top_area);
         let inner = Rect { x: top_area.x + 1, y: top_area.y + 1, width: top_area.width.saturating_sub(2), height: top_area.height.saturating_sub(2) };
         if inner.width > 0 && inner.height > 0 {
-            let files = list_var_log_files();
+            let files = &app.log_files;
             let total = files.len();
             let selected = app.logs_selected.min(total.saturating_sub(1));
             let rows_per_page = inner.height.saturating_sub(1) as usize;
@@ -2768,11 +4339,11 @@ fn draw_header(
                 Cell::from(Span::styled("MODIFIED", Style::default().add_modifier(Modifier::BOLD))),
             ]);
             let mut rows: Vec<Row> = Vec::new();
-            for (i, ent) in files.into_iter().skip(start).take(rows_per_page).enumerate() {
+            for (i, ent) in files.iter().skip(start).take(rows_per_page).enumerate() {
                 let mut row = Row::new(vec![
-                    Cell::from(Span::raw(ent.name)),
+                    Cell::from(Span::raw(ent.name.as_str())),
                     Cell::from(Span::raw(fmt_bytes(ent.size))),
-                    Cell::from(Span::raw(ent.modified)),
+                    Cell::from(Span::raw(ent.modified.as_str())),
                 ]);
                 if start + i == selected { row = row.style(Style::default().add_modifier(Modifier::REVERSED)); }
                 rows.push(row);
@@ -2803,7 +4374,7 @@ fn draw_header(
         f.render_widget(block, top_area);
         let inner = Rect { x: top_area.x + 1, y: top_area.y + 1, width: top_area.width.saturating_sub(2), height: top_area.height.saturating_sub(2) };
         if inner.width > 0 && inner.height > 0 {
-            let files = list_journal_files();
+            let files = &app.journal_files;
             let total = files.len();
             let selected = app.journal_selected.min(total.saturating_sub(1));
             let rows_per_page = inner.height.saturating_sub(1) as usize;
@@ -2818,11 +4389,11 @@ fn draw_header(
                 Cell::from(Span::styled("MODIFIED", Style::default().add_modifier(Modifier::BOLD))),
             ]);
             let mut rows: Vec<Row> = Vec::new();
-            for (i, ent) in files.into_iter().skip(start).take(rows_per_page).enumerate() {
+            for (i, ent) in files.iter().skip(start).take(rows_per_page).enumerate() {
                 let mut row = Row::new(vec![
-                    Cell::from(Span::raw(ent.name)),
+                    Cell::from(Span::raw(ent.name.as_str())),
                     Cell::from(Span::raw(fmt_bytes(ent.size))),
-                    Cell::from(Span::raw(ent.modified)),
+                    Cell::from(Span::raw(ent.modified.as_str())),
                 ]);
                 if start + i == selected { row = row.style(Style::default().add_modifier(Modifier::REVERSED)); }
                 rows.push(row);
@@ -75,3 +75,1094 @@ pub fn get_process_details(pid: i32) -> String {
 
 #[cfg(not(target_os = "linux"))]
 pub fn get_process_details(pid: i32) -> String { format!("Process details are supported on Linux only. PID {}", pid) }
+
+// Read the parent PID from the PPid: line of /proc/<pid>/status (Linux)
+#[cfg(target_os = "linux")]
+pub fn get_process_ppid(pid: i32) -> Option<i32> {
+    let status_path = format!("/proc/{}/status", pid);
+    let s = std::fs::read_to_string(&status_path).ok()?;
+    let line = s.lines().find(|l| l.starts_with("PPid:"))?;
+    line[5..].trim().parse::<i32>().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn get_process_ppid(_pid: i32) -> Option<i32> { None }
+
+// -------- Process tree (top/htop tree mode) --------
+/// A single visible row of the flattened process tree, in display order.
+#[derive(Debug, Clone)]
+pub struct ProcTreeRow {
+    pub pid: i32,
+    pub prefix: String, // tree glyphs drawn in front of the command
+    pub has_children: bool,
+    pub collapsed: bool,
+    pub subtree_cpu: f32, // CPU% of this process plus all of its descendants
+    pub subtree_mem: u64, // memory (bytes) of this process plus all of its descendants
+}
+
+/// Build the flattened process tree from `(pid, ppid, cpu%, mem bytes)` tuples.
+///
+/// Siblings keep the order in which they appear in `procs`, so callers pass the
+/// list already sorted. Descendants of PIDs in `collapsed` are hidden and their
+/// CPU/memory is rolled up into the collapsed row.
+pub fn build_process_tree(
+    procs: &[(i32, Option<i32>, f32, u64)],
+    collapsed: &std::collections::HashSet<i32>,
+) -> Vec<ProcTreeRow> {
+    use std::collections::{HashMap, HashSet};
+    let known: HashSet<i32> = procs.iter().map(|p| p.0).collect();
+    let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+    let mut usage: HashMap<i32, (f32, u64)> = HashMap::new();
+    let mut roots: Vec<i32> = Vec::new();
+    for (pid, ppid, cpu, mem) in procs.iter().cloned() {
+        usage.insert(pid, (cpu, mem));
+        match ppid {
+            Some(pp) if pp != pid && known.contains(&pp) => children.entry(pp).or_default().push(pid),
+            _ => roots.push(pid),
+        }
+    }
+
+    let mut totals: HashMap<i32, (f32, u64)> = HashMap::new();
+    for root in &roots { roll_up_usage(*root, &children, &usage, &mut totals); }
+    // A PPid cycle (parents read at different moments) is unreachable from the roots: break
+    // it at its first process in `procs`, which becomes a root itself
+    for (pid, ppid, _, _) in procs {
+        if totals.contains_key(pid) { continue; }
+        if let Some(pp) = ppid && let Some(siblings) = children.get_mut(pp) { siblings.retain(|c| c != pid); }
+        roots.push(*pid);
+        roll_up_usage(*pid, &children, &usage, &mut totals);
+    }
+
+    // Flatten depth-first, carrying the continuation glyphs for each level
+    let mut out: Vec<ProcTreeRow> = Vec::with_capacity(procs.len());
+    let mut stack: Vec<(i32, String, String)> = roots.iter().rev().map(|p| (*p, String::new(), String::new())).collect();
+    while let Some((pid, branch, cont)) = stack.pop() {
+        let kids = children.get(&pid).map(|v| v.as_slice()).unwrap_or(&[]);
+        let has_children = !kids.is_empty();
+        let is_collapsed = has_children && collapsed.contains(&pid);
+        let marker = if is_collapsed { "+ " } else if has_children { "- " } else { "" };
+        let (subtree_cpu, subtree_mem) = totals.get(&pid).cloned().unwrap_or((0.0, 0));
+        out.push(ProcTreeRow { pid, prefix: format!("{}{}", branch, marker), has_children, collapsed: is_collapsed, subtree_cpu, subtree_mem });
+        if is_collapsed { continue; }
+        for (i, c) in kids.iter().enumerate().rev() {
+            let last = i + 1 == kids.len();
+            let child_branch = format!("{}{}", cont, if last { "└─ " } else { "├─ " });
+            let child_cont = format!("{}{}", cont, if last { "   " } else { "│  " });
+            stack.push((*c, child_branch, child_cont));
+        }
+    }
+    out
+}
+
+/// Add the CPU/memory totals of `root` and everything below it to `totals`, bottom-up
+/// (iterative post-order to avoid deep recursion).
+fn roll_up_usage(
+    root: i32,
+    children: &std::collections::HashMap<i32, Vec<i32>>,
+    usage: &std::collections::HashMap<i32, (f32, u64)>,
+    totals: &mut std::collections::HashMap<i32, (f32, u64)>,
+) {
+    let mut stack: Vec<(i32, bool)> = vec![(root, false)];
+    while let Some((pid, visited)) = stack.pop() {
+        if visited {
+            let (mut cpu, mut mem) = usage.get(&pid).cloned().unwrap_or((0.0, 0));
+            for c in children.get(&pid).map(|v| v.as_slice()).unwrap_or(&[]) {
+                if let Some((ccpu, cmem)) = totals.get(c) { cpu += ccpu; mem += cmem; }
+            }
+            totals.insert(pid, (cpu, mem));
+        } else if !totals.contains_key(&pid) {
+            stack.push((pid, true));
+            for c in children.get(&pid).map(|v| v.as_slice()).unwrap_or(&[]).iter().rev() {
+                stack.push((*c, false));
+            }
+        }
+    }
+}
+
+// -------- Signals (top/htop signal menu) --------
+/// Signals offered in the top/htop signal menu, as (name, number) on Linux.
+pub const SIGNALS: &[(&str, i32)] = &[
+    ("TERM", 15),
+    ("KILL", 9),
+    ("HUP", 1),
+    ("INT", 2),
+    ("STOP", 19),
+    ("CONT", 18),
+    ("USR1", 10),
+    ("USR2", 12),
+];
+
+/// Human-readable name for a signal number (falls back to the number).
+pub fn signal_name(sig: i32) -> String {
+    match SIGNALS.iter().find(|(_, n)| *n == sig) {
+        Some((name, _)) => format!("SIG{} ({})", name, sig),
+        None => format!("signal {}", sig),
+    }
+}
+
+#[cfg(unix)]
+unsafe extern "C" {
+    fn kill(pid: i32, sig: i32) -> i32;
+    fn getuid() -> u32;
+}
+
+/// Real user ID of this process.
+#[cfg(unix)]
+pub(crate) fn current_uid() -> u32 { unsafe { getuid() } }
+
+/// Send `sig` to a single process. PIDs <= 0 are rejected so a stale selection
+/// can never turn into a process-group or broadcast kill.
+#[cfg(unix)]
+pub fn send_signal(pid: i32, sig: i32) -> std::io::Result<()> {
+    if pid <= 0 { return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput)); }
+    let rc = unsafe { kill(pid, sig) };
+    if rc == 0 { Ok(()) } else { Err(std::io::Error::last_os_error()) }
+}
+
+#[cfg(not(unix))]
+pub fn send_signal(_pid: i32, _sig: i32) -> std::io::Result<()> { Err(std::io::Error::from(std::io::ErrorKind::Unsupported)) }
+
+/// Send `sig` to `pid` via `sudo kill` using the cached sudo password.
+pub fn send_signal_sudo(pid: i32, sig: i32, password: &str) -> Result<(), String> {
+    if pid <= 0 { return Err(String::from("invalid PID")); }
+    let sig_arg = format!("-{}", sig);
+    let pid_arg = pid.to_string();
+    super::sudo::run_with_sudo(&["kill", &sig_arg, &pid_arg], password).map(|_| ())
+}
+
+// -------- Per-process column helpers (shared by the table and sorting) --------
+/// Build a uid -> username map from /etc/passwd (simple parser).
+pub fn load_uid_names() -> std::collections::HashMap<u32, String> {
+    let mut uid_name = std::collections::HashMap::new();
+    if let Ok(passwd) = std::fs::read_to_string("/etc/passwd") {
+        for line in passwd.lines() {
+            if line.trim().is_empty() || line.starts_with('#') { continue; }
+            let parts: Vec<&str> = line.split(':').collect();
+            if parts.len() > 3 && let Ok(uid) = parts[2].parse::<u32>() {
+                uid_name.insert(uid, parts[0].to_string());
+            }
+        }
+    }
+    uid_name
+}
+
+/// Resolve the USER column: username for the real UID in /proc/<pid>/status, the
+/// numeric UID if unknown, or "?" if it cannot be read.
+pub fn get_process_user(pid: i32, uid_name: &std::collections::HashMap<u32, String>) -> String {
+    let status_path = format!("/proc/{}/status", pid);
+    let uid = std::fs::read_to_string(&status_path).ok().and_then(|s| {
+        let uid_line = s.lines().find(|l| l.starts_with("Uid:"))?.to_string();
+        uid_line.split_whitespace().nth(1)?.parse::<u32>().ok()
+    });
+    match uid {
+        Some(uid) => uid_name.get(&uid).cloned().unwrap_or_else(|| uid.to_string()),
+        None => String::from("?"),
+    }
+}
+
+/// Read (priority, nice) from /proc/<pid>/stat; (0, 0) when unavailable.
+pub fn get_process_pri_nice(pid: i32) -> (i64, i64) {
+    let stat_path = format!("/proc/{}/stat", pid);
+    if let Ok(s) = std::fs::read_to_string(&stat_path) && let Some(rparen) = s.rfind(')') {
+        let after = &s[rparen+2..];
+        let toks: Vec<&str> = after.split_whitespace().collect();
+        let pri = toks.get(15).and_then(|x| x.parse::<i64>().ok()).unwrap_or(0);
+        let ni = toks.get(16).and_then(|x| x.parse::<i64>().ok()).unwrap_or(0);
+        return (pri, ni);
+    }
+    (0, 0)
+}
+
+// -------- Columns and sorting (top/htop) --------
+/// Columns the process table can show (and be sorted by), in default chooser order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProcColumn { Pid, Ppid, User, Pri, Ni, State, Threads, Cpu, Mem, Rss, Virt, Shr, Read, Write, Start, Time, Tty, Cgroup, Container, Exe, Cmd }
+
+impl ProcColumn {
+    pub const ALL: [ProcColumn; 21] = [
+        ProcColumn::Pid, ProcColumn::Ppid, ProcColumn::User, ProcColumn::Pri, ProcColumn::Ni,
+        ProcColumn::State, ProcColumn::Threads, ProcColumn::Cpu, ProcColumn::Mem, ProcColumn::Rss,
+        ProcColumn::Virt, ProcColumn::Shr, ProcColumn::Read, ProcColumn::Write, ProcColumn::Start,
+        ProcColumn::Time, ProcColumn::Tty, ProcColumn::Cgroup, ProcColumn::Container, ProcColumn::Exe,
+        ProcColumn::Cmd,
+    ];
+
+    /// Columns shown when no config file exists.
+    pub const DEFAULT: [ProcColumn; 8] = [
+        ProcColumn::Pid, ProcColumn::User, ProcColumn::Pri, ProcColumn::Ni,
+        ProcColumn::Cpu, ProcColumn::Mem, ProcColumn::Time, ProcColumn::Cmd,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ProcColumn::Pid => "PID",
+            ProcColumn::Ppid => "PPID",
+            ProcColumn::User => "USER",
+            ProcColumn::Pri => "PRI",
+            ProcColumn::Ni => "NI",
+            ProcColumn::State => "S",
+            ProcColumn::Threads => "THR",
+            ProcColumn::Cpu => "CPU%",
+            ProcColumn::Mem => "MEM%",
+            ProcColumn::Rss => "RSS",
+            ProcColumn::Virt => "VIRT",
+            ProcColumn::Shr => "SHR",
+            ProcColumn::Read => "READ/s",
+            ProcColumn::Write => "WRITE/s",
+            ProcColumn::Start => "START",
+            ProcColumn::Time => "TIME",
+            ProcColumn::Tty => "TTY",
+            ProcColumn::Cgroup => "CGROUP",
+            ProcColumn::Container => "CONTAINER",
+            ProcColumn::Exe => "EXE",
+            ProcColumn::Cmd => "CMD",
+        }
+    }
+
+    /// Longer name for the column chooser.
+    pub fn description(self) -> &'static str {
+        match self {
+            ProcColumn::Pid => "Process ID",
+            ProcColumn::Ppid => "Parent process ID",
+            ProcColumn::User => "Owner",
+            ProcColumn::Pri => "Kernel priority",
+            ProcColumn::Ni => "Nice value",
+            ProcColumn::State => "State (R/S/D/Z/T...)",
+            ProcColumn::Threads => "Number of threads",
+            ProcColumn::Cpu => "CPU usage",
+            ProcColumn::Mem => "Memory usage",
+            ProcColumn::Rss => "Resident memory",
+            ProcColumn::Virt => "Virtual memory",
+            ProcColumn::Shr => "Shared memory",
+            ProcColumn::Read => "Disk read rate (/proc/<pid>/io)",
+            ProcColumn::Write => "Disk write rate (/proc/<pid>/io)",
+            ProcColumn::Start => "Start time",
+            ProcColumn::Time => "Running time",
+            ProcColumn::Tty => "Controlling terminal",
+            ProcColumn::Cgroup => "Control group",
+            ProcColumn::Container => "Container (docker/podman/k8s/lxc)",
+            ProcColumn::Exe => "Executable path",
+            ProcColumn::Cmd => "Command line",
+        }
+    }
+
+    /// Stable name used in the config file.
+    pub fn config_name(self) -> &'static str {
+        match self {
+            ProcColumn::Pid => "pid",
+            ProcColumn::Ppid => "ppid",
+            ProcColumn::User => "user",
+            ProcColumn::Pri => "pri",
+            ProcColumn::Ni => "ni",
+            ProcColumn::State => "state",
+            ProcColumn::Threads => "threads",
+            ProcColumn::Cpu => "cpu",
+            ProcColumn::Mem => "mem",
+            ProcColumn::Rss => "rss",
+            ProcColumn::Virt => "virt",
+            ProcColumn::Shr => "shr",
+            ProcColumn::Read => "read",
+            ProcColumn::Write => "write",
+            ProcColumn::Start => "start",
+            ProcColumn::Time => "time",
+            ProcColumn::Tty => "tty",
+            ProcColumn::Cgroup => "cgroup",
+            ProcColumn::Container => "container",
+            ProcColumn::Exe => "exe",
+            ProcColumn::Cmd => "cmd",
+        }
+    }
+
+    pub fn from_config_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().cloned().find(|c| c.config_name() == name.trim())
+    }
+
+    /// Whether this is one of the I/O rate columns (which need /proc/<pid>/io sampling).
+    pub fn is_io(self) -> bool {
+        matches!(self, ProcColumn::Read | ProcColumn::Write)
+    }
+
+    /// Whether sorting by this column reads a /proc file per process (done off the UI thread).
+    pub fn sorts_from_proc(self) -> bool {
+        matches!(self, ProcColumn::User | ProcColumn::Pri | ProcColumn::Ni | ProcColumn::State | ProcColumn::Threads | ProcColumn::Shr | ProcColumn::Tty | ProcColumn::Cgroup | ProcColumn::Container)
+    }
+
+    /// Width on screen, or None for text columns that share the remaining space.
+    pub fn width(self) -> Option<u16> {
+        match self {
+            ProcColumn::Pid | ProcColumn::Ppid => Some(7),
+            ProcColumn::User => Some(8),
+            ProcColumn::Pri => Some(4),
+            ProcColumn::Ni => Some(3),
+            ProcColumn::State => Some(2),
+            ProcColumn::Threads => Some(4),
+            ProcColumn::Cpu => Some(6),
+            ProcColumn::Mem => Some(7),
+            ProcColumn::Rss | ProcColumn::Virt | ProcColumn::Shr | ProcColumn::Read | ProcColumn::Write => Some(9),
+            ProcColumn::Start => Some(6),
+            ProcColumn::Time => Some(10),
+            ProcColumn::Tty => Some(7),
+            ProcColumn::Container => Some(18),
+            ProcColumn::Cgroup | ProcColumn::Exe | ProcColumn::Cmd => None,
+        }
+    }
+
+    /// Share of the leftover width for text columns (CMD gets the most).
+    pub fn fill_weight(self) -> u16 {
+        match self {
+            ProcColumn::Cmd => 3,
+            ProcColumn::Cgroup | ProcColumn::Exe => 2,
+            _ => 1,
+        }
+    }
+
+    /// Next column to the right among `columns` (wrapping).
+    pub fn next(self, columns: &[ProcColumn]) -> Self {
+        let i = columns.iter().position(|k| *k == self).unwrap_or(0);
+        columns.get((i + 1) % columns.len().max(1)).cloned().unwrap_or(self)
+    }
+
+    /// Previous column to the left among `columns` (wrapping).
+    pub fn prev(self, columns: &[ProcColumn]) -> Self {
+        let i = columns.iter().position(|k| *k == self).unwrap_or(0);
+        columns.get((i + columns.len().max(1) - 1) % columns.len().max(1)).cloned().unwrap_or(self)
+    }
+
+    /// Usage columns start with the biggest consumers on top; identity columns ascend.
+    pub fn default_descending(self) -> bool {
+        matches!(self, ProcColumn::Cpu | ProcColumn::Mem | ProcColumn::Rss | ProcColumn::Virt | ProcColumn::Shr | ProcColumn::Threads | ProcColumn::Read | ProcColumn::Write | ProcColumn::Time)
+    }
+}
+
+/// Drop columns (from the right, never CMD) until the fixed-width ones fit in `width`,
+/// leaving at least 10 cells for the text columns.
+pub fn fit_process_columns(columns: &[ProcColumn], width: u16) -> Vec<ProcColumn> {
+    let mut cols: Vec<ProcColumn> = columns.to_vec();
+    loop {
+        let fixed: u16 = cols.iter().map(|c| c.width().unwrap_or(10) + 1).sum();
+        if fixed <= width || cols.len() <= 1 { return cols; }
+        match cols.iter().rposition(|c| *c != ProcColumn::Cmd) {
+            Some(i) => { cols.remove(i); }
+            None => return cols,
+        }
+    }
+}
+
+// -------- Extra per-process fields (optional top/htop columns) --------
+/// Fields for the optional columns that sysinfo does not provide.
+#[derive(Debug, Clone, Default)]
+pub struct ProcExtra {
+    pub state: char,
+    pub threads: u64,
+    pub tty: String,
+    pub shared: u64, // bytes
+    pub cgroup: String,
+    pub container: String,
+}
+
+/// Read the optional column fields from /proc/<pid>/{stat,statm,cgroup}. The cgroup file
+/// is only read when `with_cgroup` is set (CGROUP/CONTAINER columns).
+#[cfg(target_os = "linux")]
+pub fn read_process_extra(pid: i32, with_cgroup: bool) -> ProcExtra {
+    let mut ex = ProcExtra { state: '?', tty: String::from("?"), ..Default::default() };
+    if let Ok(s) = std::fs::read_to_string(format!("/proc/{}/stat", pid)) && let Some(rparen) = s.rfind(')') {
+        let toks: Vec<&str> = s.get(rparen + 2..).unwrap_or("").split_whitespace().collect();
+        ex.state = toks.first().and_then(|t| t.chars().next()).unwrap_or('?');
+        ex.threads = toks.get(17).and_then(|x| x.parse::<u64>().ok()).unwrap_or(0);
+        ex.tty = tty_name(toks.get(4).and_then(|x| x.parse::<i64>().ok()).unwrap_or(0));
+    }
+    if let Ok(s) = std::fs::read_to_string(format!("/proc/{}/statm", pid)) {
+        let pages = s.split_whitespace().nth(2).and_then(|x| x.parse::<u64>().ok()).unwrap_or(0);
+        ex.shared = pages * page_size();
+    }
+    if with_cgroup {
+        ex.cgroup = read_process_cgroup(pid);
+        ex.container = container_from_cgroup(&ex.cgroup);
+    }
+    ex
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_process_extra(_pid: i32, _with_cgroup: bool) -> ProcExtra { ProcExtra { state: '?', tty: String::from("?"), ..Default::default() } }
+
+/// Decode the tty_nr field of /proc/<pid>/stat into a name like "pts/3" ("?" for none).
+fn tty_name(tty_nr: i64) -> String {
+    if tty_nr == 0 { return String::from("?"); }
+    let major = (tty_nr >> 8) & 0xfff;
+    let minor = (tty_nr & 0xff) | ((tty_nr >> 12) & 0xfff00);
+    match major {
+        136..=143 => format!("pts/{}", minor + (major - 136) * 256),
+        4 if minor < 64 => format!("tty{}", minor),
+        4 => format!("ttyS{}", minor - 64),
+        _ => format!("{}:{}", major, minor),
+    }
+}
+
+#[cfg(unix)]
+fn page_size() -> u64 {
+    // _SC_PAGESIZE is 30 on Linux
+    let v = unsafe { sysconf(30) };
+    if v > 0 { v as u64 } else { 4096 }
+}
+
+#[cfg(not(unix))]
+fn page_size() -> u64 { 4096 }
+
+/// The process's cgroup path: the unified (v2) entry, else the first v1 hierarchy.
+#[cfg(target_os = "linux")]
+pub fn read_process_cgroup(pid: i32) -> String {
+    let Ok(s) = std::fs::read_to_string(format!("/proc/{}/cgroup", pid)) else { return String::new(); };
+    let path_of = |l: &str| l.splitn(3, ':').nth(2).unwrap_or("").to_string();
+    s.lines().find(|l| l.starts_with("0::")).map(path_of).or_else(|| s.lines().next().map(path_of)).unwrap_or_default()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_process_cgroup(_pid: i32) -> String { String::new() }
+
+/// Guess the container runtime and ID from a cgroup path ("-" when not containerized).
+pub fn container_from_cgroup(cgroup: &str) -> String {
+    let short = |id: &str| id.chars().take(12).collect::<String>();
+    for seg in cgroup.split('/').rev() {
+        let seg = seg.trim_end_matches(".scope");
+        if let Some(id) = seg.strip_prefix("docker-") { return format!("docker:{}", short(id)); }
+        if let Some(id) = seg.strip_prefix("libpod-") { return format!("podman:{}", short(id)); }
+        if let Some(id) = seg.strip_prefix("cri-containerd-") { return format!("k8s:{}", short(id)); }
+        if let Some(id) = seg.strip_prefix("crio-") { return format!("k8s:{}", short(id)); }
+        if let Some(name) = seg.strip_prefix("lxc.payload.") { return format!("lxc:{}", name); }
+    }
+    let parts: Vec<&str> = cgroup.split('/').collect();
+    for (i, seg) in parts.iter().enumerate() {
+        let next = parts.get(i + 1).cloned().unwrap_or("");
+        if next.is_empty() { continue; }
+        match *seg {
+            "docker" => return format!("docker:{}", short(next)),
+            "lxc" => return format!("lxc:{}", next),
+            _ => {}
+        }
+    }
+    if cgroup.contains("kubepods") { return format!("k8s:{}", short(parts.last().cloned().unwrap_or(""))); }
+    String::from("-")
+}
+
+#[cfg(unix)]
+#[repr(C)]
+struct Tm {
+    tm_sec: i32, tm_min: i32, tm_hour: i32, tm_mday: i32, tm_mon: i32, tm_year: i32,
+    tm_wday: i32, tm_yday: i32, tm_isdst: i32, tm_gmtoff: std::ffi::c_long, tm_zone: *const std::ffi::c_char,
+}
+
+#[cfg(unix)]
+unsafe extern "C" { fn localtime_r(t: *const std::ffi::c_long, out: *mut Tm) -> *mut Tm; }
+
+/// Local (year, month 0-11, day, hour, minute, second) for a Unix timestamp.
+#[cfg(unix)]
+fn local_time(secs: u64) -> Option<(i32, i32, i32, i32, i32, i32)> {
+    let t = secs as std::ffi::c_long;
+    let mut tm = Tm { tm_sec: 0, tm_min: 0, tm_hour: 0, tm_mday: 0, tm_mon: 0, tm_year: 0, tm_wday: 0, tm_yday: 0, tm_isdst: 0, tm_gmtoff: 0, tm_zone: std::ptr::null() };
+    let r = unsafe { localtime_r(&t, &mut tm) };
+    if r.is_null() { None } else { Some((tm.tm_year + 1900, tm.tm_mon, tm.tm_mday, tm.tm_hour, tm.tm_min, tm.tm_sec)) }
+}
+
+#[cfg(not(unix))]
+fn local_time(_secs: u64) -> Option<(i32, i32, i32, i32, i32, i32)> { None }
+
+/// Local "YYYY-MM-DD HH:MM:SS" for a Unix timestamp.
+pub fn format_local_datetime(secs: u64) -> String {
+    match local_time(secs) {
+        Some((y, m, d, hh, mm, ss)) => format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", y, m + 1, d, hh, mm, ss),
+        None => format!("{}s", secs),
+    }
+}
+
+/// Process start time like ps(1) STIME: "HH:MM" today, "MonDD" this year, else the year.
+pub fn format_start_time(start_secs: u64) -> String {
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    match (local_time(start_secs), local_time(now)) {
+        (Some((y, m, d, hh, mm, _)), Some((ny, nm, nd, _, _, _))) => {
+            if (y, m, d) == (ny, nm, nd) { format!("{:02}:{:02}", hh, mm) }
+            else if y == ny { format!("{}{:02}", MONTHS[m.clamp(0, 11) as usize], d) }
+            else { y.to_string() }
+        }
+        _ => String::from("-"),
+    }
+}
+
+// -------- Per-process I/O (top/htop READ/s and WRITE/s columns) --------
+/// Bytes read from and written to storage so far, from /proc/<pid>/io.
+/// None when the file is not readable (other users' processes without root).
+#[cfg(target_os = "linux")]
+pub fn read_process_io(pid: i32) -> Option<(u64, u64)> {
+    let s = std::fs::read_to_string(format!("/proc/{}/io", pid)).ok()?;
+    let field = |key: &str| s.lines().find(|l| l.starts_with(key)).and_then(|l| l[key.len()..].trim().parse::<u64>().ok());
+    Some((field("read_bytes:")?, field("write_bytes:")?))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_process_io(_pid: i32) -> Option<(u64, u64)> { None }
+
+// -------- Filtering (top/htop "/" prompt) --------
+/// A parsed process filter. Input syntax: `pid:<n>`, `user:<name>`, `re:<regex>`,
+/// anything else is a case-insensitive substring of the command line.
+pub enum ProcFilter {
+    Substring(String),
+    Regex(regex::Regex),
+    User(String),
+    Pid(i32),
+}
+
+impl ProcFilter {
+    /// Parse filter input. Empty input means "no filter"; bad input returns an error message.
+    pub fn parse(input: &str) -> Result<Option<ProcFilter>, String> {
+        let input = input.trim();
+        if input.is_empty() { return Ok(None); }
+        if let Some(rest) = input.strip_prefix("pid:") {
+            return rest.trim().parse::<i32>().map(|p| Some(ProcFilter::Pid(p))).map_err(|_| format!("Invalid PID: {}", rest.trim()));
+        }
+        if let Some(rest) = input.strip_prefix("user:") {
+            return Ok(Some(ProcFilter::User(rest.trim().to_string())));
+        }
+        if let Some(rest) = input.strip_prefix("re:") {
+            return regex::Regex::new(rest).map(|re| Some(ProcFilter::Regex(re))).map_err(|e| format!("Invalid regex: {}", e));
+        }
+        Ok(Some(ProcFilter::Substring(input.to_lowercase())))
+    }
+
+    /// Whether `matches` needs the USER column (which costs a /proc read).
+    pub fn needs_user(&self) -> bool { matches!(self, ProcFilter::User(_)) }
+
+    pub fn matches(&self, pid: i32, cmd: &str, user: &str) -> bool {
+        match self {
+            ProcFilter::Substring(s) => cmd.to_lowercase().contains(s),
+            ProcFilter::Regex(re) => re.is_match(cmd),
+            ProcFilter::User(u) => user == u,
+            ProcFilter::Pid(p) => pid == *p,
+        }
+    }
+}
+
+// -------- Scheduling: nice, CPU affinity, I/O priority (top/htop) --------
+#[cfg(target_os = "linux")]
+unsafe extern "C" {
+    fn setpriority(which: i32, who: u32, prio: i32) -> i32;
+    fn sched_getaffinity(pid: i32, cpusetsize: usize, mask: *mut u64) -> i32;
+    fn sched_setaffinity(pid: i32, cpusetsize: usize, mask: *const u64) -> i32;
+    fn syscall(num: std::ffi::c_long, ...) -> std::ffi::c_long;
+}
+
+// ioprio_get/ioprio_set have no libc wrappers; syscall numbers differ per architecture
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+const SYS_IOPRIO: Option<(std::ffi::c_long, std::ffi::c_long)> = Some((251, 252)); // (set, get)
+#[cfg(all(target_os = "linux", target_arch = "x86"))]
+const SYS_IOPRIO: Option<(std::ffi::c_long, std::ffi::c_long)> = Some((289, 290));
+#[cfg(all(target_os = "linux", any(target_arch = "aarch64", target_arch = "riscv64")))]
+const SYS_IOPRIO: Option<(std::ffi::c_long, std::ffi::c_long)> = Some((30, 31));
+#[cfg(all(target_os = "linux", target_arch = "arm"))]
+const SYS_IOPRIO: Option<(std::ffi::c_long, std::ffi::c_long)> = Some((314, 315));
+#[cfg(all(target_os = "linux", not(any(target_arch = "x86_64", target_arch = "x86", target_arch = "aarch64", target_arch = "riscv64", target_arch = "arm"))))]
+const SYS_IOPRIO: Option<(std::ffi::c_long, std::ffi::c_long)> = None;
+
+/// Maximum number of CPUs handled by affinity masks (same as glibc's cpu_set_t).
+pub const MAX_AFFINITY_CPUS: usize = 1024;
+
+/// I/O scheduling classes as used by ioprio_set(2) and `ionice -c`.
+pub const IO_CLASSES: &[&str] = &["none", "realtime", "best-effort", "idle"];
+
+/// List the thread IDs of a process from /proc/<pid>/task (just the PID if unreadable).
+pub fn list_process_tids(pid: i32) -> Vec<i32> {
+    let mut tids: Vec<i32> = std::fs::read_dir(format!("/proc/{}/task", pid))
+        .map(|rd| rd.flatten().filter_map(|e| e.file_name().to_str().and_then(|s| s.parse::<i32>().ok())).collect())
+        .unwrap_or_default();
+    if tids.is_empty() { tids.push(pid); }
+    tids.sort_unstable();
+    tids
+}
+
+/// Targets for a scheduling change: every thread, or just the main thread.
+fn tune_targets(pid: i32, all_threads: bool) -> Vec<i32> {
+    if all_threads { list_process_tids(pid) } else { vec![pid] }
+}
+
+/// Apply `op` to every target. Threads that exit meanwhile (ESRCH) are skipped;
+/// the first other error is returned after all targets were tried.
+fn for_each_target(targets: &[i32], mut op: impl FnMut(i32) -> std::io::Result<()>) -> std::io::Result<()> {
+    let mut first_err: Option<std::io::Error> = None;
+    for (i, tid) in targets.iter().enumerate() {
+        match op(*tid) {
+            Ok(()) => {}
+            Err(e) if i > 0 && e.raw_os_error() == Some(3) => {}
+            Err(e) => { if first_err.is_none() { first_err = Some(e); } }
+        }
+    }
+    match first_err { Some(e) => Err(e), None => Ok(()) }
+}
+
+/// Set the nice value of a process (and optionally all of its threads).
+#[cfg(target_os = "linux")]
+pub fn set_process_nice(pid: i32, nice: i32, all_threads: bool) -> std::io::Result<()> {
+    if pid <= 0 { return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput)); }
+    for_each_target(&tune_targets(pid, all_threads), |tid| {
+        // PRIO_PROCESS = 0; on Linux this addresses a single thread by TID
+        let rc = unsafe { setpriority(0, tid as u32, nice) };
+        if rc == 0 { Ok(()) } else { Err(std::io::Error::last_os_error()) }
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_process_nice(_pid: i32, _nice: i32, _all_threads: bool) -> std::io::Result<()> { Err(std::io::Error::from(std::io::ErrorKind::Unsupported)) }
+
+/// Read the CPU affinity of a process as one flag per CPU (first `ncpus` CPUs).
+#[cfg(target_os = "linux")]
+pub fn get_process_affinity(pid: i32, ncpus: usize) -> std::io::Result<Vec<bool>> {
+    let mut mask = [0u64; MAX_AFFINITY_CPUS / 64];
+    let rc = unsafe { sched_getaffinity(pid, std::mem::size_of_val(&mask), mask.as_mut_ptr()) };
+    if rc != 0 { return Err(std::io::Error::last_os_error()); }
+    Ok((0..ncpus.min(MAX_AFFINITY_CPUS)).map(|i| mask[i / 64] & (1u64 << (i % 64)) != 0).collect())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn get_process_affinity(_pid: i32, _ncpus: usize) -> std::io::Result<Vec<bool>> { Err(std::io::Error::from(std::io::ErrorKind::Unsupported)) }
+
+/// Restrict a process (and optionally all of its threads) to the CPUs flagged in `cpus`.
+#[cfg(target_os = "linux")]
+pub fn set_process_affinity(pid: i32, cpus: &[bool], all_threads: bool) -> std::io::Result<()> {
+    if pid <= 0 || !cpus.iter().any(|c| *c) { return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput)); }
+    let mut mask = [0u64; MAX_AFFINITY_CPUS / 64];
+    for (i, on) in cpus.iter().enumerate().take(MAX_AFFINITY_CPUS) {
+        if *on { mask[i / 64] |= 1u64 << (i % 64); }
+    }
+    for_each_target(&tune_targets(pid, all_threads), |tid| {
+        let rc = unsafe { sched_setaffinity(tid, std::mem::size_of_val(&mask), mask.as_ptr()) };
+        if rc == 0 { Ok(()) } else { Err(std::io::Error::last_os_error()) }
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_process_affinity(_pid: i32, _cpus: &[bool], _all_threads: bool) -> std::io::Result<()> { Err(std::io::Error::from(std::io::ErrorKind::Unsupported)) }
+
+/// Read the I/O priority of a process as (class index into IO_CLASSES, level 0-7).
+#[cfg(target_os = "linux")]
+pub fn get_process_ioprio(pid: i32) -> std::io::Result<(u8, u8)> {
+    let Some((_, sys_get)) = SYS_IOPRIO else { return Err(std::io::Error::from(std::io::ErrorKind::Unsupported)); };
+    // IOPRIO_WHO_PROCESS = 1
+    let v = unsafe { syscall(sys_get, 1 as std::ffi::c_long, pid as std::ffi::c_long) };
+    if v < 0 { return Err(std::io::Error::last_os_error()); }
+    Ok((((v >> 13) & 0x3) as u8, (v & 0xff).min(7) as u8))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn get_process_ioprio(_pid: i32) -> std::io::Result<(u8, u8)> { Err(std::io::Error::from(std::io::ErrorKind::Unsupported)) }
+
+/// Set the I/O priority class and level of a process (and optionally all of its threads).
+#[cfg(target_os = "linux")]
+pub fn set_process_ioprio(pid: i32, class: u8, level: u8, all_threads: bool) -> std::io::Result<()> {
+    let Some((sys_set, _)) = SYS_IOPRIO else { return Err(std::io::Error::from(std::io::ErrorKind::Unsupported)); };
+    if pid <= 0 || class as usize >= IO_CLASSES.len() { return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput)); }
+    // Class "none" and "idle" take no level
+    let level = if class == 1 || class == 2 { level.min(7) } else { 0 };
+    let ioprio = ((class as std::ffi::c_long) << 13) | level as std::ffi::c_long;
+    for_each_target(&tune_targets(pid, all_threads), |tid| {
+        // Variadic args are passed as longs, which is what syscall(2) reads them as
+        let rc = unsafe { syscall(sys_set, 1 as std::ffi::c_long, tid as std::ffi::c_long, ioprio) };
+        if rc == 0 { Ok(()) } else { Err(std::io::Error::last_os_error()) }
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_process_ioprio(_pid: i32, _class: u8, _level: u8, _all_threads: bool) -> std::io::Result<()> { Err(std::io::Error::from(std::io::ErrorKind::Unsupported)) }
+
+/// Apply the same scheduling changes through sudo with `renice`, `taskset` or `ionice`.
+pub fn set_process_nice_sudo(pid: i32, nice: i32, all_threads: bool, password: &str) -> Result<(), String> {
+    let nice_arg = nice.to_string();
+    let tids: Vec<String> = tune_targets(pid, all_threads).iter().map(|t| t.to_string()).collect();
+    let mut args: Vec<&str> = vec!["renice", "-n", &nice_arg, "-p"];
+    args.extend(tids.iter().map(|t| t.as_str()));
+    super::sudo::run_with_sudo(&args, password).map(|_| ())
+}
+
+pub fn set_process_affinity_sudo(pid: i32, cpus: &[bool], all_threads: bool, password: &str) -> Result<(), String> {
+    let list: Vec<String> = cpus.iter().enumerate().filter(|(_, on)| **on).map(|(i, _)| i.to_string()).collect();
+    let list = list.join(",");
+    let pid_arg = pid.to_string();
+    let mut args: Vec<&str> = vec!["taskset"];
+    if all_threads { args.push("-a"); }
+    args.extend(["-p", "-c", &list, &pid_arg]);
+    super::sudo::run_with_sudo(&args, password).map(|_| ())
+}
+
+pub fn set_process_ioprio_sudo(pid: i32, class: u8, level: u8, all_threads: bool, password: &str) -> Result<(), String> {
+    let class_arg = class.to_string();
+    let level_arg = level.min(7).to_string();
+    let tids: Vec<String> = tune_targets(pid, all_threads).iter().map(|t| t.to_string()).collect();
+    let mut args: Vec<&str> = vec!["ionice", "-c", &class_arg];
+    if class == 1 || class == 2 { args.extend(["-n", &level_arg]); }
+    args.push("-p");
+    args.extend(tids.iter().map(|t| t.as_str()));
+    super::sudo::run_with_sudo(&args, password).map(|_| ())
+}
+
+// -------- Threads (top/htop thread view) --------
+/// One thread of a process, read from /proc/<pid>/task/<tid>/stat.
+#[derive(Debug, Clone)]
+pub struct ThreadInfo {
+    pub tid: i32,
+    pub name: String,
+    pub state: char,
+    pub cpu_ticks: u64, // utime + stime, in clock ticks
+    pub last_cpu: i32,  // CPU the thread last ran on
+}
+
+/// Read every thread of a process. Returns None once the process has exited.
+#[cfg(target_os = "linux")]
+pub fn read_process_threads(pid: i32) -> Option<Vec<ThreadInfo>> {
+    let rd = std::fs::read_dir(format!("/proc/{}/task", pid)).ok()?;
+    let mut out: Vec<ThreadInfo> = Vec::new();
+    for entry in rd.flatten() {
+        let Some(tid) = entry.file_name().to_str().and_then(|s| s.parse::<i32>().ok()) else { continue; };
+        // Threads can exit between listing and reading; just skip them
+        let Ok(s) = std::fs::read_to_string(entry.path().join("stat")) else { continue; };
+        let (Some(lparen), Some(rparen)) = (s.find('('), s.rfind(')')) else { continue; };
+        let name = s[lparen + 1..rparen].to_string();
+        let toks: Vec<&str> = s.get(rparen + 2..).unwrap_or("").split_whitespace().collect();
+        let num = |i: usize| toks.get(i).and_then(|x| x.parse::<u64>().ok()).unwrap_or(0);
+        out.push(ThreadInfo {
+            tid,
+            name,
+            state: toks.first().and_then(|t| t.chars().next()).unwrap_or('?'),
+            cpu_ticks: num(11) + num(12),
+            last_cpu: toks.get(36).and_then(|x| x.parse::<i32>().ok()).unwrap_or(-1),
+        });
+    }
+    out.sort_by_key(|t| t.tid);
+    Some(out)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_process_threads(_pid: i32) -> Option<Vec<ThreadInfo>> { None }
+
+#[cfg(unix)]
+unsafe extern "C" { fn sysconf(name: i32) -> std::ffi::c_long; }
+
+/// Clock ticks per second used by /proc CPU times (sysconf(_SC_CLK_TCK), usually 100).
+#[cfg(unix)]
+pub fn clock_ticks_per_sec() -> f64 {
+    // _SC_CLK_TCK is 2 on Linux
+    let v = unsafe { sysconf(2) };
+    if v > 0 { v as f64 } else { 100.0 }
+}
+
+#[cfg(not(unix))]
+pub fn clock_ticks_per_sec() -> f64 { 100.0 }
+
+// -------- Process details tabs (top/htop details popup) --------
+/// Tabs of the process details popup, in display order.
+pub const PROCESS_DETAIL_TABS: &[&str] = &["Overview", "Files", "Sockets", "Memory", "Environment", "Limits", "Cgroup", "Namespaces"];
+
+/// Body text of one details tab for a process (index into PROCESS_DETAIL_TABS).
+#[cfg(target_os = "linux")]
+pub fn get_process_detail_tab(pid: i32, tab: usize) -> String {
+    if std::fs::metadata(format!("/proc/{}", pid)).is_err() { return format!("Process {} has exited.", pid); }
+    match tab {
+        1 => get_process_fds(pid),
+        2 => get_process_sockets(pid),
+        3 => get_process_memory_maps(pid),
+        4 => get_process_environ(pid),
+        5 => read_proc_text(pid, "limits"),
+        6 => read_proc_text(pid, "cgroup"),
+        7 => get_process_namespaces(pid),
+        _ => get_process_details(pid),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn get_process_detail_tab(pid: i32, _tab: usize) -> String { get_process_details(pid) }
+
+/// Explain a failed /proc read; other users' fd/environ/maps need root.
+#[cfg(target_os = "linux")]
+fn proc_read_error(what: &str, e: &std::io::Error) -> String {
+    if e.kind() == std::io::ErrorKind::PermissionDenied {
+        format!("({}: permission denied; run rtop as root to inspect other users' processes)", what)
+    } else {
+        format!("({} not available: {})", what, e)
+    }
+}
+
+/// A /proc/<pid>/<name> file verbatim.
+#[cfg(target_os = "linux")]
+fn read_proc_text(pid: i32, name: &str) -> String {
+    match std::fs::read_to_string(format!("/proc/{}/{}", pid, name)) {
+        Ok(s) if s.trim().is_empty() => format!("({} is empty)", name),
+        Ok(s) => s.trim_end().to_string(),
+        Err(e) => proc_read_error(name, &e),
+    }
+}
+
+/// Open file descriptors (fd number -> resolved target), sorted by fd.
+#[cfg(target_os = "linux")]
+fn read_process_fd_links(pid: i32) -> std::io::Result<Vec<(i32, String)>> {
+    let mut fds: Vec<(i32, String)> = Vec::new();
+    for e in std::fs::read_dir(format!("/proc/{}/fd", pid))?.flatten() {
+        let Some(fd) = e.file_name().to_str().and_then(|s| s.parse::<i32>().ok()) else { continue; };
+        let target = std::fs::read_link(e.path()).map(|p| p.to_string_lossy().into_owned()).unwrap_or_else(|_| String::from("?"));
+        fds.push((fd, target));
+    }
+    fds.sort_by_key(|(fd, _)| *fd);
+    Ok(fds)
+}
+
+#[cfg(target_os = "linux")]
+fn get_process_fds(pid: i32) -> String {
+    match read_process_fd_links(pid) {
+        Ok(fds) if fds.is_empty() => String::from("(no open file descriptors)"),
+        Ok(fds) => {
+            let mut out = format!("{} open file descriptors\n\n", fds.len());
+            for (fd, target) in fds { out.push_str(&format!("{:>5}  {}\n", fd, target)); }
+            out.trim_end().to_string()
+        }
+        Err(e) => proc_read_error("file descriptors", &e),
+    }
+}
+
+/// Decode a hex "ADDR:PORT" pair from /proc/net/{tcp,udp}[6] (addresses are in host byte order words).
+#[cfg(target_os = "linux")]
+fn decode_proc_net_addr(s: &str) -> String {
+    let Some((addr, port)) = s.split_once(':') else { return s.to_string(); };
+    let port = u16::from_str_radix(port, 16).unwrap_or(0);
+    let words: Vec<u32> = (0..addr.len() / 8).filter_map(|i| u32::from_str_radix(&addr[i * 8..i * 8 + 8], 16).ok()).collect();
+    match words.len() {
+        1 => format!("{}:{}", std::net::Ipv4Addr::from(words[0].to_ne_bytes()), port),
+        4 => {
+            let mut bytes = [0u8; 16];
+            for (i, w) in words.iter().enumerate() { bytes[i * 4..i * 4 + 4].copy_from_slice(&w.to_ne_bytes()); }
+            format!("[{}]:{}", std::net::Ipv6Addr::from(bytes), port)
+        }
+        _ => s.to_string(),
+    }
+}
+
+/// (inode, type, path) of a /proc/net/unix line. The path is the rest of the line after the
+/// inode column, so names with spaces are kept whole; unbound sockets show "(unnamed)".
+#[cfg(target_os = "linux")]
+fn parse_unix_socket_line(line: &str) -> Option<(u64, &'static str, &str)> {
+    let mut rest = line.trim_start();
+    let mut cols: Vec<&str> = Vec::new();
+    while cols.len() < 7 && !rest.is_empty() {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        cols.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    let ino = cols.get(6)?.parse::<u64>().ok()?;
+    let kind = match cols.get(4).cloned().unwrap_or("") { "0001" => "STREAM", "0002" => "DGRAM", "0005" => "SEQPACKET", _ => "?" };
+    Some((ino, kind, if rest.is_empty() { "(unnamed)" } else { rest }))
+}
+
+/// TCP state names indexed by the hex "st" column of /proc/net/tcp.
+#[cfg(target_os = "linux")]
+fn tcp_state_name(st: &str) -> &'static str {
+    match u8::from_str_radix(st, 16).unwrap_or(0) {
+        0x01 => "ESTABLISHED",
+        0x02 => "SYN_SENT",
+        0x03 => "SYN_RECV",
+        0x04 => "FIN_WAIT1",
+        0x05 => "FIN_WAIT2",
+        0x06 => "TIME_WAIT",
+        0x07 => "CLOSE",
+        0x08 => "CLOSE_WAIT",
+        0x09 => "LAST_ACK",
+        0x0A => "LISTEN",
+        0x0B => "CLOSING",
+        _ => "?",
+    }
+}
+
+/// TCP/UDP/Unix sockets owned by the process: socket inodes from its fds, matched
+/// against the tables in /proc/<pid>/net (the process's own network namespace).
+#[cfg(target_os = "linux")]
+fn get_process_sockets(pid: i32) -> String {
+    let fds = match read_process_fd_links(pid) { Ok(f) => f, Err(e) => return proc_read_error("sockets", &e) };
+    let mut inodes: std::collections::HashMap<u64, Vec<i32>> = std::collections::HashMap::new();
+    for (fd, target) in &fds {
+        if let Some(ino) = target.strip_prefix("socket:[").and_then(|s| s.strip_suffix(']')).and_then(|s| s.parse::<u64>().ok()) {
+            inodes.entry(ino).or_default().push(*fd);
+        }
+    }
+    if inodes.is_empty() { return String::from("(no sockets)"); }
+    let fd_list = |ino: u64| inodes.get(&ino).map(|v| v.iter().map(|fd| fd.to_string()).collect::<Vec<_>>().join(",")).unwrap_or_default();
+    let mut rows: Vec<String> = Vec::new();
+    let mut seen: std::collections::HashSet<u64> = std::collections::HashSet::new();
+    for proto in ["tcp", "tcp6", "udp", "udp6"] {
+        let Ok(s) = std::fs::read_to_string(format!("/proc/{}/net/{}", pid, proto)) else { continue; };
+        for line in s.lines().skip(1) {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            let Some(ino) = cols.get(9).and_then(|x| x.parse::<u64>().ok()) else { continue; };
+            if !inodes.contains_key(&ino) || !seen.insert(ino) { continue; }
+            let local = decode_proc_net_addr(cols.get(1).cloned().unwrap_or(""));
+            let remote = decode_proc_net_addr(cols.get(2).cloned().unwrap_or(""));
+            let st = cols.get(3).cloned().unwrap_or("");
+            let state = if proto.starts_with("tcp") { tcp_state_name(st) } else if st == "01" { "ESTABLISHED" } else { "UNCONN" };
+            rows.push(format!("{:<5} {:<30} {:<30} {:<11} fd {}", proto, local, remote, state, fd_list(ino)));
+        }
+    }
+    if let Ok(s) = std::fs::read_to_string(format!("/proc/{}/net/unix", pid)) {
+        for line in s.lines().skip(1) {
+            let Some((ino, kind, path)) = parse_unix_socket_line(line) else { continue; };
+            if !inodes.contains_key(&ino) || !seen.insert(ino) { continue; }
+            rows.push(format!("{:<5} {:<30} {:<30} {:<11} fd {}", "unix", path, "", kind, fd_list(ino)));
+        }
+    }
+    // Netlink, packet and other socket families are listed by inode only
+    let mut other: Vec<u64> = inodes.keys().filter(|i| !seen.contains(i)).cloned().collect();
+    other.sort_unstable();
+    for ino in other { rows.push(format!("{:<5} {:<30} {:<30} {:<11} fd {}", "other", format!("inode {}", ino), "", "", fd_list(ino))); }
+    let mut out = format!("{} sockets\n\n{:<5} {:<30} {:<30} {:<11} {}\n", inodes.len(), "PROTO", "LOCAL", "REMOTE", "STATE", "FDS");
+    out.push_str(&rows.join("\n"));
+    out
+}
+
+/// Memory summary from /proc/<pid>/smaps_rollup plus the number of mappings.
+#[cfg(target_os = "linux")]
+fn get_process_memory_maps(pid: i32) -> String {
+    let rollup = match std::fs::read_to_string(format!("/proc/{}/smaps_rollup", pid)) { Ok(s) => s, Err(e) => return proc_read_error("smaps_rollup", &e) };
+    let mut out = String::new();
+    if let Ok(maps) = std::fs::read_to_string(format!("/proc/{}/maps", pid)) { out.push_str(&format!("Mappings: {}\n\n", maps.lines().count())); }
+    // Skip the address-range header line; align "Key: value kB" pairs
+    for line in rollup.lines().filter(|l| l.contains(':') && !l.contains('-')) {
+        if let Some((k, v)) = line.split_once(':') { out.push_str(&format!("{:<16} {:>14}\n", format!("{}:", k), v.trim())); }
+    }
+    if out.is_empty() { String::from("(smaps_rollup is empty; kernel thread?)") } else { out.trim_end().to_string() }
+}
+
+/// Environment variables from /proc/<pid>/environ, sorted.
+#[cfg(target_os = "linux")]
+fn get_process_environ(pid: i32) -> String {
+    match std::fs::read(format!("/proc/{}/environ", pid)) {
+        Ok(raw) => {
+            let mut vars: Vec<String> = raw.split(|b| *b == 0).filter(|s| !s.is_empty()).map(|s| String::from_utf8_lossy(s).into_owned()).collect();
+            if vars.is_empty() { return String::from("(empty environment)"); }
+            vars.sort();
+            vars.join("\n")
+        }
+        Err(e) => proc_read_error("environ", &e),
+    }
+}
+
+/// Namespaces from /proc/<pid>/ns, marking those that differ from rtop's own.
+#[cfg(target_os = "linux")]
+fn get_process_namespaces(pid: i32) -> String {
+    let rd = match std::fs::read_dir(format!("/proc/{}/ns", pid)) { Ok(rd) => rd, Err(e) => return proc_read_error("namespaces", &e) };
+    let mut names: Vec<String> = rd.flatten().map(|e| e.file_name().to_string_lossy().into_owned()).collect();
+    names.sort();
+    let mut out = String::new();
+    for name in names {
+        let target = std::fs::read_link(format!("/proc/{}/ns/{}", pid, name)).map(|p| p.to_string_lossy().into_owned());
+        let own = std::fs::read_link(format!("/proc/self/ns/{}", name)).map(|p| p.to_string_lossy().into_owned()).ok();
+        match target {
+            Ok(t) => {
+                let mark = if own.as_deref() == Some(t.as_str()) { "" } else { "  (differs from rtop)" };
+                out.push_str(&format!("{:<18} {}{}\n", name, t, mark));
+            }
+            Err(e) => out.push_str(&format!("{:<18} {}\n", name, proc_read_error(&name, &e))),
+        }
+    }
+    if out.is_empty() { String::from("(no namespaces)") } else { out.trim_end().to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (pid, prefix) of each row, for comparing tree shapes.
+    fn shape(rows: &[ProcTreeRow]) -> Vec<(i32, &str)> {
+        rows.iter().map(|r| (r.pid, r.prefix.as_str())).collect()
+    }
+
+    #[test]
+    fn process_tree_nests_and_rolls_up_collapsed_branches() {
+        let procs = [(1, None, 1.0, 100), (2, Some(1), 2.0, 200), (3, Some(2), 4.0, 400), (4, Some(1), 8.0, 800)];
+        let rows = build_process_tree(&procs, &Default::default());
+        assert_eq!(shape(&rows), [(1, "- "), (2, "├─ - "), (3, "│  └─ "), (4, "└─ ")]);
+        assert_eq!((rows[0].subtree_cpu, rows[0].subtree_mem), (15.0, 1500));
+        assert_eq!((rows[1].subtree_cpu, rows[1].subtree_mem), (6.0, 600));
+        // Collapsing 2 hides 3 and keeps the totals on the collapsed row
+        let rows = build_process_tree(&procs, &[2].into());
+        assert_eq!(shape(&rows), [(1, "- "), (2, "├─ + "), (4, "└─ ")]);
+        assert!(rows[1].collapsed && rows[1].has_children);
+        assert_eq!((rows[1].subtree_cpu, rows[1].subtree_mem), (6.0, 600));
+        // Collapsing a leaf changes nothing
+        assert_eq!(shape(&build_process_tree(&procs, &[3].into())), [(1, "- "), (2, "├─ - "), (3, "│  └─ "), (4, "└─ ")]);
+    }
+
+    #[test]
+    fn process_tree_orphans_become_roots() {
+        // 5's parent is gone (or was not readable), 6 names itself: both are roots, in order
+        let procs = [(5, Some(99), 1.0, 10), (1, None, 1.0, 10), (6, Some(6), 1.0, 10), (7, Some(5), 1.0, 10)];
+        let rows = build_process_tree(&procs, &Default::default());
+        assert_eq!(shape(&rows), [(5, "- "), (7, "└─ "), (1, ""), (6, "")]);
+        assert_eq!(rows[0].subtree_mem, 20);
+    }
+
+    #[test]
+    fn process_tree_breaks_ppid_cycles() {
+        // 2 -> 3 -> 4 -> 2 (PPids read at different moments); 5 hangs below the cycle
+        let procs = [(1, None, 1.0, 1), (3, Some(4), 1.0, 1), (2, Some(3), 1.0, 1), (4, Some(2), 1.0, 1), (5, Some(4), 1.0, 1)];
+        let rows = build_process_tree(&procs, &Default::default());
+        // Every process shows up once; the cycle is cut above its first process (3)
+        let mut pids: Vec<i32> = rows.iter().map(|r| r.pid).collect();
+        pids.sort_unstable();
+        assert_eq!(pids, [1, 2, 3, 4, 5]);
+        assert_eq!(shape(&rows), [(1, ""), (3, "- "), (2, "└─ - "), (4, "   └─ - "), (5, "      └─ ")]);
+        assert_eq!(rows[1].subtree_mem, 4);
+    }
+
+    #[test]
+    fn proc_filter_parses_prefixes() {
+        assert!(matches!(ProcFilter::parse("  "), Ok(None)));
+        assert!(matches!(ProcFilter::parse("pid: 42"), Ok(Some(ProcFilter::Pid(42)))));
+        assert!(matches!(ProcFilter::parse("user:root"), Ok(Some(ProcFilter::User(ref u))) if u == "root"));
+        assert!(matches!(ProcFilter::parse("re:^ssh"), Ok(Some(ProcFilter::Regex(_)))));
+        assert!(matches!(ProcFilter::parse(" Firefox "), Ok(Some(ProcFilter::Substring(ref s))) if s == "firefox"));
+        assert_eq!(ProcFilter::parse("pid:abc").err().as_deref(), Some("Invalid PID: abc"));
+        assert!(ProcFilter::parse("re:(").err().is_some_and(|e| e.starts_with("Invalid regex: ")));
+    }
+
+    #[test]
+    fn proc_filter_matches() {
+        let filter = |input: &str| ProcFilter::parse(input).unwrap().unwrap();
+        // Substrings ignore case, regexes do not
+        assert!(filter("BASH").matches(1, "/usr/bin/bash -l", "root"));
+        assert!(!filter("zsh").matches(1, "/usr/bin/bash -l", "root"));
+        assert!(filter("re:bash -[lc]$").matches(1, "/usr/bin/bash -l", "root"));
+        assert!(!filter("re:BASH").matches(1, "/usr/bin/bash -l", "root"));
+        // user: compares the whole name, pid: the whole PID
+        assert!(filter("user:root").matches(1, "init", "root"));
+        assert!(!filter("user:roo").matches(1, "init", "root"));
+        assert!(filter("pid:12").matches(12, "init", "root"));
+        assert!(!filter("pid:12").matches(123, "init", "root"));
+        assert!(filter("user:x").needs_user() && !filter("x").needs_user());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn decode_proc_net_addr_ipv4_and_ipv6() {
+        // Words are printed as the host stores them: these match a little-endian /proc
+        if cfg!(target_endian = "little") {
+            assert_eq!(decode_proc_net_addr("0100007F:0016"), "127.0.0.1:22");
+            assert_eq!(decode_proc_net_addr("0000000000000000FFFF00000100007F:01BB"), "[::ffff:127.0.0.1]:443");
+            assert_eq!(decode_proc_net_addr("000080FE00000000FF005452B7E7F2FE:1F90"), "[fe80::5254:ff:fef2:e7b7]:8080");
+        }
+        assert_eq!(decode_proc_net_addr("00000000:0000"), "0.0.0.0:0");
+        assert_eq!(decode_proc_net_addr("00000000000000000000000000000000:0035"), "[::]:53");
+        // Not an address: shown as is
+        assert_eq!(decode_proc_net_addr("garbage"), "garbage");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn unix_socket_paths_keep_spaces() {
+        let line = "0000000000000000: 00000002 00000000 00010000 0001 01    23456 /run/user/1000/my app/sock";
+        assert_eq!(parse_unix_socket_line(line), Some((23456, "STREAM", "/run/user/1000/my app/sock")));
+        let unnamed = "0000000000000000: 00000003 00000000 00000000 0002 03 789";
+        assert_eq!(parse_unix_socket_line(unnamed), Some((789, "DGRAM", "(unnamed)")));
+        assert_eq!(parse_unix_socket_line("Num       RefCount Protocol Flags    Type St Inode Path"), None);
+    }
+}
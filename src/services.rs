@@ -1,50 +1,470 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(target_os = "linux")]
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
-// Fetch all services in a simple parsed form (Linux)
-#[cfg(target_os = "linux")]
-pub fn get_all_services() -> Vec<(String, String, String)> {
-    let mut services: Vec<(String, String, String)> = Vec::new();
-    // Use systemctl list-units --type=service --all --no-legend --no-pager
-    if let Ok(out) = Command::new("systemctl")
-        .args(["list-units", "--type=service", "--all", "--no-legend", "--no-pager"]) 
-        .output() {
-        if out.status.success() {
-            if let Ok(s) = String::from_utf8(out.stdout) {
-                for line in s.lines() {
-                    let l = line.trim();
-                    if l.is_empty() { continue; }
-                    // Typical columns: UNIT LOAD ACTIVE SUB DESCRIPTION
-                    // We only need UNIT, ACTIVE, DESCRIPTION (join remainder)
-                    let toks: Vec<&str> = l.split_whitespace().collect();
-                    if toks.len() >= 5 {
-                        let unit = toks[0].to_string();
-                        let active = toks[2].to_string();
-                        let desc = toks[4..].join(" ");
-                        services.push((unit, active, desc));
-                    }
-                }
-            }
+use crate::collector::Collector;
+#[cfg(target_os = "linux")]
+use crate::dbus::{Connection, Value};
+use crate::processes::format_local_datetime;
+use crate::sudo::run_with_sudo;
+
+// -------- Services (SystemD) helpers --------
+/// Unit types listed in the Services tab, in the order `t` cycles through them.
+pub const UNIT_TYPES: &[&str] = &["service", "timer", "socket", "mount", "path", "target"];
+
+/// One systemd unit as listed by ListUnits or `systemctl list-units`.
+#[derive(Debug, Clone)]
+pub struct UnitInfo {
+    pub name: String,
+    pub load: String,
+    pub active: String,
+    pub sub: String,
+    pub description: String,
+    pub file_state: String, // enablement from the unit file: enabled, disabled, static, ... ("" if unknown)
+    pub next_elapse: Option<u64>,  // timers: next trigger, realtime usec
+    pub last_trigger: Option<u64>, // timers: last trigger, realtime usec
+    pub main_pid: Option<i32>,     // services: MainPID reported by systemd
+}
+
+impl UnitInfo {
+    /// Unit type suffix ("service", "timer", ...).
+    pub fn unit_type(&self) -> &str { self.name.rsplit('.').next().unwrap_or("") }
+
+    /// Services that may have a main process (not inactive or failed).
+    fn may_have_main_pid(&self) -> bool { self.unit_type() == "service" && !matches!(self.active.as_str(), "inactive" | "failed") }
+}
+
+/// `systemctl`, talking to the per-user manager when `user` is set.
+fn systemctl(user: bool) -> Command {
+    let mut cmd = Command::new("systemctl");
+    if user { cmd.arg("--user"); }
+    cmd
+}
+
+// All units of the listed types via systemctl (fallback when the bus is unavailable)
+#[cfg(target_os = "linux")]
+pub fn list_units_systemctl(user: bool) -> Vec<UnitInfo> {
+    let types = format!("--type={}", UNIT_TYPES.join(","));
+    // --plain drops the "●" marker systemctl puts in front of failed units
+    let output = systemctl(user)
+        .args(["list-units", &types, "--all", "--no-legend", "--no-pager", "--plain"])
+        .output();
+    let mut units: Vec<UnitInfo> = Vec::new();
+    if let Ok(out) = output && out.status.success() {
+        let text = String::from_utf8_lossy(&out.stdout);
+        for line in text.lines() {
+            let l = line.trim().trim_start_matches('●').trim_start();
+            if l.is_empty() { continue; }
+            // Expected columns: UNIT LOAD ACTIVE SUB DESCRIPTION (description may contain spaces)
+            let mut parts = l.split_whitespace();
+            let (Some(name), Some(load), Some(active), Some(sub)) = (parts.next(), parts.next(), parts.next(), parts.next()) else { continue; };
+            let description = parts.collect::<Vec<_>>().join(" ");
+            units.push(UnitInfo { name: name.to_string(), load: load.to_string(), active: active.to_string(), sub: sub.to_string(), description, file_state: String::new(), next_elapse: None, last_trigger: None, main_pid: None });
+        }
+    }
+    // Enablement state: UNIT FILE STATE [PRESET]
+    let files = systemctl(user)
+        .args(["list-unit-files", &types, "--no-legend", "--no-pager"])
+        .output();
+    if let Ok(out) = files && out.status.success() {
+        let states: HashMap<String, String> = String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .filter_map(|l| { let mut p = l.split_whitespace(); Some((p.next()?.to_string(), p.next()?.to_string())) })
+            .collect();
+        for u in units.iter_mut() { u.file_state = states.get(&u.name).cloned().unwrap_or_default(); }
+    }
+    // Timer trigger times and service main PIDs; --timestamp=unix prints "@<secs>" (systemd 248+,
+    // otherwise left empty)
+    let shown: Vec<String> = units.iter().filter(|u| u.unit_type() == "timer" || u.may_have_main_pid()).map(|u| u.name.clone()).collect();
+    if !shown.is_empty() {
+        let out = systemctl(user)
+            .args(["show", "--timestamp=unix", "-p", "Id", "-p", "NextElapseUSecRealtime", "-p", "LastTriggerUSec", "-p", "MainPID", "--"])
+            .args(&shown)
+            .output();
+        if let Ok(out) = out && out.status.success() {
+            apply_systemctl_show(&mut units, &String::from_utf8_lossy(&out.stdout));
         }
     }
     // Sort by unit name for stable display
-    services.sort_by(|a, b| a.0.cmp(&b.0));
-    services
+    units.sort_by(|a, b| a.name.cmp(&b.name));
+    units
+}
+
+/// Fill timer trigger times and main PIDs from `systemctl show` output: one blank-line separated
+/// block of `Key=value` lines per unit, matched to `units` by `Id`. Timestamps that are not
+/// "@<secs>" (never, or a systemd without --timestamp=unix) are left empty.
+#[cfg(target_os = "linux")]
+fn apply_systemctl_show(units: &mut [UnitInfo], text: &str) {
+    for block in text.split("\n\n") {
+        let get = |key: &str| block.lines().find_map(|l| l.strip_prefix(key)?.strip_prefix('='));
+        let stamp = |key: &str| get(key)?.strip_prefix('@')?.trim().parse::<u64>().ok().map(|s| s * 1_000_000);
+        let Some(u) = get("Id").and_then(|id| units.iter_mut().find(|u| u.name == id)) else { continue; };
+        u.next_elapse = stamp("NextElapseUSecRealtime");
+        u.last_trigger = stamp("LastTriggerUSec");
+        u.main_pid = get("MainPID").and_then(|v| v.trim().parse::<i32>().ok()).filter(|p| *p > 0);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn list_units_systemctl(_user: bool) -> Vec<UnitInfo> { Vec::new() }
+
+// -------- systemd over D-Bus (org.freedesktop.systemd1) --------
+#[cfg(target_os = "linux")]
+const SYSTEMD_DEST: &str = "org.freedesktop.systemd1";
+#[cfg(target_os = "linux")]
+const SYSTEMD_PATH: &str = "/org/freedesktop/systemd1";
+#[cfg(target_os = "linux")]
+const SYSTEMD_MANAGER: &str = "org.freedesktop.systemd1.Manager";
+#[cfg(target_os = "linux")]
+const DBUS_PROPERTIES: &str = "org.freedesktop.DBus.Properties";
+
+/// Bus of the system manager, or of the calling user's manager (session bus) when `user` is set.
+#[cfg(target_os = "linux")]
+fn manager_bus(user: bool) -> std::io::Result<Connection> {
+    if user { Connection::session() } else { Connection::system() }
+}
+
+/// Type-specific D-Bus interface of a unit, if it has one (targets do not).
+#[cfg(target_os = "linux")]
+fn unit_type_interface(unit_type: &str) -> Option<&'static str> {
+    match unit_type {
+        "service" => Some("org.freedesktop.systemd1.Service"),
+        "timer" => Some("org.freedesktop.systemd1.Timer"),
+        "socket" => Some("org.freedesktop.systemd1.Socket"),
+        "mount" => Some("org.freedesktop.systemd1.Mount"),
+        "path" => Some("org.freedesktop.systemd1.Path"),
+        _ => None,
+    }
+}
+
+/// All properties of `path` on the given interfaces (missing interfaces are skipped).
+#[cfg(target_os = "linux")]
+fn get_all_properties(conn: &mut Connection, path: &str, ifaces: &[&str]) -> Vec<(String, Value)> {
+    let mut all: Vec<(String, Value)> = Vec::new();
+    for iface in ifaces {
+        let Ok(props) = conn.call(SYSTEMD_DEST, path, DBUS_PROPERTIES, "GetAll", &[iface]) else { continue; };
+        for entry in props.first().map(|v| v.items()).unwrap_or(&[]) {
+            if let Value::DictEntry(k, v) = entry { all.push((k.as_str().unwrap_or("").to_string(), (**v).clone())); }
+        }
+    }
+    all
+}
+
+/// Next elapse of a timer in realtime usec. Monotonic timers (OnBootSec=, OnUnitActiveSec=)
+/// only report time since boot, which is converted using /proc/uptime.
+#[cfg(target_os = "linux")]
+fn timer_next_elapse(realtime: u64, monotonic: u64) -> Option<u64> {
+    let realtime = (realtime != 0 && realtime != u64::MAX).then_some(realtime);
+    let monotonic = (monotonic != 0 && monotonic != u64::MAX).then(|| {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).ok()?.as_micros() as u64;
+        let uptime = std::fs::read_to_string("/proc/uptime").ok()?.split_whitespace().next()?.parse::<f64>().ok()?;
+        Some(now.saturating_sub((uptime * 1e6) as u64).saturating_add(monotonic))
+    }).flatten();
+    match (realtime, monotonic) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// All loaded units of the listed types from Manager.ListUnits, sorted by name.
+#[cfg(target_os = "linux")]
+pub fn list_units_dbus(conn: &mut Connection) -> std::io::Result<Vec<UnitInfo>> {
+    let reply = conn.call(SYSTEMD_DEST, SYSTEMD_PATH, SYSTEMD_MANAGER, "ListUnits", &[])?;
+    let mut units: Vec<UnitInfo> = Vec::new();
+    let mut timer_paths: Vec<(usize, String)> = Vec::new();
+    let mut service_paths: Vec<(usize, String)> = Vec::new();
+    // a(ssssssouso): name, description, load, active, sub, following, path, job id, job type, job path
+    for u in reply.first().map(|v| v.items()).unwrap_or(&[]) {
+        let f = u.items();
+        let field = |i: usize| f.get(i).and_then(|v| v.as_str()).unwrap_or("").to_string();
+        let info = UnitInfo { name: field(0), description: field(1), load: field(2), active: field(3), sub: field(4), file_state: String::new(), next_elapse: None, last_trigger: None, main_pid: None };
+        if !UNIT_TYPES.contains(&info.unit_type()) { continue; }
+        if info.unit_type() == "timer" { timer_paths.push((units.len(), field(6))); }
+        if info.may_have_main_pid() { service_paths.push((units.len(), field(6))); }
+        units.push(info);
+    }
+    // Enablement state from ListUnitFiles: a(ss) of unit file path and state
+    let files = conn.call(SYSTEMD_DEST, SYSTEMD_PATH, SYSTEMD_MANAGER, "ListUnitFiles", &[])?;
+    let mut states: HashMap<String, String> = HashMap::new();
+    for f in files.first().map(|v| v.items()).unwrap_or(&[]) {
+        let f = f.items();
+        let (Some(path), Some(state)) = (f.first().and_then(|v| v.as_str()), f.get(1).and_then(|v| v.as_str())) else { continue; };
+        let name = path.rsplit('/').next().unwrap_or(path);
+        states.insert(name.to_string(), state.to_string());
+    }
+    for u in units.iter_mut() { u.file_state = states.get(&u.name).cloned().unwrap_or_default(); }
+    // Timer trigger times from each timer's Timer interface
+    for (i, path) in timer_paths {
+        let props = get_all_properties(conn, &path, &["org.freedesktop.systemd1.Timer"]);
+        let get = |key: &str| props.iter().find(|(k, _)| k == key).and_then(|(_, v)| v.as_u64()).unwrap_or(0);
+        units[i].next_elapse = timer_next_elapse(get("NextElapseUSecRealtime"), get("NextElapseUSecMonotonic"));
+        units[i].last_trigger = Some(get("LastTriggerUSec")).filter(|t| *t != 0);
+    }
+    // Main process of each running service
+    for (i, path) in service_paths {
+        let Ok(reply) = conn.call(SYSTEMD_DEST, &path, DBUS_PROPERTIES, "Get", &["org.freedesktop.systemd1.Service", "MainPID"]) else { continue; };
+        units[i].main_pid = reply.first().and_then(|v| v.as_u64()).filter(|p| *p != 0).map(|p| p as i32);
+    }
+    units.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(units)
+}
+
+/// Unit properties worth showing in the details popup, via LoadUnit + GetAll on the Unit
+/// interface and the type-specific one.
+#[cfg(target_os = "linux")]
+fn get_unit_properties(conn: &mut Connection, unit: &str) -> std::io::Result<Vec<(String, String)>> {
+    let reply = conn.call(SYSTEMD_DEST, SYSTEMD_PATH, SYSTEMD_MANAGER, "LoadUnit", &[unit])?;
+    let path = reply.first().and_then(|v| v.as_str()).unwrap_or("").to_string();
+    let mut ifaces = vec!["org.freedesktop.systemd1.Unit"];
+    ifaces.extend(unit_type_interface(unit.rsplit('.').next().unwrap_or("")));
+    let all = get_all_properties(conn, &path, &ifaces);
+    const SHOWN: &[&str] = &[
+        "Id", "Description", "LoadState", "ActiveState", "SubState", "UnitFileState", "FragmentPath",
+        "ActiveEnterTimestamp", "InactiveEnterTimestamp", "MainPID", "ExecMainStatus", "Result", "NRestarts",
+        "MemoryCurrent", "TasksCurrent", "CPUUsageNSec",
+        // timers and paths: activated unit and trigger times; sockets and mounts: what they bind
+        "Unit", "NextElapseUSecRealtime", "LastTriggerUSec", "Listen", "What", "Where", "Triggers",
+    ];
+    let mut out: Vec<(String, String)> = Vec::new();
+    for key in SHOWN {
+        let Some((_, v)) = all.iter().find(|(k, _)| k == key) else { continue; };
+        let n = v.as_u64();
+        // systemd reports "unset" counters as u64::MAX and unset timestamps as 0
+        if n == Some(u64::MAX) { continue; }
+        let text = match *key {
+            k if k.ends_with("Timestamp") || k == "NextElapseUSecRealtime" || k == "LastTriggerUSec" => match n { Some(0) | None => continue, Some(us) => format_local_datetime(us / 1_000_000) },
+            "MemoryCurrent" => crate::fmt_bytes(n.unwrap_or(0)),
+            "CPUUsageNSec" => format!("{:.3}s", n.unwrap_or(0) as f64 / 1e9),
+            "MainPID" if n == Some(0) => continue,
+            _ => v.display(),
+        };
+        if !text.is_empty() { out.push((key.to_string(), text)); }
+    }
+    Ok(out)
+}
+
+/// Unit list source for one manager (system, or the current user's): the systemd D-Bus API
+/// when the bus is reachable (reloaded when systemd signals a change), otherwise
+/// `systemctl list-units` on a timer.
+#[cfg(target_os = "linux")]
+pub struct ServiceBackend {
+    user: bool,
+    conn: Option<Connection>,
+    changed: Arc<AtomicBool>,   // set by the signal watcher thread
+    bus_lost: Arc<AtomicBool>,  // watcher connection failed; fall back to systemctl
+    stop: Arc<AtomicBool>,      // backend dropped; the watcher exits within WATCH_POLL
+    watcher: Option<JoinHandle<()>>,
+    last_load: Option<Instant>,
+}
+
+/// Safety-net reload interval when using D-Bus (signals normally trigger reloads).
+#[cfg(target_os = "linux")]
+const DBUS_RELOAD_INTERVAL: Duration = Duration::from_secs(30);
+/// Reload interval for the systemctl fallback.
+#[cfg(target_os = "linux")]
+const SYSTEMCTL_RELOAD_INTERVAL: Duration = Duration::from_secs(2);
+/// How long the watcher waits for a signal before checking whether it should stop.
+#[cfg(target_os = "linux")]
+const WATCH_POLL: Duration = Duration::from_millis(500);
+
+#[cfg(target_os = "linux")]
+impl ServiceBackend {
+    pub fn new(user: bool) -> Self {
+        let changed = Arc::new(AtomicBool::new(true));
+        let bus_lost = Arc::new(AtomicBool::new(false));
+        let stop = Arc::new(AtomicBool::new(false));
+        let conn = manager_bus(user).ok();
+        let watcher = conn.is_some().then(|| spawn_unit_watcher(user, changed.clone(), bus_lost.clone(), stop.clone()));
+        ServiceBackend { user, conn, changed, bus_lost, stop, watcher, last_load: None }
+    }
+
+    /// Where the list currently comes from, for the Services header.
+    pub fn source(&self) -> &'static str { if self.conn.is_some() { "D-Bus" } else { "systemctl" } }
+
+    /// Reload the unit list if something changed, it is due, or `force` is set.
+    /// Returns the new list when reloaded.
+    pub fn poll(&mut self, force: bool) -> Option<Vec<UnitInfo>> {
+        if self.bus_lost.load(Ordering::Relaxed) { self.conn = None; }
+        let interval = if self.conn.is_some() { DBUS_RELOAD_INTERVAL } else { SYSTEMCTL_RELOAD_INTERVAL };
+        let due = self.last_load.is_none_or(|t| t.elapsed() >= interval);
+        let changed = self.conn.is_some() && self.changed.swap(false, Ordering::Relaxed);
+        if !(force || due || changed) { return None; }
+        self.last_load = Some(Instant::now());
+        if let Some(conn) = self.conn.as_mut() {
+            match list_units_dbus(conn) {
+                Ok(list) => return Some(list),
+                // Bus went away (or systemd is not on it): use the parser from now on
+                Err(_) => self.conn = None,
+            }
+        }
+        Some(list_units_systemctl(self.user))
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for ServiceBackend {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(watcher) = self.watcher.take() { let _ = watcher.join(); }
+    }
 }
 
+/// Without systemd there is nothing to list: one empty list, reported as unsupported.
 #[cfg(not(target_os = "linux"))]
-pub fn get_all_services() -> Vec<(String, String, String)> { Vec::new() }
+pub struct ServiceBackend { loaded: bool }
+
+#[cfg(not(target_os = "linux"))]
+impl ServiceBackend {
+    pub fn new(_user: bool) -> Self { ServiceBackend { loaded: false } }
+
+    pub fn source(&self) -> &'static str { "unsupported" }
+
+    pub fn poll(&mut self, _force: bool) -> Option<Vec<UnitInfo>> { (!std::mem::replace(&mut self.loaded, true)).then(Vec::new) }
+}
+
+/// Unit list (and per-unit usage) handed from the collector thread to the UI.
+pub struct ServicesSnapshot {
+    pub units: Option<Vec<UnitInfo>>,            // set when the list was reloaded
+    pub source: &'static str, // "D-Bus", "systemctl" or "unsupported"
+    pub usage: Option<HashMap<String, UnitUsage>>, // set when usage was read
+    pub details: Option<(String, String)>,         // (unit, details popup text) when requested
+}
+
+/// How often the collector thread checks for unit changes and re-reads usage.
+const SERVICES_COLLECT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Runs a `ServiceBackend` on a background thread so that listing units (D-Bus calls or
+/// `systemctl`) and reading their cgroups never happens on the UI thread.
+pub struct ServiceCollector {
+    user: bool,
+    want_usage: Arc<AtomicBool>,
+    want_reload: Arc<AtomicBool>,
+    want_details: Arc<Mutex<Option<String>>>, // unit whose details popup text to read next
+    collector: Collector<ServicesSnapshot>,
+}
+
+impl ServiceCollector {
+    pub fn new(user: bool) -> Self {
+        let want_usage = Arc::new(AtomicBool::new(false));
+        let want_reload = Arc::new(AtomicBool::new(false));
+        let want_details: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+        let (usage_wanted, reload_wanted, details_wanted) = (want_usage.clone(), want_reload.clone(), want_details.clone());
+        let mut backend: Option<ServiceBackend> = None;
+        let mut details_bus = DetailsBus::default();
+        let mut units: Vec<UnitInfo> = Vec::new();
+        let collector = Collector::spawn("services", SERVICES_COLLECT_INTERVAL, move |_| {
+            // Details first: the popup is already open and waiting for them
+            let details = details_wanted.lock().ok().and_then(|mut d| d.take()).map(|unit| {
+                let text = unit_details(&mut details_bus, &unit, user);
+                (unit, text)
+            });
+            // Connect on the worker: a slow or hung bus must not delay the UI
+            let backend = backend.get_or_insert_with(|| ServiceBackend::new(user));
+            let reloaded = backend.poll(reload_wanted.swap(false, Ordering::Relaxed));
+            if let Some(list) = &reloaded { units = list.clone(); }
+            let usage = usage_wanted.load(Ordering::Relaxed).then(|| read_units_usage(&units, user));
+            if reloaded.is_none() && usage.is_none() && details.is_none() { return None; }
+            Some(ServicesSnapshot { units: reloaded, source: backend.source(), usage, details })
+        });
+        ServiceCollector { user, want_usage, want_reload, want_details, collector }
+    }
+
+    /// Whether this collector lists the per-user manager's units.
+    pub fn is_user(&self) -> bool { self.user }
+
+    /// Per-unit cgroup usage is only read while it is shown.
+    pub fn set_usage_wanted(&self, wanted: bool) { self.want_usage.store(wanted, Ordering::Relaxed); }
+
+    /// Reload the list now (e.g. after a unit action).
+    pub fn reload(&self) {
+        self.want_reload.store(true, Ordering::Relaxed);
+        self.collector.wake();
+    }
 
-// Fetch detailed status text for a service (Linux)
+    /// Read the details popup text for `unit` now; it comes back in the next snapshot.
+    pub fn request_details(&self, unit: String) {
+        if let Ok(mut d) = self.want_details.lock() { *d = Some(unit); }
+        self.collector.wake();
+    }
+
+    /// Newest snapshot, if the collector produced one since the last call.
+    pub fn take(&self) -> Option<ServicesSnapshot> { self.collector.take() }
+}
+
+/// Bus connection for details lookups, opened on the first one and kept.
+#[derive(Default)]
+struct DetailsBus {
+    #[cfg(target_os = "linux")]
+    conn: Option<Connection>,
+}
+
+impl DetailsBus {
+    /// Properties over the kept connection; a failed lookup drops it so the next one reconnects.
+    #[cfg(target_os = "linux")]
+    fn unit_properties(&mut self, unit: &str, user: bool) -> Option<Vec<(String, String)>> {
+        if self.conn.is_none() { self.conn = manager_bus(user).ok(); }
+        let props = get_unit_properties(self.conn.as_mut()?, unit);
+        if props.is_err() { self.conn = None; }
+        props.ok()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn unit_properties(&mut self, _unit: &str, _user: bool) -> Option<Vec<(String, String)>> { None }
+}
+
+/// Details popup text: D-Bus properties (when the bus is reachable) followed by `systemctl status`.
+fn unit_details(bus: &mut DetailsBus, unit: &str, user: bool) -> String {
+    let mut out = String::new();
+    if let Some(props) = bus.unit_properties(unit, user) && !props.is_empty() {
+        let w = props.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+        for (k, v) in &props { out.push_str(&format!("{:<w$}  {}\n", k, v, w = w)); }
+        out.push('\n');
+    }
+    out.push_str(&get_service_status(unit, user));
+    out
+}
+
+/// Watch systemd unit changes on a separate connection: Subscribe to the manager and match
+/// unit property changes and unit add/remove signals; each one marks the list as changed.
+#[cfg(target_os = "linux")]
+fn spawn_unit_watcher(user: bool, changed: Arc<AtomicBool>, bus_lost: Arc<AtomicBool>, stop: Arc<AtomicBool>) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        let setup = || -> std::io::Result<Connection> {
+            let mut conn = manager_bus(user)?;
+            conn.call(SYSTEMD_DEST, SYSTEMD_PATH, SYSTEMD_MANAGER, "Subscribe", &[])?;
+            conn.add_match("type='signal',sender='org.freedesktop.systemd1',interface='org.freedesktop.DBus.Properties',member='PropertiesChanged',path_namespace='/org/freedesktop/systemd1/unit'")?;
+            conn.add_match("type='signal',sender='org.freedesktop.systemd1',interface='org.freedesktop.systemd1.Manager'")?;
+            Ok(conn)
+        };
+        let Ok(mut conn) = setup() else { bus_lost.store(true, Ordering::Relaxed); return; };
+        while !stop.load(Ordering::Relaxed) {
+            match conn.next_signal(WATCH_POLL) {
+                Ok(Some(_)) => changed.store(true, Ordering::Relaxed),
+                Ok(None) => {}
+                Err(_) => { bus_lost.store(true, Ordering::Relaxed); return; }
+            }
+        }
+    })
+}
+
+// Fetch detailed status text for a unit (Linux)
 #[cfg(target_os = "linux")]
-pub fn get_service_status(unit: &str) -> String {
-    let output = Command::new("systemctl")
+pub fn get_service_status(unit: &str, user: bool) -> String {
+    let output = systemctl(user)
         .args(["status", unit, "--no-pager", "--full"]) 
         .output();
     match output {
         Ok(out) if out.status.success() => String::from_utf8(out.stdout).unwrap_or_else(|_| String::from("(failed to decode output)")),
         Ok(out) => {
             let mut s = String::new();
-            if !out.stdout.is_empty() { s = String::from_utf8_lossy(&out.stdout).into_owned(); }
+            if !out.stdout.is_empty() {
+                s = String::from_utf8_lossy(&out.stdout).into_owned();
+            }
             if !out.stderr.is_empty() {
                 if !s.is_empty() { s.push_str("\n"); }
                 s.push_str(&String::from_utf8_lossy(&out.stderr));
@@ -57,4 +477,601 @@ pub fn get_service_status(unit: &str) -> String {
 }
 
 #[cfg(not(target_os = "linux"))]
-pub fn get_service_status(_unit: &str) -> String { String::from("Service details are supported on Linux only.") }
+pub fn get_service_status(_unit: &str, _user: bool) -> String { String::from("Service details are supported on Linux only.") }
+
+// -------- Service details popup tabs --------
+/// Tabs of the service details popup, in order.
+pub const SERVICE_DETAIL_TABS: &[&str] = &["Status", "Unit file", "Dependencies", "Properties"];
+
+/// Stdout of a `systemctl` query, or its error output when it fails.
+fn systemctl_text(user: bool, args: &[&str]) -> String {
+    match systemctl(user).args(args).output() {
+        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout).into_owned(),
+        Ok(out) => {
+            let text = command_output_text(&out);
+            if text.trim().is_empty() { String::from("(no output)") } else { text }
+        }
+        Err(e) => format!("Failed to run systemctl: {}", e),
+    }
+}
+
+/// Unit file and drop-ins as printed by `systemctl cat` (each file starts with a "# /path" line).
+pub fn get_unit_file(unit: &str, user: bool) -> String {
+    systemctl_text(user, &["cat", "--no-pager", "--", unit])
+}
+
+/// Effective properties from `systemctl show` (KEY=VALUE lines, empty values omitted).
+pub fn get_unit_show(unit: &str, user: bool) -> String {
+    systemctl_text(user, &["show", "--no-pager", "--", unit])
+}
+
+/// Whether a `systemctl cat` header line names a drop-in (".../foo.service.d/override.conf").
+pub fn is_drop_in_header(line: &str) -> bool {
+    line.strip_prefix("# /").is_some_and(|path| path.contains(".d/"))
+}
+
+/// One row of a `systemctl list-dependencies` tree.
+#[derive(Debug, Clone)]
+pub struct DepRow {
+    pub prefix: String, // tree glyphs drawn in front of the unit name
+    pub unit: String,
+}
+
+/// Dependency tree of a unit (what it pulls in, or with `reverse` what pulls it in).
+/// The first row is the unit itself.
+pub fn get_unit_dependencies(unit: &str, user: bool, reverse: bool) -> Result<Vec<DepRow>, String> {
+    let mut cmd = systemctl(user);
+    cmd.args(["list-dependencies", "--no-pager"]);
+    if reverse { cmd.arg("--reverse"); }
+    let out = cmd.args(["--", unit]).output().map_err(|e| format!("Failed to run systemctl: {}", e))?;
+    if !out.status.success() { return Err(command_output_text(&out)); }
+    Ok(parse_dependency_rows(&String::from_utf8_lossy(&out.stdout)))
+}
+
+/// Rows of `systemctl list-dependencies` output, split into tree glyphs and unit name.
+fn parse_dependency_rows(text: &str) -> Vec<DepRow> {
+    let is_tree = |c: char| c.is_whitespace() || ('\u{2500}'..='\u{257f}').contains(&c);
+    let mut rows: Vec<DepRow> = Vec::new();
+    for line in text.lines() {
+        if line.trim().is_empty() { continue; }
+        // Rows look like "● │ └─foo.service": a state marker, then the tree glyphs, then the name
+        let mut chars = line.chars();
+        let rest = match (chars.next(), chars.next()) {
+            (Some(c), Some(' ')) if !is_tree(c) => &line[c.len_utf8() + 1..],
+            _ => line,
+        };
+        let split = rest.find(|c: char| !is_tree(c)).unwrap_or(rest.len());
+        let (prefix, name) = rest.split_at(split);
+        rows.push(DepRow { prefix: prefix.to_string(), unit: name.trim().to_string() });
+    }
+    rows
+}
+
+// -------- Services list filters --------
+/// Quick state filters for the Services table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceFilter { All, Running, Failed, Inactive, EnabledDead }
+
+impl ServiceFilter {
+    pub fn label(self) -> &'static str {
+        match self {
+            ServiceFilter::All => "all",
+            ServiceFilter::Running => "running",
+            ServiceFilter::Failed => "failed",
+            ServiceFilter::Inactive => "inactive",
+            ServiceFilter::EnabledDead => "enabled but dead",
+        }
+    }
+
+    pub fn matches(self, u: &UnitInfo) -> bool {
+        match self {
+            ServiceFilter::All => true,
+            ServiceFilter::Running => u.sub == "running",
+            ServiceFilter::Failed => u.active == "failed",
+            ServiceFilter::Inactive => u.active == "inactive",
+            // Enabled to start at boot (or on demand) but currently not running
+            ServiceFilter::EnabledDead => u.file_state == "enabled" && (u.active == "inactive" || u.active == "failed"),
+        }
+    }
+}
+
+// -------- Failed units (Dashboard alert panel) --------
+/// A unit of the system manager in the failed state.
+#[derive(Debug, Clone)]
+pub struct FailedUnit {
+    pub name: String,
+    pub description: String,
+    pub since: Option<u64>, // when it entered the failed state, realtime usec
+    pub result: String,     // exit-code, signal, timeout, core-dump, ... ("" if unknown)
+}
+
+/// How often the Dashboard re-checks for failed units.
+pub const FAILED_UNITS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// All failed units of the system manager (any unit type), with the time they failed and
+/// the unit's Result. Uses D-Bus when reachable, otherwise `systemctl`.
+#[cfg(target_os = "linux")]
+pub fn list_failed_units() -> Vec<FailedUnit> {
+    let mut units = match Connection::system().and_then(|mut conn| failed_units_dbus(&mut conn)) {
+        Ok(units) => units,
+        Err(_) => failed_units_systemctl(),
+    };
+    units.sort_by(|a, b| a.name.cmp(&b.name));
+    units
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn list_failed_units() -> Vec<FailedUnit> { Vec::new() }
+
+#[cfg(target_os = "linux")]
+fn failed_units_dbus(conn: &mut Connection) -> std::io::Result<Vec<FailedUnit>> {
+    let reply = conn.call(SYSTEMD_DEST, SYSTEMD_PATH, SYSTEMD_MANAGER, "ListUnits", &[])?;
+    let mut units: Vec<FailedUnit> = Vec::new();
+    for u in reply.first().map(|v| v.items()).unwrap_or(&[]) {
+        let f = u.items();
+        let field = |i: usize| f.get(i).and_then(|v| v.as_str()).unwrap_or("").to_string();
+        if field(3) != "failed" { continue; }
+        let name = field(0);
+        let mut ifaces = vec!["org.freedesktop.systemd1.Unit"];
+        ifaces.extend(unit_type_interface(name.rsplit('.').next().unwrap_or("")));
+        let props = get_all_properties(conn, &field(6), &ifaces);
+        let get = |key: &str| props.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+        let since = get("StateChangeTimestamp").and_then(|v| v.as_u64()).filter(|t| *t != 0);
+        let result = get("Result").and_then(|v| v.as_str()).unwrap_or("").to_string();
+        units.push(FailedUnit { name, description: field(1), since, result });
+    }
+    Ok(units)
+}
+
+#[cfg(target_os = "linux")]
+fn failed_units_systemctl() -> Vec<FailedUnit> {
+    let Ok(out) = systemctl(false)
+        .args(["list-units", "--state=failed", "--all", "--no-legend", "--no-pager", "--plain"])
+        .output() else { return Vec::new(); };
+    let mut units: Vec<FailedUnit> = String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .filter_map(|l| {
+            // UNIT LOAD ACTIVE SUB DESCRIPTION
+            let mut parts = l.trim().trim_start_matches('●').split_whitespace();
+            let name = parts.next()?.to_string();
+            let description = parts.skip(3).collect::<Vec<_>>().join(" ");
+            Some(FailedUnit { name, description, since: None, result: String::new() })
+        })
+        .collect();
+    if units.is_empty() { return units; }
+    let out = systemctl(false)
+        .args(["show", "--timestamp=unix", "-p", "Id", "-p", "Result", "-p", "StateChangeTimestamp", "--"])
+        .args(units.iter().map(|u| &u.name))
+        .output();
+    if let Ok(out) = out && out.status.success() {
+        let text = String::from_utf8_lossy(&out.stdout);
+        for block in text.split("\n\n") {
+            let get = |key: &str| block.lines().find_map(|l| l.strip_prefix(key)?.strip_prefix('='));
+            let Some(u) = get("Id").and_then(|id| units.iter_mut().find(|u| u.name == id)) else { continue; };
+            u.since = get("StateChangeTimestamp").and_then(|t| t.strip_prefix('@')?.trim().parse::<u64>().ok()).map(|s| s * 1_000_000);
+            u.result = get("Result").unwrap_or("").to_string();
+        }
+    }
+    units
+}
+
+// -------- Per-unit resource usage (cgroups under /sys/fs/cgroup) --------
+/// Resource usage of one unit read from its cgroup; None when a value is unavailable
+/// (inactive unit, controller not enabled, or no permission).
+#[derive(Debug, Clone, Default)]
+pub struct UnitUsage {
+    pub tasks: Option<u64>,
+    pub memory: Option<u64>,   // bytes
+    pub cpu_usec: Option<u64>, // cumulative CPU time
+}
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// Root of the hierarchy systemd organizes units in: the unified (v2) tree, the "unified"
+/// mount of a hybrid setup, or the v1 name=systemd tree.
+fn systemd_cgroup_root() -> Option<PathBuf> {
+    let root = Path::new(CGROUP_ROOT);
+    if root.join("cgroup.controllers").exists() { return Some(root.to_path_buf()); }
+    ["unified", "systemd"].iter().map(|d| root.join(d)).find(|p| p.join("cgroup.procs").exists())
+}
+
+/// Map unit names to their cgroup path relative to `root`, searching at most `max_depth`
+/// levels below `start`. Breadth-first, so the shallowest unit of a name wins.
+fn unit_cgroup_paths(root: &Path, start: &Path, max_depth: usize) -> HashMap<String, PathBuf> {
+    let mut found: HashMap<String, PathBuf> = HashMap::new();
+    let mut queue: std::collections::VecDeque<(PathBuf, usize)> = std::collections::VecDeque::from([(start.to_path_buf(), 0)]);
+    while let Some((rel, depth)) = queue.pop_front() {
+        let Ok(entries) = std::fs::read_dir(root.join(&rel)) else { continue; };
+        for entry in entries.flatten() {
+            if !entry.file_type().map(|t| t.is_dir()).unwrap_or(false) { continue; }
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let child = rel.join(&name);
+            if name.rsplit('.').next().is_some_and(|t| UNIT_TYPES.contains(&t)) { found.entry(name).or_insert_with(|| child.clone()); }
+            if depth < max_depth { queue.push_back((child, depth + 1)); }
+        }
+    }
+    found
+}
+
+/// Units nest at most a few levels below their manager (app.slice/app-foo.slice/foo.service/...).
+const UNIT_CGROUP_DEPTH: usize = 6;
+
+fn read_cgroup_u64(path: &Path) -> Option<u64> {
+    std::fs::read_to_string(path).ok()?.trim().parse::<u64>().ok()
+}
+
+/// Usage of one unit, given its cgroup path relative to the systemd hierarchy `root`.
+/// On cgroup v1 the counters come from the per-controller trees at the same path.
+fn read_unit_usage(root: &Path, rel: &Path) -> UnitUsage {
+    let dir = root.join(rel);
+    let v1 = |controller: &str, file: &str| read_cgroup_u64(&Path::new(CGROUP_ROOT).join(controller).join(rel).join(file));
+    let memory = read_cgroup_u64(&dir.join("memory.current")).or_else(|| v1("memory", "memory.usage_in_bytes"));
+    let cpu_usec = std::fs::read_to_string(dir.join("cpu.stat"))
+        .ok()
+        .and_then(|s| s.lines().find_map(|l| l.strip_prefix("usage_usec ").and_then(|v| v.trim().parse::<u64>().ok())))
+        .or_else(|| v1("cpuacct", "cpuacct.usage").map(|ns| ns / 1000));
+    let tasks = read_cgroup_u64(&dir.join("pids.current")).or_else(|| v1("pids", "pids.current"));
+    UnitUsage { tasks, memory, cpu_usec }
+}
+
+/// Usage for every active unit in `units`, keyed by unit name. User units are looked up
+/// below the user's manager (user.slice/user-<uid>.slice/user@<uid>.service).
+#[cfg(target_os = "linux")]
+pub fn read_units_usage(units: &[UnitInfo], user: bool) -> HashMap<String, UnitUsage> {
+    let mut out: HashMap<String, UnitUsage> = HashMap::new();
+    let Some(root) = systemd_cgroup_root() else { return out; };
+    let paths = if user {
+        let uid = crate::processes::current_uid();
+        unit_cgroup_paths(&root, Path::new(&format!("user.slice/user-{uid}.slice/user@{uid}.service")), UNIT_CGROUP_DEPTH)
+    } else {
+        // System units sit at the top (init.scope, the slices) or in system.slice; user.slice
+        // holds user managers whose units may share a name with a system unit
+        let mut paths = unit_cgroup_paths(&root, Path::new(""), 0);
+        paths.extend(unit_cgroup_paths(&root, Path::new("system.slice"), UNIT_CGROUP_DEPTH));
+        paths
+    };
+    for u in units {
+        if let Some(rel) = paths.get(&u.name) { out.insert(u.name.clone(), read_unit_usage(&root, rel)); }
+    }
+    out
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_units_usage(_units: &[UnitInfo], _user: bool) -> HashMap<String, UnitUsage> { HashMap::new() }
+
+/// Format cumulative CPU time: seconds with centiseconds below a minute, else [Nd ]HH:MM:SS.
+pub fn format_cpu_usec(usec: u64) -> String {
+    let secs = usec / 1_000_000;
+    if secs < 60 { return format!("{:.2}s", usec as f64 / 1e6); }
+    let (days, hours, minutes, seconds) = (secs / 86_400, (secs % 86_400) / 3_600, (secs % 3_600) / 60, secs % 60);
+    if days > 0 { format!("{}d {:02}:{:02}:{:02}", days, hours, minutes, seconds) } else { format!("{:02}:{:02}:{:02}", hours, minutes, seconds) }
+}
+
+// -------- Services table columns and sorting --------
+/// Columns of the Services table; which ones are shown depends on the unit type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceColumn { Unit, Load, Active, Sub, MainPid, Tasks, Memory, Cpu, Next, Last, Description }
+
+impl ServiceColumn {
+    /// Units with processes (services, sockets, mounts): state plus cgroup usage.
+    pub const USAGE: [ServiceColumn; 9] = [
+        ServiceColumn::Unit, ServiceColumn::Load, ServiceColumn::Active, ServiceColumn::Sub, ServiceColumn::MainPid,
+        ServiceColumn::Tasks, ServiceColumn::Memory, ServiceColumn::Cpu, ServiceColumn::Description,
+    ];
+    /// Timers: state plus next/last trigger times.
+    pub const TIMER: [ServiceColumn; 7] = [
+        ServiceColumn::Unit, ServiceColumn::Load, ServiceColumn::Active, ServiceColumn::Sub,
+        ServiceColumn::Next, ServiceColumn::Last, ServiceColumn::Description,
+    ];
+    /// Paths and targets: state only.
+    pub const STATE: [ServiceColumn; 5] = [
+        ServiceColumn::Unit, ServiceColumn::Load, ServiceColumn::Active, ServiceColumn::Sub, ServiceColumn::Description,
+    ];
+
+    /// Columns shown for a unit type.
+    pub fn for_type(unit_type: &str) -> &'static [ServiceColumn] {
+        match unit_type {
+            "service" | "socket" | "mount" => &Self::USAGE,
+            "timer" => &Self::TIMER,
+            _ => &Self::STATE,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ServiceColumn::Unit => "UNIT",
+            ServiceColumn::Load => "LOAD",
+            ServiceColumn::Active => "ACTIVE",
+            ServiceColumn::Sub => "SUB",
+            ServiceColumn::MainPid => "MAIN PID",
+            ServiceColumn::Tasks => "TASKS",
+            ServiceColumn::Memory => "MEMORY",
+            ServiceColumn::Cpu => "CPU TIME",
+            ServiceColumn::Next => "NEXT",
+            ServiceColumn::Last => "LAST",
+            ServiceColumn::Description => "DESCRIPTION",
+        }
+    }
+
+    /// Fixed column width, or None for the columns sharing the remaining space.
+    pub fn width(self) -> Option<u16> {
+        match self {
+            ServiceColumn::Unit | ServiceColumn::Description => None,
+            ServiceColumn::Load => Some(9),
+            ServiceColumn::Active | ServiceColumn::Sub => Some(12),
+            ServiceColumn::MainPid => Some(8),
+            ServiceColumn::Tasks => Some(6),
+            ServiceColumn::Memory => Some(10),
+            ServiceColumn::Cpu => Some(11),
+            ServiceColumn::Next | ServiceColumn::Last => Some(19),
+        }
+    }
+
+    /// Next column to the right among `columns` (wrapping).
+    pub fn next(self, columns: &[ServiceColumn]) -> Self {
+        let i = columns.iter().position(|c| *c == self).unwrap_or(0);
+        columns.get((i + 1) % columns.len().max(1)).cloned().unwrap_or(self)
+    }
+
+    /// Previous column to the left among `columns` (wrapping).
+    pub fn prev(self, columns: &[ServiceColumn]) -> Self {
+        let i = columns.iter().position(|c| *c == self).unwrap_or(0);
+        columns.get((i + columns.len().max(1) - 1) % columns.len().max(1)).cloned().unwrap_or(self)
+    }
+
+    /// Usage columns start with the heaviest units on top and LAST with the most recent
+    /// trigger; text columns and NEXT (soonest first) ascend.
+    pub fn default_descending(self) -> bool {
+        matches!(self, ServiceColumn::Tasks | ServiceColumn::Memory | ServiceColumn::Cpu | ServiceColumn::Last)
+    }
+}
+
+/// Sort units by a Services table column. Units without a value (inactive, unreadable
+/// cgroup, timer never triggered) always sort last; ties fall back to the unit name.
+pub fn sort_services(units: &mut [UnitInfo], usage: &HashMap<String, UnitUsage>, key: ServiceColumn, desc: bool) {
+    use std::cmp::Ordering;
+    let num = |u: &UnitInfo| -> Option<u64> {
+        match key {
+            ServiceColumn::Next => return u.next_elapse,
+            ServiceColumn::Last => return u.last_trigger,
+            ServiceColumn::MainPid => return u.main_pid.map(|p| p as u64),
+            _ => {}
+        }
+        let us = usage.get(&u.name)?;
+        match key {
+            ServiceColumn::Tasks => us.tasks,
+            ServiceColumn::Memory => us.memory,
+            ServiceColumn::Cpu => us.cpu_usec,
+            _ => None,
+        }
+    };
+    let text = |u: &UnitInfo| -> String {
+        match key {
+            ServiceColumn::Load => u.load.clone(),
+            ServiceColumn::Active => u.active.clone(),
+            ServiceColumn::Sub => u.sub.clone(),
+            ServiceColumn::Description => u.description.to_lowercase(),
+            _ => u.name.clone(),
+        }
+    };
+    let numeric = matches!(key, ServiceColumn::MainPid | ServiceColumn::Tasks | ServiceColumn::Memory | ServiceColumn::Cpu | ServiceColumn::Next | ServiceColumn::Last);
+    units.sort_by(|a, b| {
+        let ord = if numeric {
+            match (num(a), num(b)) {
+                (Some(x), Some(y)) => x.cmp(&y),
+                (None, None) => Ordering::Equal,
+                (None, _) => return Ordering::Greater,
+                (_, None) => return Ordering::Less,
+            }
+        } else {
+            text(a).cmp(&text(b))
+        };
+        let ord = if desc { ord.reverse() } else { ord };
+        ord.then(a.name.cmp(&b.name))
+    });
+}
+
+// -------- Unit journal (journalctl -u) --------
+/// Priority filters for the unit journal view, from everything to emergencies only
+/// (`journalctl -p` shows the given level and more severe ones).
+pub const JOURNAL_PRIORITIES: &[&str] = &["all", "info", "notice", "warning", "err", "crit", "alert", "emerg"];
+
+/// Entries read from a unit's journal.
+pub struct JournalChunk {
+    pub text: String,
+    pub cursor: Option<String>, // position after the last entry, for follow-up reads
+    pub limited: bool,          // journalctl warned that other users'/system entries are hidden
+}
+
+/// `journalctl [--user] -u <unit>` arguments: `priority` indexes JOURNAL_PRIORITIES and
+/// `boot` is a `-b` offset (0 = current boot, None = all boots).
+fn unit_journal_args(unit: &str, user: bool, priority: usize, boot: Option<i32>) -> Vec<String> {
+    let mut args: Vec<String> = vec![String::from("journalctl")];
+    if user { args.push(String::from("--user")); }
+    args.extend(["-u", unit, "-o", "short-iso", "--no-pager"].map(String::from));
+    if let Some(p) = JOURNAL_PRIORITIES.get(priority).filter(|p| **p != "all") { args.extend([String::from("-p"), p.to_string()]); }
+    if let Some(b) = boot { args.extend([String::from("-b"), b.to_string()]); }
+    args
+}
+
+/// Read the last 5000 entries of a unit's journal (see `unit_journal_args`), with the cursor
+/// after the last one. With a sudo password the command runs through sudo.
+pub fn read_unit_journal(unit: &str, user: bool, priority: usize, boot: Option<i32>, sudo_pass: Option<&str>) -> Result<JournalChunk, String> {
+    let mut args = unit_journal_args(unit, user, priority, boot);
+    args.extend(["--show-cursor", "-n", "5000"].map(String::from));
+    let argv: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    let (stdout, stderr) = match sudo_pass {
+        Some(pw) => (run_with_sudo(&argv, pw)?, String::new()),
+        None => {
+            let out = Command::new(argv[0]).args(&argv[1..]).output().map_err(|e| format!("Failed to run journalctl: {}", e))?;
+            let stderr = String::from_utf8_lossy(&out.stderr).into_owned();
+            if !out.status.success() { return Err(if stderr.trim().is_empty() { String::from("journalctl failed") } else { stderr }); }
+            (String::from_utf8_lossy(&out.stdout).into_owned(), stderr)
+        }
+    };
+    let lower = stderr.to_lowercase();
+    let limited = lower.contains("insufficient permissions") || lower.contains("not seeing messages from other users");
+    let mut cursor = None;
+    let mut text = String::new();
+    for line in stdout.lines() {
+        if let Some(c) = line.strip_prefix("-- cursor: ") { cursor = Some(c.trim().to_string()); continue; }
+        text.push_str(line);
+        text.push('\n');
+    }
+    Ok(JournalChunk { text, cursor, limited })
+}
+
+/// `journalctl -f` printing the unit's entries after `after_cursor` (only new ones without a
+/// cursor), through `sudo -S` when `sudo` is set; the caller supplies the password.
+pub fn unit_journal_follow_command(unit: &str, user: bool, priority: usize, boot: Option<i32>, after_cursor: Option<&str>, sudo: bool) -> Command {
+    let mut args = unit_journal_args(unit, user, priority, boot);
+    match after_cursor {
+        Some(c) => args.extend([format!("--after-cursor={}", c), String::from("--lines=all")]),
+        None => args.push(String::from("--lines=0")),
+    }
+    args.push(String::from("--follow"));
+    let mut cmd = Command::new(if sudo { "sudo" } else { "journalctl" });
+    if sudo { cmd.args(["-S", "-p", "", "--", "journalctl"]); }
+    cmd.args(&args[1..]);
+    cmd
+}
+
+// -------- Unit actions (Services tab action menu) --------
+/// Actions offered for the selected unit, in menu order.
+pub const SERVICE_ACTIONS: &[&str] = &["start", "stop", "restart", "reload", "enable", "disable", "mask", "unmask"];
+
+/// Why a unit action failed: denied by polkit/permissions (retry with sudo) or any other error.
+pub enum ServiceActionError {
+    Denied(String),
+    Failed(String),
+}
+
+/// Combined stdout and stderr of a finished command.
+fn command_output_text(out: &std::process::Output) -> String {
+    let mut s = String::from_utf8_lossy(&out.stdout).into_owned();
+    let err = String::from_utf8_lossy(&out.stderr);
+    if !err.trim().is_empty() {
+        if !s.is_empty() && !s.ends_with('\n') { s.push('\n'); }
+        s.push_str(&err);
+    }
+    s
+}
+
+/// Run `systemctl [--user] <action> <unit>` without interactive polkit prompts (which would
+/// draw over the TUI). Returns the command output.
+#[cfg(target_os = "linux")]
+pub fn run_service_action(unit: &str, action: &str, user: bool) -> Result<String, ServiceActionError> {
+    let out = systemctl(user)
+        .args(["--no-ask-password", action, "--", unit])
+        .output()
+        .map_err(|e| ServiceActionError::Failed(format!("Failed to run systemctl: {}", e)))?;
+    let text = command_output_text(&out);
+    if out.status.success() { return Ok(text); }
+    let lower = text.to_lowercase();
+    // sudo would reach root's user manager, not ours, so user units never retry with it
+    let denied = lower.contains("access denied") || lower.contains("authentication required") || lower.contains("permission denied") || lower.contains("not authorized");
+    if denied && !user {
+        Err(ServiceActionError::Denied(text))
+    } else {
+        Err(ServiceActionError::Failed(text))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn run_service_action(_unit: &str, _action: &str, _user: bool) -> Result<String, ServiceActionError> {
+    Err(ServiceActionError::Failed(String::from("Service actions are supported on Linux only.")))
+}
+
+/// Run `systemctl <action> <unit>` through sudo with the given password.
+pub fn run_service_action_sudo(unit: &str, action: &str, password: &str) -> Result<String, String> {
+    run_with_sudo(&["systemctl", action, "--", unit], password)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(name: &str) -> UnitInfo {
+        UnitInfo { name: name.to_string(), load: "loaded".into(), active: "active".into(), sub: "running".into(), description: String::new(), file_state: String::new(), next_elapse: None, last_trigger: None, main_pid: None }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn systemctl_show_blocks_fill_units_by_id() {
+        let mut units = vec![unit("a.timer"), unit("b.service"), unit("c.timer")];
+        let text = "Id=a.timer\nNextElapseUSecRealtime=@1700000000\nLastTriggerUSec=@1690000000\nMainPID=0\n\n\
+                    MainPID=999\nNextElapseUSecRealtime=@1\n\n\
+                    Id=b.service\nMainPID=1234\nNextElapseUSecRealtime=\n\n\
+                    \n\n\
+                    Id=c.timer\nNextElapseUSecRealtime=Mon 2024-01-01 00:00:00 UTC\nLastTriggerUSec=n/a\n";
+        apply_systemctl_show(&mut units, text);
+        assert_eq!((units[0].next_elapse, units[0].last_trigger, units[0].main_pid), (Some(1_700_000_000_000_000), Some(1_690_000_000_000_000), None));
+        // Empty timestamps stay empty; the block without an Id went nowhere
+        assert_eq!((units[1].next_elapse, units[1].last_trigger, units[1].main_pid), (None, None, Some(1234)));
+        // Formatted timestamps (systemd without --timestamp=unix) are not parsed
+        assert_eq!((units[2].next_elapse, units[2].last_trigger, units[2].main_pid), (None, None, None));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn timer_next_elapse_takes_the_earlier_trigger() {
+        // 0 and u64::MAX mean "not scheduled" on that clock
+        assert_eq!(timer_next_elapse(0, 0), None);
+        assert_eq!(timer_next_elapse(u64::MAX, u64::MAX), None);
+        assert_eq!(timer_next_elapse(5_000_000, 0), Some(5_000_000));
+        assert_eq!(timer_next_elapse(5_000_000, u64::MAX), Some(5_000_000));
+        // Monotonic triggers are counted from boot, which lies before now
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_micros() as u64;
+        let far = now + 3_600_000_000;
+        let boot_based = timer_next_elapse(0, 1_000_000).unwrap();
+        assert!(boot_based <= now + 1_000_000);
+        // (boot time is derived from the clock and /proc/uptime again, so allow some drift)
+        assert!(timer_next_elapse(far, 1_000_000).is_some_and(|t| t.abs_diff(boot_based) < 1_000_000));
+        assert_eq!(timer_next_elapse(5_000_000, 1_000_000), Some(5_000_000));
+    }
+
+    #[test]
+    fn sort_services_puts_missing_values_last_and_breaks_ties_by_name() {
+        let mut units = vec![unit("d.service"), unit("b.service"), unit("a.service"), unit("c.service")];
+        let usage: HashMap<String, UnitUsage> = [
+            ("a.service", UnitUsage { memory: Some(300), ..Default::default() }),
+            ("b.service", UnitUsage { memory: Some(100), ..Default::default() }),
+            ("c.service", UnitUsage { memory: Some(100), ..Default::default() }),
+        ].into_iter().map(|(n, u)| (n.to_string(), u)).collect();
+        let names = |units: &[UnitInfo]| units.iter().map(|u| u.name.clone()).collect::<Vec<_>>();
+        // d has no usage: last either way; b and c tie on memory and stay in name order
+        sort_services(&mut units, &usage, ServiceColumn::Memory, false);
+        assert_eq!(names(&units), ["b.service", "c.service", "a.service", "d.service"]);
+        sort_services(&mut units, &usage, ServiceColumn::Memory, true);
+        assert_eq!(names(&units), ["a.service", "b.service", "c.service", "d.service"]);
+        // Text columns tie on equal values the same way
+        units[3].sub = "dead".into();
+        sort_services(&mut units, &usage, ServiceColumn::Sub, false);
+        assert_eq!(names(&units), ["d.service", "a.service", "b.service", "c.service"]);
+        sort_services(&mut units, &usage, ServiceColumn::Sub, true);
+        assert_eq!(names(&units), ["a.service", "b.service", "c.service", "d.service"]);
+    }
+
+    #[test]
+    fn dependency_rows_split_markers_glyphs_and_names() {
+        let text = "sshd.service\n\
+                    ● ├─system.slice\n\
+                    ○ ├─sshd-keygen.target\n\
+                    ● │ └─sshd-keygen@rsa.service\n\
+                    \n\
+                    ● └─sysinit.target\n\
+                    ●   └─dev-hugepages.mount\n";
+        let rows: Vec<(String, String)> = parse_dependency_rows(text).into_iter().map(|r| (r.prefix, r.unit)).collect();
+        let expect = [("", "sshd.service"), ("├─", "system.slice"), ("├─", "sshd-keygen.target"), ("│ └─", "sshd-keygen@rsa.service"), ("└─", "sysinit.target"), ("  └─", "dev-hugepages.mount")];
+        assert_eq!(rows, expect.map(|(p, u)| (p.to_string(), u.to_string())));
+    }
+
+    #[test]
+    fn drop_in_headers_are_told_from_unit_files() {
+        assert!(is_drop_in_header("# /etc/systemd/system/sshd.service.d/override.conf"));
+        assert!(is_drop_in_header("# /run/systemd/system/service.d/50-defaults.conf"));
+        assert!(!is_drop_in_header("# /usr/lib/systemd/system/sshd.service"));
+        assert!(!is_drop_in_header("# Comment mentioning foo.d/bar"));
+        assert!(!is_drop_in_header("ExecStart=/usr/bin/foo.d/bar"));
+    }
+}
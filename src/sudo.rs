@@ -0,0 +1,30 @@
+use std::process::{Command, Stdio};
+
+/// Run a command through `sudo -S`, feeding the password on stdin.
+///
+/// Returns stdout on success, or stderr (or a generic message) on failure.
+pub fn run_with_sudo(args: &[&str], password: &str) -> Result<String, String> {
+    let mut child = match Command::new("sudo")
+        .arg("-S")
+        .arg("--")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn() {
+        Ok(c) => c,
+        Err(spawn_err) => return Err(format!("Failed to spawn sudo: {}", spawn_err)),
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        use std::io::Write;
+        let _ = stdin.write_all(password.as_bytes());
+        let _ = stdin.write_all(b"\n");
+    }
+    let out = child.wait_with_output().map_err(|e| format!("sudo error: {}", e))?;
+    if out.status.success() {
+        Ok(String::from_utf8_lossy(&out.stdout).into_owned())
+    } else {
+        let err = String::from_utf8_lossy(&out.stderr).into_owned();
+        Err(if err.trim().is_empty() { String::from("sudo failed") } else { err })
+    }
+}
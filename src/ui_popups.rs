@@ -1,9 +1,14 @@
 use ratatui::layout::Rect;
-use ratatui::style::{Color, Style};
+use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::Span;
 use ratatui::text::Line;
 use ratatui::widgets::{Block, Borders, Clear};
 
+use crate::app::{App, SignalStage, TuneMode};
+use crate::processes::{signal_name, IO_CLASSES, PROCESS_DETAIL_TABS, SIGNALS};
+use crate::logview::{line_severity, LogMatch, Severity};
+use crate::services::{is_drop_in_header, SERVICE_ACTIONS, SERVICE_DETAIL_TABS};
+
 // Help popup drawing (F1)
 /// Draw the F1 Help popup with multiline content and a cyan border + shadow.
 pub fn draw_help_popup(f: &mut ratatui::Frame<'_>, size: Rect) {
@@ -16,6 +21,27 @@ pub fn draw_help_popup(f: &mut ratatui::Frame<'_>, size: Rect) {
         Line::from(Span::raw("Navigation and hotkeys:")),
         Line::from(Span::raw("    - Switch tabs: Left/Right, h/l, Tab/BackTab, or 1/2/3/4/5/6.")),
         Line::from(Span::raw("    - In tables (Processes/Services/Logs/Journal): Home/End jump to first/last; PgUp/PgDn move by 10.")),
+        Line::from(Span::raw("    - top/htop: < and > change the sort column, r reverses the sort direction.")),
+        Line::from(Span::raw("    - top/htop: / filters by command text, pid:N, user:NAME or re:REGEX; in tree view n/N jump between matches, Esc clears.")),
+        Line::from(Span::raw("    - top/htop: t toggles tree view; +/- or Space expand/collapse the selected branch.")),
+        Line::from(Span::raw("    - top/htop: k opens the signal menu (TERM/KILL/HUP/INT/STOP/CONT/USR1/USR2 or a number); s retries with sudo.")),
+        Line::from(Span::raw("    - top/htop: p renice, a CPU affinity, i I/O priority for the selected process; t toggles all threads; Enter applies.")),
+        Line::from(Span::raw("    - top/htop: Enter opens tabbed details (Files, Sockets, Memory, Environment, Limits, Cgroup, Namespaces); Left/Right or 1-8.")),
+        Line::from(Span::raw("    - top/htop: C opens the column chooser (PPID, S, THR, RSS, VIRT, SHR, START, TTY, CGROUP, ...); saved to config.")),
+        Line::from(Span::raw("    - top/htop: I toggles READ/s and WRITE/s columns from /proc/<pid>/io (sorted by I/O; - = not readable).")),
+        Line::from(Span::raw("    - top/htop: T opens the live thread view (TID, name, state, CPU%, last CPU); also from the details popup.")),
+        Line::from(Span::raw("    - Services: </> cycle the sort column (UNIT, LOAD, ACTIVE, SUB, MAIN PID, TASKS, MEMORY, CPU TIME, DESCRIPTION); r reverses.")),
+        Line::from(Span::raw("    - Services: R running, F failed, I inactive, E enabled-but-dead (again = all); / searches unit names; Esc clears.")),
+        Line::from(Span::raw("    - Services: t/T cycle unit type (service, timer, socket, mount, path, target); u toggles the --user manager.")),
+        Line::from(Span::raw("    - Services: a opens the action menu (start/stop/restart/reload/enable/disable/mask/unmask) with confirmation.")),
+        Line::from(Span::raw("    - Services: j opens the unit journal (also from the details popup): f follow, p/P stricter/looser priority, b/B older/newer boot.")),
+        Line::from(Span::raw("    - Service popup: Left/Right or 1-4 switch Status, Unit file (drop-ins in yellow), Dependencies, Properties.")),
+        Line::from(Span::raw("    - Service popup Dependencies: Up/Down select, Enter opens that unit's tree, Backspace goes back, r reverses.")),
+        Line::from(Span::raw("    - Logs: .gz/.xz/.zst/.bz2 rotations are decompressed; R opens a log with all its rotations (also R in the popup).")),
+        Line::from(Span::raw("    - Log popup: files of any size are paged from disk; Home/End jump to the first/last line.")),
+        Line::from(Span::raw("    - Log popup: lines are colored by their level (<N>, [error], level=, JSON); p/P hide lines below a level (stricter/looser; journals use journalctl -p).")),
+        Line::from(Span::raw("    - Log popup: f follows a /var/log file like tail -F (survives logrotate); scrolling up pauses, End resumes.")),
+        Line::from(Span::raw("    - Log popup: / regex search (Tab toggles case while typing), n/N next/prev match, c case, Esc clears.")),
         Line::from(Span::raw("    - F2 Dashboard, F3 top/htop, F4 Services (SystemD), F5 Logs, F6 Journal, F12 Shell (embedded PTY).")), 
         Line::from(Span::raw("    - In Shell tab: keys go to your shell; Ctrl-C is sent to the shell (F10 exits app).")), 
         Line::from(Span::raw("    - F10 exit app; q also exits.")), 
@@ -76,105 +102,159 @@ pub fn draw_help_popup(f: &mut ratatui::Frame<'_>, size: Rect) {
     f.render_widget(paragraph, inner);
 }
 
-/// Draw the Service Details popup with a dynamic title and multi-line body text.
-pub fn draw_service_popup(f: &mut ratatui::Frame<'_>, size: Rect, title: &str, text: &str) {
-    // Split text into lines and measure width
-    let lines_raw: Vec<&str> = text.split('\n').collect();
-    let max_text_width: u16 = lines_raw.iter().map(|l| l.chars().count() as u16).max().unwrap_or(0).saturating_add(1);
-    // Add some padding and clamp to screen bounds
-    let mut popup_w: u16 = max_text_width.saturating_add(4);
-    if popup_w > size.width { popup_w = size.width; }
-    // Height is based on number of lines (capped to screen)
-    let mut popup_h: u16 = (lines_raw.len() as u16).saturating_add(2);
-    if popup_h > size.height { popup_h = size.height; }
-
-    // Center the popup
-    let popup_x = size.x + (size.width.saturating_sub(popup_w)) / 2;
-    let popup_y = size.y + (size.height.saturating_sub(popup_h)) / 2;
-    let area = Rect { x: popup_x, y: popup_y, width: popup_w, height: popup_h };
+/// Draw the tabbed Service Details popup: status (or action output), unit file with drop-ins
+/// highlighted, navigable dependency tree, and effective properties.
+pub fn draw_service_popup(f: &mut ratatui::Frame<'_>, size: Rect, app: &App) {
+    let popup_w = size.width.saturating_sub(6).max(40);
+    let popup_h = size.height.saturating_sub(4).max(12);
+    let manager = if app.service_detail_user { " (user)" } else { "" };
+    let inner = draw_popup_frame(f, size, popup_w, popup_h, &format!("Service: {}{}", app.service_detail_title, manager), Color::Green);
 
-    // Shadow
-    let sx = area.x.saturating_add(1);
-    let sy = area.y.saturating_add(1);
-    if sx < size.x + size.width && sy < size.y + size.height {
-        let sw = area.width.min((size.x + size.width).saturating_sub(sx));
-        let sh = area.height.min((size.y + size.height).saturating_sub(sy));
-        if sw > 0 && sh > 0 {
-            let shadow = Rect { x: sx, y: sy, width: sw, height: sh };
-            let shadow_block = Block::default().style(Style::default().bg(Color::Black));
-            f.render_widget(shadow_block, shadow);
-        }
+    // Tab bar: active tab highlighted, with 1..4 shortcuts
+    let mut tabs: Vec<Span> = vec![Span::raw(" ")];
+    for (i, name) in SERVICE_DETAIL_TABS.iter().enumerate() {
+        let style = if i == app.service_detail_tab { Style::default().fg(Color::Black).bg(Color::Green).add_modifier(Modifier::BOLD) } else { Style::default().fg(Color::Gray) };
+        tabs.push(Span::styled(format!(" {} {} ", i + 1, name), style));
+        tabs.push(Span::raw(" "));
     }
+    let mut lines: Vec<Line> = vec![Line::from(tabs), Line::from(Span::styled("─".repeat(inner.width as usize), Style::default().fg(Color::DarkGray)))];
+    let body_h = (inner.height as usize).saturating_sub(3);
 
-    // Border and clear
-    f.render_widget(Clear, area);
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .title(format!(" Service: {} ", title))
-        .border_style(Style::default().fg(Color::Green));
-    f.render_widget(block, area);
-
-    // Inner text area
-    let inner = Rect { x: area.x + 1, y: area.y + 1, width: area.width.saturating_sub(2), height: area.height.saturating_sub(2) };
-    let lines: Vec<Line> = lines_raw.into_iter().map(|l| Line::from(Span::raw(format!(" {}", l)))).collect();
-    let paragraph = ratatui::widgets::Paragraph::new(lines);
-    f.render_widget(paragraph, inner);
+    let (hint, shown, total) = if app.service_detail_tab == 2 {
+        // Dependency tree: selection kept in view, units colored by their cached state
+        let direction = if app.service_deps_reverse { "required by (reverse)" } else { "requires/wants" };
+        lines.push(Line::from(vec![
+            Span::styled(format!(" {} ", app.service_deps_root), Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(direction, Style::default().fg(Color::DarkGray)),
+        ]));
+        let rows_h = body_h.saturating_sub(1);
+        let total = app.service_deps.len();
+        let selected = app.service_deps_selected.min(total.saturating_sub(1));
+        let start = if rows_h > 0 && selected >= rows_h { selected + 1 - rows_h } else { 0 };
+        if !app.service_deps_error.is_empty() {
+            for l in app.service_deps_error.lines() { lines.push(Line::from(Span::styled(format!(" {}", l), Style::default().fg(Color::Red)))); }
+        }
+        for (i, row) in app.service_deps.iter().enumerate().skip(start).take(rows_h) {
+            let color = match app.services.iter().find(|u| u.name == row.unit).map(|u| u.active.as_str()) {
+                Some("active") => Color::Green,
+                Some("failed") => Color::Red,
+                Some(_) => Color::Gray,
+                None => Color::Reset,
+            };
+            let mut line = Line::from(vec![
+                Span::styled(format!(" {}", row.prefix), Style::default().fg(Color::DarkGray)),
+                Span::styled(row.unit.clone(), Style::default().fg(color)),
+            ]);
+            if i == selected { line = line.style(Style::default().add_modifier(Modifier::REVERSED)); }
+            lines.push(line);
+        }
+        let hint = " Up/Down select, Enter open dependency, Backspace back, r reverse, Left/Right tabs, j journal, Esc close";
+        (hint, (start + rows_h).min(total), total)
+    } else {
+        let text = if app.service_detail_tab == 0 { &app.service_detail_text } else { &app.service_tab_text };
+        let total = text.lines().count();
+        // Unit file: "# /path" headers in cyan, drop-in files (…/unit.d/*.conf) in yellow
+        let mut drop_in = false;
+        for (i, l) in text.lines().enumerate() {
+            let header = app.service_detail_tab == 1 && l.starts_with("# /");
+            if header { drop_in = is_drop_in_header(l); }
+            if i < app.service_detail_scroll { continue; }
+            if lines.len() >= body_h + 2 { break; }
+            let style = if header {
+                Style::default().fg(if drop_in { Color::Yellow } else { Color::Cyan }).add_modifier(Modifier::BOLD)
+            } else if app.service_detail_tab == 1 && drop_in {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            lines.push(Line::from(Span::styled(format!(" {}", l), style)));
+        }
+        let hint = " Left/Right/Tab or 1-4 switch tab, Up/Down/PgUp/PgDn scroll, j journal, Esc close";
+        (hint, (app.service_detail_scroll + body_h).min(total), total)
+    };
+    while lines.len() < (inner.height as usize).saturating_sub(1) { lines.push(Line::from(Span::raw(""))); }
+    lines.push(Line::from(Span::styled(format!("{}   [{}/{}]", hint, shown, total), Style::default().fg(Color::DarkGray))));
+    f.render_widget(ratatui::widgets::Paragraph::new(lines), inner);
 }
 
-/// Draw the Process Details popup with a dynamic title and multi-line body text.
-pub fn draw_process_popup(f: &mut ratatui::Frame<'_>, size: Rect, title: &str, text: &str) {
-    // Split text into lines and measure width
-    let lines_raw: Vec<&str> = text.split('\n').collect();
-    let max_text_width: u16 = lines_raw.iter().map(|l| l.chars().count() as u16).max().unwrap_or(0).saturating_add(1);
-    // Add some padding and clamp to screen bounds
-    let mut popup_w: u16 = max_text_width.saturating_add(4);
-    if popup_w > size.width { popup_w = size.width; }
-    // Height is based on number of lines (capped to screen)
-    let mut popup_h: u16 = (lines_raw.len() as u16).saturating_add(2);
-    if popup_h > size.height { popup_h = size.height; }
-
-    // Center the popup
-    let popup_x = size.x + (size.width.saturating_sub(popup_w)) / 2;
-    let popup_y = size.y + (size.height.saturating_sub(popup_h)) / 2;
-    let area = Rect { x: popup_x, y: popup_y, width: popup_w, height: popup_h };
+/// Draw the tabbed Process Details popup: live CPU%/RSS sparklines, tab bar, scrolled body
+/// for the active tab, and key hints. An exited process keeps its last snapshot, greyed out.
+pub fn draw_process_popup(f: &mut ratatui::Frame<'_>, size: Rect, app: &App) {
+    let popup_w = size.width.saturating_sub(8).clamp(40, 140);
+    let popup_h = size.height.saturating_sub(4).max(12);
+    let (title, color) = if app.process_detail_exited {
+        (format!("Process: {} - EXITED", app.process_detail_title), Color::Red)
+    } else {
+        (format!("Process: {}", app.process_detail_title), Color::Yellow)
+    };
+    let inner = draw_popup_frame(f, size, popup_w, popup_h, &title, color);
 
-    // Shadow
-    let sx = area.x.saturating_add(1);
-    let sy = area.y.saturating_add(1);
-    if sx < size.x + size.width && sy < size.y + size.height {
-        let sw = area.width.min((size.x + size.width).saturating_sub(sx));
-        let sh = area.height.min((size.y + size.height).saturating_sub(sy));
-        if sw > 0 && sh > 0 {
-            let shadow = Rect { x: sx, y: sy, width: sw, height: sh };
-            let shadow_block = Block::default().style(Style::default().bg(Color::Black));
-            f.render_widget(shadow_block, shadow);
+    // Sparkline rows: label on the left, history (newest on the right) filling the rest
+    let label_w: u16 = 18;
+    let spark_w = inner.width.saturating_sub(label_w + 2) as usize;
+    let cpu_now = app.process_detail_cpu_hist.last().cloned().unwrap_or(0.0);
+    let rss_now = app.process_detail_rss_hist.last().cloned().unwrap_or(0);
+    let cpu_data: Vec<u64> = app.process_detail_cpu_hist.iter().rev().take(spark_w).rev().map(|v| (v * 10.0).round() as u64).collect();
+    let rss_data: Vec<u64> = app.process_detail_rss_hist.iter().rev().take(spark_w).rev().cloned().collect();
+    let (cpu_color, rss_color) = if app.process_detail_exited { (Color::DarkGray, Color::DarkGray) } else { (Color::Green, Color::Magenta) };
+    for (row, label, data, spark_color, max) in [
+        (0u16, format!(" CPU {:>8.1}%", cpu_now), &cpu_data, cpu_color, cpu_data.iter().cloned().max().unwrap_or(0).max(1000)),
+        (1u16, format!(" RSS {:>9}", crate::fmt_bytes(rss_now)), &rss_data, rss_color, rss_data.iter().cloned().max().unwrap_or(1).max(1)),
+    ] {
+        if inner.height <= row { break; }
+        f.render_widget(ratatui::widgets::Paragraph::new(Line::from(Span::styled(label, Style::default().fg(spark_color)))), Rect { x: inner.x, y: inner.y + row, width: label_w.min(inner.width), height: 1 });
+        let area = Rect { x: inner.x + label_w + 1, y: inner.y + row, width: spark_w as u16, height: 1 };
+        if area.width > 0 && area.x < inner.x + inner.width {
+            f.render_widget(ratatui::widgets::Sparkline::default().data(data).max(max).style(Style::default().fg(spark_color)), area);
         }
     }
 
-    // Border and clear
-    f.render_widget(Clear, area);
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .title(format!(" Process: {} ", title))
-        .border_style(Style::default().fg(Color::Yellow));
-    f.render_widget(block, area);
+    // Tab bar: active tab highlighted, with 1..8 shortcuts
+    let mut tabs: Vec<Span> = vec![Span::raw(" ")];
+    for (i, name) in PROCESS_DETAIL_TABS.iter().enumerate() {
+        let style = if i == app.process_detail_tab { Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD) } else { Style::default().fg(Color::Gray) };
+        tabs.push(Span::styled(format!(" {} {} ", i + 1, name), style));
+        tabs.push(Span::raw(" "));
+    }
+    let mut lines: Vec<Line> = vec![Line::from(tabs), Line::from(Span::styled("─".repeat(inner.width as usize), Style::default().fg(Color::DarkGray)))];
+    let body_style = if app.process_detail_exited {
+        lines.push(Line::from(Span::styled(" Process has exited; showing the last known state.", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))));
+        Style::default().fg(Color::DarkGray)
+    } else {
+        Style::default()
+    };
+    let body_h = (inner.height as usize).saturating_sub(2 + lines.len() + 1);
+    let total = app.process_detail_text.lines().count();
+    for l in app.process_detail_text.lines().skip(app.process_detail_scroll).take(body_h) {
+        lines.push(Line::from(Span::styled(format!(" {}", l), body_style)));
+    }
+    while lines.len() < (inner.height as usize).saturating_sub(3) { lines.push(Line::from(Span::raw(""))); }
+    let end = (app.process_detail_scroll + body_h).min(total);
+    let hint = format!(" Left/Right/Tab or 1-{} switch tab, Up/Down/PgUp/PgDn scroll, T threads, Esc close   [{}-{}/{}]", PROCESS_DETAIL_TABS.len(), if total == 0 { 0 } else { app.process_detail_scroll + 1 }, end, total);
+    lines.push(Line::from(Span::styled(hint, Style::default().fg(Color::DarkGray))));
+    let text_area = Rect { x: inner.x, y: inner.y.saturating_add(2), width: inner.width, height: inner.height.saturating_sub(2) };
+    f.render_widget(ratatui::widgets::Paragraph::new(lines), text_area);
+}
 
-    // Inner text area
-    let inner = Rect { x: area.x + 1, y: area.y + 1, width: area.width.saturating_sub(2), height: area.height.saturating_sub(2) };
-    let lines: Vec<Line> = lines_raw.into_iter().map(|l| Line::from(Span::raw(format!(" {}", l)))).collect();
-    let paragraph = ratatui::widgets::Paragraph::new(lines);
-    f.render_widget(paragraph, inner);
+/// Search state for the log popup: matches of `re` are highlighted, `current` stands out,
+/// and `footer` (the search prompt or hint) goes on the bottom border.
+pub struct LogSearchView<'a> {
+    pub re: Option<&'a regex::Regex>,
+    pub current: Option<LogMatch>,
+    pub footer: String,
 }
 
-/// Draw the Log Details popup with a dynamic title and multi-line body text.
-pub fn draw_log_popup(f: &mut ratatui::Frame<'_>, size: Rect, title: &str, text: &str, start_offset: usize) {
-    // Split text into lines and measure width
-    let lines_raw: Vec<&str> = text.split('\n').collect();
-    let max_text_width: u16 = lines_raw.iter().map(|l| l.chars().count() as u16).max().unwrap_or(0).saturating_add(1);
-    let mut popup_w: u16 = max_text_width.saturating_add(4);
-    if popup_w > size.width { popup_w = size.width; }
-    let mut popup_h: u16 = (lines_raw.len() as u16).saturating_add(2);
-    if popup_h > size.height { popup_h = size.height; }
+/// Draw the Log Details popup with a dynamic title over `lines` (offset and text of each visible
+/// line). When `whole` (the document fits) the popup is sized to its content, else it fills the screen.
+pub fn draw_log_popup(f: &mut ratatui::Frame<'_>, size: Rect, title: &str, lines: &[(u64, String)], whole: bool, search: &LogSearchView) {
+    let (mut popup_w, mut popup_h) = (size.width, size.height);
+    if whole {
+        let max_text_width: u16 = lines.iter().map(|(_, l)| l.chars().count() as u16).max().unwrap_or(0).saturating_add(1);
+        // Wide enough for the title and the search prompt on the borders too
+        let max_text_width = max_text_width.max(search.footer.chars().count() as u16).max(title.chars().count() as u16 + 7);
+        popup_w = max_text_width.saturating_add(4).min(size.width);
+        popup_h = (lines.len().max(1) as u16).saturating_add(2).min(size.height);
+    }
     let popup_x = size.x + (size.width.saturating_sub(popup_w)) / 2;
     let popup_y = size.y + (size.height.saturating_sub(popup_h)) / 2;
     let area = Rect { x: popup_x, y: popup_y, width: popup_w, height: popup_h };
@@ -186,29 +266,50 @@ pub fn draw_log_popup(f: &mut ratatui::Frame<'_>, size: Rect, title: &str, text:
         if sw > 0 && sh > 0 { let shadow = Rect { x: sx, y: sy, width: sw, height: sh }; let shadow_block = Block::default().style(Style::default().bg(Color::Black)); f.render_widget(shadow_block, shadow); }
     }
     f.render_widget(Clear, area);
-    let block = Block::default().borders(Borders::ALL).title(format!(" Log: {} ", title)).border_style(Style::default().fg(Color::LightBlue));
+    let mut block = Block::default().borders(Borders::ALL).title(format!(" Log: {} ", title)).border_style(Style::default().fg(Color::LightBlue));
+    if !search.footer.is_empty() { block = block.title_bottom(Span::styled(format!(" {} ", search.footer), Style::default().fg(Color::Yellow))); }
     f.render_widget(block, area);
     let inner = Rect { x: area.x + 1, y: area.y + 1, width: area.width.saturating_sub(2), height: area.height.saturating_sub(2) };
-    // Determine visible window based on start_offset and inner height
-    let total = lines_raw.len();
-    let vis_rows = inner.height as usize;
-    let max_start = total.saturating_sub(vis_rows);
-    let start = start_offset.min(max_start);
-    let end = start.saturating_add(vis_rows).min(total);
-    let lines: Vec<Line> = lines_raw
-        .into_iter()
-        .skip(start)
-        .take(end.saturating_sub(start))
-        .map(|l| Line::from(Span::raw(format!(" {}", l))))
+    let hit = Style::default().fg(Color::Black).bg(Color::Yellow);
+    let current_hit = Style::default().fg(Color::Black).bg(Color::LightRed).add_modifier(Modifier::BOLD);
+    let lines: Vec<Line> = lines
+        .iter()
+        .map(|(at, l)| {
+            let style = severity_style(line_severity(l));
+            let Some(re) = search.re else { return Line::from(Span::styled(format!(" {}", l), style)); };
+            // Split the line around its matches
+            let mut spans = vec![Span::raw(" ")];
+            let mut pos = 0;
+            for m in re.find_iter(l).filter(|m| m.start() < m.end()) {
+                if m.start() > pos { spans.push(Span::styled(&l[pos..m.start()], style)); }
+                let is_current = search.current.is_some_and(|c| c.line == *at && c.start == m.start());
+                spans.push(Span::styled(m.as_str(), if is_current { current_hit } else { hit }));
+                pos = m.end();
+            }
+            if pos < l.len() { spans.push(Span::styled(&l[pos..], style)); }
+            Line::from(spans)
+        })
         .collect();
     let paragraph = ratatui::widgets::Paragraph::new(lines);
     f.render_widget(paragraph, inner);
 }
 
-/// Draw a sudo password prompt popup for Logs, with masked input and error line.
-pub fn draw_logs_password_prompt(f: &mut ratatui::Frame<'_>, size: Rect, error_text: &str, chars_len: usize) {
+/// Log line color by severity (unknown and info lines keep the default color).
+fn severity_style(severity: Option<Severity>) -> Style {
+    match severity {
+        Some(Severity::Critical) => Style::default().fg(Color::LightRed).add_modifier(Modifier::BOLD),
+        Some(Severity::Error) => Style::default().fg(Color::Red),
+        Some(Severity::Warning) => Style::default().fg(Color::Yellow),
+        Some(Severity::Notice) => Style::default().fg(Color::Cyan),
+        Some(Severity::Debug) => Style::default().fg(Color::DarkGray),
+        Some(Severity::Info) | None => Style::default(),
+    }
+}
+
+/// Draw a sudo password prompt popup (Logs, process actions), with masked input and error line.
+pub fn draw_logs_password_prompt(f: &mut ratatui::Frame<'_>, size: Rect, prompt: &str, error_text: &str, chars_len: usize) {
     let lines = vec![
-        Line::from(Span::raw("Enter sudo password to read protected logs:")),
+        Line::from(Span::raw(prompt.to_string())),
         Line::from(Span::raw(" ")), // spacer
         Line::from(Span::raw("Password: ")), // input line label
         Line::from(Span::raw(" ")), // spacer
@@ -245,3 +346,254 @@ pub fn draw_logs_password_prompt(f: &mut ratatui::Frame<'_>, size: Rect, error_t
     let paragraph = ratatui::widgets::Paragraph::new(display_lines);
     f.render_widget(paragraph, inner);
 }
+
+/// Center a `w` x `h` popup in `size`, draw its shadow, clear it and draw a bordered
+/// block with `title`. Returns the inner area.
+fn draw_popup_frame(f: &mut ratatui::Frame<'_>, size: Rect, w: u16, h: u16, title: &str, color: Color) -> Rect {
+    let popup_w = w.min(size.width);
+    let popup_h = h.min(size.height);
+    let popup_x = size.x + (size.width.saturating_sub(popup_w)) / 2;
+    let popup_y = size.y + (size.height.saturating_sub(popup_h)) / 2;
+    let area = Rect { x: popup_x, y: popup_y, width: popup_w, height: popup_h };
+    let sx = area.x.saturating_add(1);
+    let sy = area.y.saturating_add(1);
+    if sx < size.x + size.width && sy < size.y + size.height {
+        let sw = area.width.min((size.x + size.width).saturating_sub(sx));
+        let sh = area.height.min((size.y + size.height).saturating_sub(sy));
+        if sw > 0 && sh > 0 { let shadow = Rect { x: sx, y: sy, width: sw, height: sh }; let shadow_block = Block::default().style(Style::default().bg(Color::Black)); f.render_widget(shadow_block, shadow); }
+    }
+    f.render_widget(Clear, area);
+    let block = Block::default().borders(Borders::ALL).title(format!(" {} ", title)).border_style(Style::default().fg(color));
+    f.render_widget(block, area);
+    Rect { x: area.x + 1, y: area.y + 1, width: area.width.saturating_sub(2), height: area.height.saturating_sub(2) }
+}
+
+/// Draw the top/htop signal menu: signal list, custom number entry, confirmation, or result.
+pub fn draw_signal_popup(f: &mut ratatui::Frame<'_>, size: Rect, app: &App) {
+    let target = if app.signal_proc_name.is_empty() { format!("PID {}", app.signal_pid) } else { format!("{} (PID {})", app.signal_proc_name, app.signal_pid) };
+    let mut lines: Vec<Line> = Vec::new();
+    match app.signal_stage {
+        SignalStage::Menu => {
+            lines.push(Line::from(Span::raw(format!("Send signal to {}:", target))));
+            lines.push(Line::from(Span::raw(" ")));
+            for (i, (name, num)) in SIGNALS.iter().enumerate() {
+                let text = format!("  {:>2}  SIG{:<6}", num, name);
+                let style = if i == app.signal_selected { Style::default().add_modifier(Modifier::REVERSED) } else { Style::default() };
+                lines.push(Line::from(Span::styled(text, style)));
+            }
+            let style = if app.signal_selected >= SIGNALS.len() { Style::default().add_modifier(Modifier::REVERSED) } else { Style::default() };
+            lines.push(Line::from(Span::styled("      Other (number)...", style)));
+            lines.push(Line::from(Span::raw(" ")));
+            lines.push(Line::from(Span::styled("Up/Down select, Enter choose, Esc cancel", Style::default().fg(Color::DarkGray))));
+        }
+        SignalStage::Custom => {
+            lines.push(Line::from(Span::raw(format!("Send signal to {}:", target))));
+            lines.push(Line::from(Span::raw(" ")));
+            lines.push(Line::from(Span::raw(format!("Signal number: {}_", app.signal_custom_input))));
+            lines.push(Line::from(Span::raw(" ")));
+            lines.push(Line::from(Span::styled(app.signal_result.clone(), Style::default().fg(Color::Red))));
+            lines.push(Line::from(Span::styled("Enter confirm, Esc back", Style::default().fg(Color::DarkGray))));
+        }
+        SignalStage::Confirm => {
+            lines.push(Line::from(Span::raw(format!("Send {} to {}?", signal_name(app.signal_number), target))));
+            lines.push(Line::from(Span::raw(" ")));
+            lines.push(Line::from(Span::styled("y/Enter send, n/Esc back", Style::default().fg(Color::DarkGray))));
+        }
+        SignalStage::Result => {
+            let color = if app.signal_result.starts_with("Sent") { Color::Green } else { Color::Red };
+            lines.push(Line::from(Span::styled(app.signal_result.clone(), Style::default().fg(color))));
+            lines.push(Line::from(Span::raw(" ")));
+            let hint = if app.signal_denied { "s retry with sudo, Esc/Enter close" } else { "Esc/Enter close" };
+            lines.push(Line::from(Span::styled(hint, Style::default().fg(Color::DarkGray))));
+        }
+    }
+    let max_text_width: u16 = lines.iter().map(|l| l.width() as u16).max().unwrap_or(0).saturating_add(1);
+    let popup_w = max_text_width.saturating_add(4).max(40);
+    let popup_h = (lines.len() as u16).saturating_add(2);
+    let inner = draw_popup_frame(f, size, popup_w, popup_h, "Signal", Color::Red);
+    let padded: Vec<Line> = lines.into_iter().map(|l| { let mut spans = vec![Span::raw(" ")]; spans.extend(l.spans); Line::from(spans) }).collect();
+    f.render_widget(ratatui::widgets::Paragraph::new(padded), inner);
+}
+
+/// Scheduling popup for the selected process: nice value, CPU affinity or I/O priority.
+pub fn draw_tune_popup(f: &mut ratatui::Frame<'_>, size: Rect, app: &App) {
+    let target = if app.tune_proc_name.is_empty() { format!("PID {}", app.tune_pid) } else { format!("{} (PID {})", app.tune_proc_name, app.tune_pid) };
+    let hint_style = Style::default().fg(Color::DarkGray);
+    let sel = Style::default().add_modifier(Modifier::REVERSED);
+    let mut lines: Vec<Line> = Vec::new();
+    let title = match app.tune_mode {
+        TuneMode::Nice => "Renice",
+        TuneMode::Affinity => "CPU Affinity",
+        TuneMode::IoPrio => "I/O Priority",
+    };
+    lines.push(Line::from(Span::raw(format!("{} for {}", title, target))));
+    lines.push(Line::from(Span::raw(" ")));
+    if !app.tune_result.is_empty() {
+        let color = if app.tune_result.starts_with("Set ") { Color::Green } else { Color::Red };
+        lines.push(Line::from(Span::styled(app.tune_result.clone(), Style::default().fg(color))));
+        lines.push(Line::from(Span::raw(" ")));
+        let hint = if app.tune_denied { "s retry with sudo, Esc/Enter close" } else { "Esc/Enter close" };
+        lines.push(Line::from(Span::styled(hint, hint_style)));
+    } else {
+        match app.tune_mode {
+            TuneMode::Nice => {
+                // Simple bar from -20 (left) to 19 (right)
+                let pos = (app.tune_nice + 20).clamp(0, 39) as usize;
+                let bar: String = (0..40).map(|i| if i == pos { '|' } else { '-' }).collect();
+                lines.push(Line::from(vec![Span::raw("Nice: "), Span::styled(format!("{:>3}", app.tune_nice), sel)]));
+                lines.push(Line::from(Span::styled(format!("-20 {} 19", bar), hint_style)));
+                lines.push(Line::from(Span::raw(" ")));
+                lines.push(Line::from(Span::styled("Up/+ lower priority, Down/- higher priority, PgUp/PgDn +/-5", hint_style)));
+            }
+            TuneMode::Affinity => {
+                // Same layout as the per-core gauges: up to 8 rows, filled column by column
+                let n = app.tune_cpus.len();
+                let rows = n.clamp(1, 8);
+                let cols = n.div_ceil(rows);
+                for r in 0..rows {
+                    let mut spans: Vec<Span> = Vec::new();
+                    for c in 0..cols {
+                        let idx = r + c * rows;
+                        if idx >= n { break; }
+                        let mark = if app.tune_cpus[idx] { "[x]" } else { "[ ]" };
+                        let style = if idx == app.tune_cpu_cursor { sel } else { Style::default() };
+                        spans.push(Span::styled(format!("{} CPU C{:<3}", mark, idx), style));
+                        spans.push(Span::raw("  "));
+                    }
+                    lines.push(Line::from(spans));
+                }
+                lines.push(Line::from(Span::raw(" ")));
+                let count = app.tune_cpus.iter().filter(|c| **c).count();
+                lines.push(Line::from(Span::raw(format!("{}/{} CPUs selected", count, n))));
+                lines.push(Line::from(Span::styled("Arrows move, Space toggle, a all/none", hint_style)));
+            }
+            TuneMode::IoPrio => {
+                let mut spans: Vec<Span> = vec![Span::raw("Class: ")];
+                for (i, name) in IO_CLASSES.iter().enumerate() {
+                    let style = if i == app.tune_io_class as usize { sel } else { Style::default() };
+                    spans.push(Span::styled(format!(" {} ", name), style));
+                }
+                lines.push(Line::from(spans));
+                // The level only applies to the realtime and best-effort classes
+                if app.tune_io_class == 1 || app.tune_io_class == 2 {
+                    lines.push(Line::from(vec![Span::raw("Level: "), Span::styled(format!(" {} ", app.tune_io_level), sel), Span::styled("  (0 highest, 7 lowest)", hint_style)]));
+                } else {
+                    lines.push(Line::from(Span::styled("Level: n/a for this class", hint_style)));
+                }
+                lines.push(Line::from(Span::raw(" ")));
+                lines.push(Line::from(Span::styled("Left/Right class, Up/Down level", hint_style)));
+            }
+        }
+        let threads = if app.tune_all_threads { "[x]" } else { "[ ]" };
+        lines.push(Line::from(Span::raw(format!("{} Apply to all threads (t)", threads))));
+        lines.push(Line::from(Span::raw(" ")));
+        lines.push(Line::from(Span::styled("Enter apply, Esc cancel", hint_style)));
+    }
+    let max_text_width: u16 = lines.iter().map(|l| l.width() as u16).max().unwrap_or(0).saturating_add(1);
+    let popup_w = max_text_width.saturating_add(4).max(40);
+    let popup_h = (lines.len() as u16).saturating_add(2);
+    let inner = draw_popup_frame(f, size, popup_w, popup_h, title, Color::Yellow);
+    let padded: Vec<Line> = lines.into_iter().map(|l| { let mut spans = vec![Span::raw(" ")]; spans.extend(l.spans); Line::from(spans) }).collect();
+    f.render_widget(ratatui::widgets::Paragraph::new(padded), inner);
+}
+
+/// Thread view popup: one row per TID with name, state, CPU% over the last tick, and last CPU.
+pub fn draw_threads_popup(f: &mut ratatui::Frame<'_>, size: Rect, app: &App) {
+    let count = app.threads_rows.len();
+    let title = if app.threads_exited {
+        format!("Threads: {} - exited", app.threads_title)
+    } else {
+        format!("Threads: {} - {} thread{}", app.threads_title, count, if count == 1 { "" } else { "s" })
+    };
+    let popup_w = (size.width.saturating_sub(4)).min(64);
+    let popup_h = ((count as u16).saturating_add(5)).min(size.height.saturating_sub(2)).max(7);
+    let color = if app.threads_exited { Color::DarkGray } else { Color::Yellow };
+    let inner = draw_popup_frame(f, size, popup_w, popup_h, &title, color);
+
+    let header_style = Style::default().fg(Color::Black).bg(Color::Cyan);
+    let name_w = (inner.width as usize).saturating_sub(8 + 1 + 2 + 1 + 6 + 1 + 4 + 2).max(4);
+    let mut lines: Vec<Line> = Vec::new();
+    let header = format!(" {:>7} {:<name_w$} {:>2} {:>6} {:>4}", "TID", "NAME", "S", "CPU%", "CPU", name_w = name_w);
+    lines.push(Line::from(Span::styled(format!("{:<w$}", header, w = inner.width as usize), header_style)));
+    let body_h = (inner.height as usize).saturating_sub(2);
+    for t in app.threads_rows.iter().skip(app.threads_scroll).take(body_h) {
+        let cpu = app.threads_cpu.get(&t.tid).cloned().unwrap_or(0.0);
+        let name: String = t.name.chars().take(name_w).collect();
+        let state_color = match t.state {
+            'R' => Color::Green,
+            'D' => Color::Red,
+            'Z' | 'X' => Color::DarkGray,
+            _ => Color::White,
+        };
+        let style = if app.threads_exited { Style::default().fg(Color::DarkGray) } else { Style::default() };
+        let cpu_style = if app.threads_exited { style } else if cpu >= 50.0 { Style::default().fg(Color::Red) } else if cpu >= 10.0 { Style::default().fg(Color::Yellow) } else { style };
+        let last_cpu = if t.last_cpu >= 0 { t.last_cpu.to_string() } else { String::from("-") };
+        lines.push(Line::from(vec![
+            Span::styled(format!(" {:>7} {:<name_w$} ", t.tid, name, name_w = name_w), style),
+            Span::styled(format!("{:>2}", t.state), if app.threads_exited { style } else { Style::default().fg(state_color) }),
+            Span::styled(format!(" {:>6.1}", cpu), cpu_style),
+            Span::styled(format!(" {:>4}", last_cpu), style),
+        ]));
+    }
+    if app.threads_rows.is_empty() {
+        lines.push(Line::from(Span::raw(" (no threads readable)")));
+    }
+    // Footer hint pinned to the last inner line
+    while lines.len() < (inner.height as usize).saturating_sub(1) { lines.push(Line::from(Span::raw(""))); }
+    let hint = if app.threads_exited { " Process exited. Esc/Enter close" } else { " Up/Down/PgUp/PgDn scroll, Esc/Enter close" };
+    lines.push(Line::from(Span::styled(hint, Style::default().fg(Color::DarkGray))));
+    f.render_widget(ratatui::widgets::Paragraph::new(lines), inner);
+}
+
+/// Column chooser for the process table: checklist in display order with reorder hints.
+pub fn draw_columns_popup(f: &mut ratatui::Frame<'_>, size: Rect, app: &App) {
+    let hint_style = Style::default().fg(Color::DarkGray);
+    let mut lines: Vec<Line> = Vec::new();
+    for (i, (col, on)) in app.columns_edit.iter().enumerate() {
+        let mark = if *on { "[x]" } else { "[ ]" };
+        let style = if i == app.columns_cursor { Style::default().add_modifier(Modifier::REVERSED) } else if *on { Style::default() } else { hint_style };
+        lines.push(Line::from(Span::styled(format!(" {} {:<10} {}", mark, col.label(), col.description()), style)));
+    }
+    lines.push(Line::from(Span::raw(" ")));
+    if !app.columns_error.is_empty() {
+        lines.push(Line::from(Span::styled(app.columns_error.clone(), Style::default().fg(Color::Red))));
+    }
+    lines.push(Line::from(Span::styled("Space show/hide, [ ] move up/down, r defaults", hint_style)));
+    lines.push(Line::from(Span::styled("Enter apply and save, Esc cancel", hint_style)));
+    let max_text_width: u16 = lines.iter().map(|l| l.width() as u16).max().unwrap_or(0).saturating_add(1);
+    let popup_w = max_text_width.saturating_add(4).max(40);
+    let popup_h = (lines.len() as u16).saturating_add(2);
+    // Keep the cursor row visible when the terminal is shorter than the list
+    let visible = size.height.saturating_sub(2) as usize;
+    let skip = (app.columns_cursor + 1).saturating_sub(visible.saturating_sub(4));
+    let inner = draw_popup_frame(f, size, popup_w, popup_h, "Process Columns", Color::Cyan);
+    let padded: Vec<Line> = lines.into_iter().skip(skip).map(|l| { let mut spans = vec![Span::raw(" ")]; spans.extend(l.spans); Line::from(spans) }).collect();
+    f.render_widget(ratatui::widgets::Paragraph::new(padded), inner);
+}
+
+/// Services tab action menu: list of systemctl actions, then a confirmation step.
+pub fn draw_service_action_popup(f: &mut ratatui::Frame<'_>, size: Rect, app: &App) {
+    let hint_style = Style::default().fg(Color::DarkGray);
+    let mut lines: Vec<Line> = Vec::new();
+    let action = SERVICE_ACTIONS.get(app.service_action_selected).cloned().unwrap_or("");
+    if app.service_action_confirm {
+        lines.push(Line::from(vec![Span::raw("Run "), Span::styled(format!("systemctl {} {}", action, app.service_action_unit), Style::default().add_modifier(Modifier::BOLD)), Span::raw("?")]));
+        lines.push(Line::from(Span::raw(" ")));
+        lines.push(Line::from(Span::styled("y/Enter run, n/Esc back", hint_style)));
+    } else {
+        lines.push(Line::from(Span::raw(format!("Action for {}:", app.service_action_unit))));
+        lines.push(Line::from(Span::raw(" ")));
+        for (i, name) in SERVICE_ACTIONS.iter().enumerate() {
+            let style = if i == app.service_action_selected { Style::default().add_modifier(Modifier::REVERSED) } else { Style::default() };
+            lines.push(Line::from(Span::styled(format!("  {:<10}", name), style)));
+        }
+        lines.push(Line::from(Span::raw(" ")));
+        lines.push(Line::from(Span::styled("Up/Down select, Enter choose, Esc cancel", hint_style)));
+    }
+    let max_text_width: u16 = lines.iter().map(|l| l.width() as u16).max().unwrap_or(0).saturating_add(1);
+    let popup_w = max_text_width.saturating_add(4).max(40);
+    let popup_h = (lines.len() as u16).saturating_add(2);
+    let inner = draw_popup_frame(f, size, popup_w, popup_h, "Unit Action", Color::Green);
+    let padded: Vec<Line> = lines.into_iter().map(|l| { let mut spans = vec![Span::raw(" ")]; spans.extend(l.spans); Line::from(spans) }).collect();
+    f.render_widget(ratatui::widgets::Paragraph::new(padded), inner);
+}